@@ -0,0 +1,52 @@
+//! The paths the `unsafe` feature skips bounds checks on, to compare builds with and without it.
+
+use btreelist::BTreeList;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// Pseudo-random indices below `len`, the same on every run.
+fn indices(count: usize, len: usize) -> Vec<usize> {
+    let mut seed = 7usize;
+    (0..count)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) % len
+        })
+        .collect()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_paths");
+    for len in [1_000, 1_000_000] {
+        let list = (0..len).collect::<BTreeList<usize>>();
+        let lookups = indices(10_000, len);
+        group.bench_with_input(BenchmarkId::new("get", len), &lookups, |b, lookups| {
+            b.iter(|| {
+                lookups
+                    .iter()
+                    .map(|&i| *list.get(i).unwrap())
+                    .fold(0usize, usize::wrapping_add)
+            })
+        });
+
+        // inserting at random places descends to a leaf and splits the full nodes on the way
+        let inserts = indices(10_000, len);
+        group.bench_with_input(BenchmarkId::new("insert", len), &inserts, |b, inserts| {
+            b.iter_batched(
+                || list.clone(),
+                |mut list| {
+                    for &i in inserts {
+                        list.insert(i, i).unwrap();
+                    }
+                    black_box(list)
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
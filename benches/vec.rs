@@ -10,7 +10,7 @@ macro_rules! empty {
         BTreeVec::new()
     };
     (btl) => {
-        BTreeList::default()
+        BTreeList::<_>::default()
     };
 }
 
@@ -0,0 +1,5 @@
+fn main() {
+    // `release` is set by hand through `RUSTFLAGS` to run the slower property tests. Declaring it
+    // keeps newer toolchains from warning about an unexpected cfg, while older ones ignore this.
+    println!("cargo:rustc-check-cfg=cfg(release)");
+}
@@ -0,0 +1,51 @@
+use arbitrary::{size_hint, Arbitrary, Result, Unstructured};
+
+use crate::BTreeList;
+
+/// Generates lists built in one of several ways, so that the same elements can come out as trees
+/// of different fills and depths: filled bottom-up, pushed onto either end, or inserted at
+/// arbitrary positions.
+impl<'a, T, const B: usize> Arbitrary<'a> for BTreeList<T, B>
+where
+    T: Arbitrary<'a>,
+{
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let shape = u.choose_index(4)?;
+        let elements = u.arbitrary_iter::<T>()?.collect::<Result<Vec<_>>>()?;
+        let len = elements.len();
+        let mut list = Self::new();
+        match shape {
+            0 => list = Self::from_exact_iter(elements.into_iter(), len),
+            1 => elements.into_iter().for_each(|e| list.push_back(e)),
+            2 => elements.into_iter().rev().for_each(|e| list.push_front(e)),
+            _ => {
+                for element in elements {
+                    let index = u.int_in_range(0..=list.len())?;
+                    let _ = list.insert(index, element);
+                }
+            }
+        }
+        Ok(list)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        size_hint::and(<u32 as Arbitrary<'a>>::size_hint(depth), (0, None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::{assert_shape, TestRng};
+
+    #[test]
+    fn arbitrary_shapes() {
+        let mut rng = TestRng::new(3);
+        for _ in 0..200 {
+            let bytes = (0..2000).map(|_| rng.next() as u8).collect::<Vec<_>>();
+            let t = BTreeList::<u8, 2>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            assert_shape(&t);
+            assert_eq!(t.iter().count(), t.len());
+        }
+    }
+}
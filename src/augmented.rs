@@ -0,0 +1,1151 @@
+use std::{
+    fmt,
+    iter::FromIterator,
+    mem,
+    ops::{Add, Bound, RangeBounds, Sub},
+};
+
+use crate::{
+    node::{TreeNode, TreeNodeMut},
+    BTreeList,
+};
+
+/// A way of summarising runs of elements, cached in each node of an [`AugmentedList`].
+///
+/// Summaries form a monoid: [`combine`](Self::combine) must be associative with
+/// [`empty`](Self::empty) as its identity, so that the summary of a run doesn't depend on how it
+/// is split up between nodes. Lengths, sums, counts of matching elements and the metrics of a
+/// rope all fit.
+///
+/// ```
+/// # use btreelist::{AugmentedList, Measure};
+/// /// The number of lines and characters in a run of lines.
+/// struct Lines;
+///
+/// impl Measure<String> for Lines {
+///     type Summary = (usize, usize);
+///
+///     fn empty() -> Self::Summary {
+///         (0, 0)
+///     }
+///
+///     fn measure(line: &String) -> Self::Summary {
+///         (1, line.chars().count())
+///     }
+///
+///     fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary {
+///         (left.0 + right.0, left.1 + right.1)
+///     }
+/// }
+///
+/// let lines = ["one", "two", "three"].iter().map(|line| line.to_string());
+/// let list = lines.collect::<AugmentedList<_, Lines>>();
+/// assert_eq!(list.summary(), (3, 11));
+/// // the line containing the 7th character
+/// assert_eq!(list.find_index(|&(_, chars)| chars > 7), Some(2));
+/// ```
+pub trait Measure<T> {
+    /// The summary of a run of elements.
+    type Summary: Clone;
+
+    /// The summary of no elements.
+    fn empty() -> Self::Summary;
+
+    /// The summary of a single element.
+    fn measure(element: &T) -> Self::Summary;
+
+    /// The summary of the run summarised by `left` followed by the one summarised by `right`.
+    fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+}
+
+/// The [`Measure`] summing numeric elements, for the `prefix_sum` and `range_sum` of an
+/// [`AugmentedList`].
+///
+/// The sum of no elements is taken to be [`Default::default`], which is zero for the numeric
+/// types.
+#[derive(Debug, Clone, Copy)]
+pub struct Sum;
+
+impl<T> Measure<T> for Sum
+where
+    T: Clone + Default + Add<Output = T>,
+{
+    type Summary = T;
+
+    fn empty() -> T {
+        T::default()
+    }
+
+    fn measure(element: &T) -> T {
+        element.clone()
+    }
+
+    fn combine(left: &T, right: &T) -> T {
+        left.clone() + right.clone()
+    }
+}
+
+/// A list caching a user defined [`Measure`] of its elements in each node.
+///
+/// The tree has the same shape as a [`BTreeList`], with each node also holding the summary of
+/// its subtree, kept up to date as elements are inserted, removed and updated in
+/// `O(B log(n))` time. The summary of any prefix or range of the list can then be found in
+/// `O(B log(n))` time, and the list can be searched by summary, such as for the element at a
+/// given offset in a rope or with a given cumulative weight.
+///
+/// Elements are changed through [`set`](Self::set) and [`update`](Self::update), which keep the
+/// summaries up to date. Those borrowed with [`get_mut`](Self::get_mut) leave the summaries
+/// stale until [`update_measure`](Self::update_measure) is called for them.
+///
+/// ```
+/// # use btreelist::{AugmentedList, Sum};
+/// let mut list = (1..=10).collect::<AugmentedList<u32, Sum>>();
+/// assert_eq!(list.summary(), 55);
+/// assert_eq!(list.range_summary(2..5), Some(3 + 4 + 5));
+///
+/// list.update(0, |e| *e += 100);
+/// list.remove(9);
+/// assert_eq!(list.summary(), 145);
+/// ```
+pub struct AugmentedList<T, M, const B: usize = 6>
+where
+    M: Measure<T>,
+{
+    root_node: Option<AugmentedNode<T, M, B>>,
+}
+
+struct AugmentedNode<T, M, const B: usize>
+where
+    M: Measure<T>,
+{
+    elements: Vec<T>,
+    children: Vec<AugmentedNode<T, M, B>>,
+    length: usize,
+    summary: M::Summary,
+}
+
+impl<T, M, const B: usize> AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+{
+    /// Construct a new, empty [`AugmentedList`].
+    ///
+    /// ```
+    /// # use btreelist::AugmentedList;
+    /// # use btreelist::Sum;
+    /// let list: AugmentedList<u32, Sum> = AugmentedList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        let () = BTreeList::<T, B>::VALID_B;
+        Self { root_node: None }
+    }
+
+    /// Get the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.root_node.as_ref().map_or(0, |root| root.length)
+    }
+
+    /// Check if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Create an iterator through the list's elements.
+    pub fn iter(&self) -> AugmentedIter<'_, T, M, B> {
+        AugmentedIter {
+            inner: self,
+            index: 0,
+            index_back: self.len(),
+        }
+    }
+
+    /// Get the element at `index`, if it is in the list.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.root_node.as_ref().and_then(|root| root.get(index))
+    }
+
+    /// Get the element at `index` mutably, if it is in the list.
+    ///
+    /// The summaries aren't updated for changes made through the reference, so call
+    /// [`update_measure`](Self::update_measure) after, or use [`update`](Self::update) which does
+    /// both.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.root_node.as_mut().and_then(|root| root.get_mut(index))
+    }
+
+    /// The measure of the element at `index`, if it is in the list.
+    pub fn measure_of(&self, index: usize) -> Option<M::Summary> {
+        self.get(index).map(M::measure)
+    }
+
+    /// Measure the element at `index` again and update the summaries above it, after it was
+    /// changed through [`get_mut`](Self::get_mut) or interior mutability, taking `O(B log(n))`
+    /// time. Returns false if the index isn't in the list.
+    ///
+    /// ```
+    /// # use btreelist::{AugmentedList, Sum};
+    /// let mut list = (1..=10).collect::<AugmentedList<u32, Sum>>();
+    /// *list.get_mut(0).unwrap() = 100;
+    /// assert_eq!(list.measure_of(0), Some(100));
+    /// assert_eq!(list.summary(), 55);
+    ///
+    /// assert!(list.update_measure(0));
+    /// assert_eq!(list.summary(), 154);
+    /// ```
+    pub fn update_measure(&mut self, index: usize) -> bool {
+        self.update(index, |_| ()).is_some()
+    }
+
+    /// Get the first element of the list, if it isn't empty.
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Get the last element of the list, if it isn't empty.
+    pub fn last(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Insert the `element` into the list at `index`, returning it back if the index is past the
+    /// end of the list.
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        if index > self.len() {
+            return Err(element);
+        }
+        match self.root_node.as_mut() {
+            Some(root) => {
+                if let Some((median, sibling)) = root.insert(index, element) {
+                    let old_root = self.root_node.take().expect("root was just edited");
+                    self.root_node = Some(AugmentedNode::from_parts(
+                        vec![median],
+                        vec![old_root, sibling],
+                    ));
+                }
+            }
+            None => {
+                self.root_node = Some(AugmentedNode::from_parts(vec![element], Vec::new()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Push the `element` onto the back of the list.
+    pub fn push_back(&mut self, element: T) {
+        let _ = self.insert(self.len(), element);
+    }
+
+    /// Remove the element at `index` from the list, returning it if the index was in the list.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let root = self.root_node.as_mut()?;
+        let element = root.remove(index);
+        if root.elements.is_empty() {
+            // the root has run out of elements, leaving at most a single child to take its place
+            self.root_node = root.children.pop();
+        }
+        Some(element)
+    }
+
+    /// Remove the last element of the list, returning it if the list wasn't empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.len().checked_sub(1)?)
+    }
+
+    /// Split the list in two at `at`, returning the elements from `at` onwards, or `None` if it
+    /// is past the end of the list.
+    ///
+    /// The tree is cut along the path to `at` and the pieces either side of it joined back up,
+    /// recomputing the summaries only on that path, taking `O(B log(n))` time.
+    pub fn split_off(&mut self, at: usize) -> Option<Self> {
+        if at > self.len() {
+            return None;
+        }
+        let (left, right) = match self.root_node.take() {
+            Some(root) => root.split_at(at),
+            None => (None, None),
+        };
+        self.root_node = left;
+        Some(Self { root_node: right })
+    }
+
+    /// Replace the element at `index` with `element`, returning the old element, or returning
+    /// `element` back if the index is not in the list.
+    pub fn set(&mut self, index: usize, element: T) -> Result<T, T> {
+        match self.root_node.as_mut() {
+            Some(root) if index < root.length => {
+                Ok(root.update(index, |e| mem::replace(e, element)))
+            }
+            _ => Err(element),
+        }
+    }
+
+    /// Change the element at `index` with `f`, updating the summaries after, and returning what
+    /// `f` returns if the index is in the list.
+    ///
+    /// ```
+    /// # use btreelist::AugmentedList;
+    /// # use btreelist::Sum;
+    /// let mut list = (1..4).collect::<AugmentedList<u32, Sum>>();
+    /// assert_eq!(list.update(1, |e| { *e *= 10; *e }), Some(20));
+    /// assert_eq!(list.update(3, |e| *e), None);
+    /// assert_eq!(list.summary(), 24);
+    /// ```
+    pub fn update<R, F>(&mut self, index: usize, f: F) -> Option<R>
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        match self.root_node.as_mut() {
+            Some(root) if index < root.length => Some(root.update(index, f)),
+            _ => None,
+        }
+    }
+
+    /// The summary of the whole list.
+    pub fn summary(&self) -> M::Summary {
+        self.root_node
+            .as_ref()
+            .map_or_else(M::empty, |root| root.summary.clone())
+    }
+
+    /// The summary of the first `len` elements of the list, or `None` if the list is shorter.
+    ///
+    /// ```
+    /// # use btreelist::AugmentedList;
+    /// # use btreelist::Sum;
+    /// let list = (1..=10).collect::<AugmentedList<u32, Sum>>();
+    /// assert_eq!(list.prefix_summary(3), Some(6));
+    /// assert_eq!(list.prefix_summary(11), None);
+    /// ```
+    pub fn prefix_summary(&self, len: usize) -> Option<M::Summary> {
+        self.range_summary(..len)
+    }
+
+    /// The summary of the elements in `range`, or `None` if it isn't in the list.
+    pub fn range_summary<R>(&self, range: R) -> Option<M::Summary>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            return None;
+        }
+        let mut summary = M::empty();
+        if let Some(root) = self.root_node.as_ref() {
+            root.range_summary(start, end, &mut summary);
+        }
+        Some(summary)
+    }
+
+    /// Find the first index where `pred` holds for the summary of the elements up to and
+    /// including it, or `None` if it doesn't hold for the whole list.
+    ///
+    /// `pred` must be false for some prefix of the list and true for the rest, like with
+    /// [`slice::partition_point`], which lets the search descend the tree in `O(B log(n))` time.
+    pub fn find_index<P>(&self, mut pred: P) -> Option<usize>
+    where
+        P: FnMut(&M::Summary) -> bool,
+    {
+        let root = self.root_node.as_ref()?;
+        let mut summary = M::empty();
+        if !pred(&root.summary) {
+            return None;
+        }
+        Some(root.find_index(&mut summary, &mut pred))
+    }
+}
+
+impl<T, const B: usize> AugmentedList<T, Sum, B>
+where
+    T: Clone + Default + Add<Output = T>,
+{
+    /// The sum of the first `len` elements, or `None` if the list is shorter, taking
+    /// `O(B log(n))` time.
+    ///
+    /// ```
+    /// # use btreelist::{AugmentedList, Sum};
+    /// let mut list = (1..=100).collect::<AugmentedList<u64, Sum>>();
+    /// assert_eq!(list.prefix_sum(10), Some(55));
+    /// list.set(0, 11).unwrap();
+    /// assert_eq!(list.prefix_sum(10), Some(65));
+    /// assert_eq!(list.prefix_sum(101), None);
+    /// ```
+    pub fn prefix_sum(&self, len: usize) -> Option<T> {
+        self.prefix_summary(len)
+    }
+
+    /// The sum of the elements in `range`, or `None` if it isn't in the list, taking
+    /// `O(B log(n))` time.
+    ///
+    /// ```
+    /// # use btreelist::{AugmentedList, Sum};
+    /// let mut list = (1..=100).collect::<AugmentedList<i64, Sum>>();
+    /// assert_eq!(list.range_sum(10..20), Some(155));
+    /// list.insert(15, -1000).unwrap();
+    /// assert_eq!(list.range_sum(10..=20), Some(-845));
+    /// assert_eq!(list.range_sum(..), Some(4050));
+    /// assert_eq!(list.range_sum(100..102), None);
+    /// ```
+    pub fn range_sum<R>(&self, range: R) -> Option<T>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.range_summary(range)
+    }
+}
+
+impl<T, M, const B: usize> AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+    M::Summary: PartialOrd,
+{
+    /// Choose an element at random with probability proportional to its weight, where the
+    /// summaries are the total weights of the elements, taking `O(B log(n))` time.
+    ///
+    /// `rng` is given the total weight of the list and should return a point chosen uniformly
+    /// from zero up to (but not including) it, using whichever source of randomness suits. The
+    /// element whose span of weight holds the point is returned, or `None` if the point is past
+    /// the total weight, such as when the list is empty.
+    ///
+    /// ```
+    /// # use btreelist::{AugmentedList, Measure};
+    /// struct Task {
+    ///     name: &'static str,
+    ///     weight: f64,
+    /// }
+    ///
+    /// struct Weight;
+    ///
+    /// impl Measure<Task> for Weight {
+    ///     type Summary = f64;
+    ///
+    ///     fn empty() -> f64 {
+    ///         0.0
+    ///     }
+    ///
+    ///     fn measure(task: &Task) -> f64 {
+    ///         task.weight
+    ///     }
+    ///
+    ///     fn combine(left: &f64, right: &f64) -> f64 {
+    ///         left + right
+    ///     }
+    /// }
+    ///
+    /// let mut tasks = AugmentedList::<Task, Weight>::new();
+    /// tasks.push_back(Task { name: "rare", weight: 1.0 });
+    /// tasks.push_back(Task { name: "common", weight: 9.0 });
+    ///
+    /// // a point from a random number generator, fixed here to stay repeatable
+    /// let random = 0.5;
+    /// let task = tasks.choose_weighted(|total| random * total).unwrap();
+    /// assert_eq!(task.name, "common");
+    ///
+    /// tasks.update(1, |task| task.weight = 0.0);
+    /// let task = tasks.choose_weighted(|total| random * total).unwrap();
+    /// assert_eq!(task.name, "rare");
+    /// ```
+    pub fn choose_weighted<R>(&self, rng: R) -> Option<&T>
+    where
+        R: FnOnce(&M::Summary) -> M::Summary,
+    {
+        let point = rng(&self.summary());
+        let index = self.find_index(|weight| *weight > point)?;
+        self.get(index)
+    }
+}
+
+impl<T, M, const B: usize> AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+    M::Summary: PartialOrd + Sub<Output = M::Summary>,
+{
+    /// Split the list at the point `m` of the measure rather than at an index, such as a byte
+    /// offset in a rope or a position in a layout, returning `None` if it is past the summary of
+    /// the whole list.
+    ///
+    /// The element holding the point starts the returned tail, and how far into it the point is
+    /// comes back alongside, which is the measure's empty value when the point is on a boundary
+    /// between elements. This takes `O(B log(n))` time, like [`split_off`](Self::split_off).
+    ///
+    /// ```
+    /// # use btreelist::{AugmentedList, Sum};
+    /// // the lengths of the pieces of a rope
+    /// let mut pieces = vec![5, 3, 8].into_iter().collect::<AugmentedList<usize, Sum>>();
+    /// let (tail, offset) = pieces.split_off_at_measure(10).unwrap();
+    /// assert!(pieces.iter().eq(&[5, 3]));
+    /// assert!(tail.iter().eq(&[8]));
+    /// assert_eq!(offset, 2);
+    /// assert!(pieces.split_off_at_measure(9).is_none());
+    /// ```
+    pub fn split_off_at_measure(&mut self, m: M::Summary) -> Option<(Self, M::Summary)> {
+        if m > self.summary() {
+            return None;
+        }
+        let index = self
+            .find_index(|prefix| *prefix > m)
+            .unwrap_or_else(|| self.len());
+        let before = self.prefix_summary(index)?;
+        let tail = self.split_off(index)?;
+        Some((tail, m - before))
+    }
+}
+
+impl<T, M, const B: usize> TreeNode<T, B> for AugmentedNode<T, M, B>
+where
+    M: Measure<T>,
+{
+    type Child = Self;
+
+    fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    fn children(&self) -> &[Self] {
+        &self.children
+    }
+
+    fn child(child: &Self) -> &Self {
+        child
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+}
+
+impl<T, M, const B: usize> TreeNodeMut<T, B> for AugmentedNode<T, M, B>
+where
+    M: Measure<T>,
+{
+    fn from_parts(elements: Vec<T>, children: Vec<Self>) -> Self {
+        let mut node = Self {
+            elements,
+            children,
+            length: 0,
+            summary: M::empty(),
+        };
+        node.refresh();
+        node
+    }
+
+    fn into_parts(self) -> (Vec<T>, Vec<Self>) {
+        (self.elements, self.children)
+    }
+
+    fn parts_mut(&mut self) -> (&mut Vec<T>, &mut Vec<Self>) {
+        (&mut self.elements, &mut self.children)
+    }
+
+    fn edit(child: &mut Self) -> &mut Self {
+        child
+    }
+
+    fn unwrap(child: Self) -> Self {
+        child
+    }
+
+    fn wrap(node: Self) -> Self {
+        node
+    }
+
+    fn refresh(&mut self) {
+        let mut length = self.elements.len();
+        let mut summary = M::empty();
+        for (i, element) in self.elements.iter().enumerate() {
+            if let Some(child) = self.children.get(i) {
+                length += child.length;
+                summary = M::combine(&summary, &child.summary);
+            }
+            summary = M::combine(&summary, &M::measure(element));
+        }
+        if let Some(child) = self.children.get(self.elements.len()) {
+            length += child.length;
+            summary = M::combine(&summary, &child.summary);
+        }
+        self.length = length;
+        self.summary = summary;
+    }
+}
+
+impl<T, M, const B: usize> AugmentedNode<T, M, B>
+where
+    M: Measure<T>,
+{
+    fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if self.is_leaf() {
+            return self.elements.get_mut(index);
+        }
+        if index >= self.length {
+            return None;
+        }
+        let (child_index, index) = self.find_child(index);
+        if index < self.children[child_index].length {
+            self.children[child_index].get_mut(index)
+        } else {
+            self.elements.get_mut(child_index)
+        }
+    }
+
+    /// Insert `element` at `index` in this subtree, returning the median and new sibling if this
+    /// node had to be split.
+    fn insert(&mut self, index: usize, element: T) -> Option<(T, Self)> {
+        if self.is_leaf() {
+            self.elements.insert(index, element);
+        } else {
+            let (child_index, index) = self.find_child(index);
+            if let Some((median, sibling)) = self.children[child_index].insert(index, element) {
+                self.elements.insert(child_index, median);
+                self.children.insert(child_index + 1, sibling);
+            }
+        }
+        let split = self.split_overfull();
+        self.refresh();
+        split
+    }
+
+    fn update<R, F>(&mut self, index: usize, f: F) -> R
+    where
+        F: FnOnce(&mut T) -> R,
+    {
+        let result = if self.is_leaf() {
+            f(&mut self.elements[index])
+        } else {
+            let (child_index, index) = self.find_child(index);
+            if index < self.children[child_index].length {
+                self.children[child_index].update(index, f)
+            } else {
+                f(&mut self.elements[child_index])
+            }
+        };
+        self.refresh();
+        result
+    }
+
+    /// Remove the element at `index`, which must be in this subtree, topping the child it was
+    /// removed from back up if it runs low.
+    fn remove(&mut self, index: usize) -> T {
+        let element = if self.is_leaf() {
+            self.elements.remove(index)
+        } else {
+            let (child_index, index) = self.find_child(index);
+            let child = &mut self.children[child_index];
+            let element = if index < child.length {
+                child.remove(index)
+            } else {
+                // replace the separator with its predecessor from the end of the child before it
+                let predecessor = child.pop_back();
+                mem::replace(&mut self.elements[child_index], predecessor)
+            };
+            self.fix_underfull_child(child_index);
+            element
+        };
+        self.refresh();
+        element
+    }
+
+    fn pop_back(&mut self) -> T {
+        let element = if self.is_leaf() {
+            self.elements
+                .pop()
+                .expect("non-root nodes shouldn't be empty")
+        } else {
+            let last = self.children.len() - 1;
+            let element = self.children[last].pop_back();
+            self.fix_underfull_child(last);
+            element
+        };
+        self.refresh();
+        element
+    }
+
+    /// Add the summary of the elements from `start` to `end` in this subtree onto `summary`.
+    fn range_summary(&self, start: usize, end: usize, summary: &mut M::Summary) {
+        if start >= end {
+            return;
+        }
+        if start == 0 && end == self.length {
+            *summary = M::combine(summary, &self.summary);
+            return;
+        }
+        let mut offset = 0;
+        for (i, element) in self.elements.iter().enumerate() {
+            if let Some(child) = self.children.get(i) {
+                if start < offset + child.length && offset < end {
+                    let child_start = start.saturating_sub(offset);
+                    let child_end = (end - offset).min(child.length);
+                    child.range_summary(child_start, child_end, summary);
+                }
+                offset += child.length;
+            }
+            if start <= offset && offset < end {
+                *summary = M::combine(summary, &M::measure(element));
+            }
+            offset += 1;
+            if offset >= end {
+                return;
+            }
+        }
+        if let Some(child) = self.children.get(self.elements.len()) {
+            let child_start = start.saturating_sub(offset);
+            let child_end = (end - offset).min(child.length);
+            child.range_summary(child_start, child_end, summary);
+        }
+    }
+
+    /// Find the first index in this subtree where `pred` holds for `summary` combined with the
+    /// summary of the elements up to it, given that it holds for the whole subtree.
+    fn find_index<P>(&self, summary: &mut M::Summary, pred: &mut P) -> usize
+    where
+        P: FnMut(&M::Summary) -> bool,
+    {
+        let mut offset = 0;
+        for (i, element) in self.elements.iter().enumerate() {
+            if let Some(child) = self.children.get(i) {
+                let with_child = M::combine(summary, &child.summary);
+                if pred(&with_child) {
+                    return offset + child.find_index(summary, pred);
+                }
+                *summary = with_child;
+                offset += child.length;
+            }
+            let with_element = M::combine(summary, &M::measure(element));
+            if pred(&with_element) {
+                return offset;
+            }
+            *summary = with_element;
+            offset += 1;
+        }
+        let child = self
+            .children
+            .last()
+            .expect("the predicate holds for the whole subtree");
+        offset + child.find_index(summary, pred)
+    }
+
+    #[cfg(test)]
+    fn check(&self, root: bool) -> usize
+    where
+        M::Summary: PartialEq + fmt::Debug,
+    {
+        assert!(self.elements.len() < 2 * B);
+        assert!(root || self.elements.len() >= B - 1);
+        assert!(!self.elements.is_empty());
+        let mut depth = 0;
+        if !self.is_leaf() {
+            assert_eq!(self.children.len(), self.elements.len() + 1);
+            let depths = self
+                .children
+                .iter()
+                .map(|c| c.check(false))
+                .collect::<Vec<_>>();
+            assert!(depths.iter().all(|d| *d == depths[0]));
+            depth = depths[0] + 1;
+        }
+        assert_eq!(
+            self.length,
+            self.elements.len() + self.children.iter().map(|c| c.length).sum::<usize>()
+        );
+        let mut summary = M::empty();
+        self.range_summary(0, self.length - 1, &mut summary);
+        let last = self.get(self.length - 1).unwrap();
+        assert_eq!(self.summary, M::combine(&summary, &M::measure(last)));
+        depth
+    }
+}
+
+impl<T, M, const B: usize> Clone for AugmentedList<T, M, B>
+where
+    T: Clone,
+    M: Measure<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            root_node: self.root_node.clone(),
+        }
+    }
+}
+
+impl<T, M, const B: usize> Clone for AugmentedNode<T, M, B>
+where
+    T: Clone,
+    M: Measure<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            elements: self.elements.clone(),
+            children: self.children.clone(),
+            length: self.length,
+            summary: self.summary.clone(),
+        }
+    }
+}
+
+impl<T, M, const B: usize> Default for AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, M, const B: usize> fmt::Debug for AugmentedList<T, M, B>
+where
+    T: fmt::Debug,
+    M: Measure<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, M, const B: usize> PartialEq for AugmentedList<T, M, B>
+where
+    T: PartialEq,
+    M: Measure<T>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, M, const B: usize> Eq for AugmentedList<T, M, B>
+where
+    T: Eq,
+    M: Measure<T>,
+{
+}
+
+impl<T, M, const B: usize> Extend<T> for AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            self.push_back(element);
+        }
+    }
+}
+
+impl<T, M, const B: usize> FromIterator<T> for AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T, M, const B: usize> IntoIterator for &'a AugmentedList<T, M, B>
+where
+    M: Measure<T>,
+{
+    type Item = &'a T;
+
+    type IntoIter = AugmentedIter<'a, T, M, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over items in an [`AugmentedList`].
+pub struct AugmentedIter<'a, T, M, const B: usize>
+where
+    M: Measure<T>,
+{
+    inner: &'a AugmentedList<T, M, B>,
+    index: usize,
+    index_back: usize,
+}
+
+impl<'a, T, M, const B: usize> Clone for AugmentedIter<'a, T, M, B>
+where
+    M: Measure<T>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner,
+            index: self.index,
+            index_back: self.index_back,
+        }
+    }
+}
+
+impl<'a, T, M, const B: usize> fmt::Debug for AugmentedIter<'a, T, M, B>
+where
+    T: fmt::Debug,
+    M: Measure<T>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AugmentedIter")
+            .field("inner", self.inner)
+            .field("index", &self.index)
+            .field("index_back", &self.index_back)
+            .finish()
+    }
+}
+
+impl<'a, T, M, const B: usize> Iterator for AugmentedIter<'a, T, M, B>
+where
+    M: Measure<T>,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.index_back {
+            self.index += 1;
+            self.inner.get(self.index - 1)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, M, const B: usize> DoubleEndedIterator for AugmentedIter<'a, T, M, B>
+where
+    M: Measure<T>,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.index_back {
+            self.index_back -= 1;
+            self.inner.get(self.index_back)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::TestRng;
+
+    /// The count, sum and maximum of the elements, to check summaries don't depend on the shape.
+    struct Stats;
+
+    impl Measure<usize> for Stats {
+        type Summary = (usize, usize, Option<usize>);
+
+        fn empty() -> Self::Summary {
+            (0, 0, None)
+        }
+
+        fn measure(element: &usize) -> Self::Summary {
+            (1, *element, Some(*element))
+        }
+
+        fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary {
+            (left.0 + right.0, left.1 + right.1, left.2.max(right.2))
+        }
+    }
+
+    fn stats(elements: &[usize]) -> (usize, usize, Option<usize>) {
+        (
+            elements.len(),
+            elements.iter().sum(),
+            elements.iter().copied().max(),
+        )
+    }
+
+    fn assert_shape<T, M, const B: usize>(list: &AugmentedList<T, M, B>)
+    where
+        M: Measure<T>,
+        M::Summary: PartialEq + fmt::Debug,
+    {
+        if let Some(root) = list.root_node.as_ref() {
+            root.check(true);
+        }
+    }
+
+    #[test]
+    fn summaries() {
+        fn summaries<const B: usize>() {
+            let mut t = AugmentedList::<usize, Stats, B>::new();
+            let mut v = Vec::new();
+            let mut rng = TestRng::new(3);
+            for i in 0..2000 {
+                let r = rng.next();
+                match (v.len(), r % 6) {
+                    (0, _) | (_, 0..=2) => {
+                        let index = r % (v.len() + 1);
+                        t.insert(index, i).unwrap();
+                        v.insert(index, i);
+                    }
+                    (len, 3) => {
+                        let index = r % len;
+                        assert_eq!(t.set(index, i), Ok(v[index]));
+                        v[index] = i;
+                    }
+                    (len, 4) => {
+                        let index = r % len;
+                        if r & 1 == 0 {
+                            t.update(index, |e| *e += 1).unwrap();
+                        } else {
+                            *t.get_mut(index).unwrap() += 1;
+                            let changed = v[index] + 1;
+                            assert_eq!(t.measure_of(index), Some((1, changed, Some(changed))));
+                            assert!(t.update_measure(index));
+                        }
+                        v[index] += 1;
+                    }
+                    (len, _) => {
+                        let index = r % len;
+                        assert_eq!(t.remove(index), Some(v.remove(index)));
+                    }
+                }
+                assert_shape(&t);
+                assert_eq!(t.summary(), stats(&v));
+                let start = r % (v.len() + 1);
+                let end = start + (r >> 8) % (v.len() - start + 1);
+                assert_eq!(t.range_summary(start..end), Some(stats(&v[start..end])));
+                assert_eq!(t.prefix_summary(end), Some(stats(&v[..end])));
+                let total = r % (2 * stats(&v).1 + 1);
+                let expected = v
+                    .iter()
+                    .scan(0, |sum, e| {
+                        *sum += e;
+                        Some(*sum)
+                    })
+                    .position(|sum| sum > total);
+                assert_eq!(t.find_index(|s| s.1 > total), expected);
+            }
+            assert!(t.iter().eq(v.iter()));
+            assert_eq!(t.range_summary(..=v.len()), None);
+            assert_eq!(
+                t.range_summary((Bound::Excluded(0), Bound::Excluded(0))),
+                None
+            );
+            assert_eq!(t.set(v.len(), 0), Err(0));
+            assert_eq!(t.update(v.len(), |_| ()), None);
+            assert_eq!(t.get_mut(v.len()), None);
+            assert_eq!(t.measure_of(v.len()), None);
+            assert!(!t.update_measure(v.len()));
+            while let Some(element) = t.pop_back() {
+                assert_eq!(Some(element), v.pop());
+                assert_shape(&t);
+                assert_eq!(t.summary(), stats(&v));
+            }
+            assert!(t.is_empty());
+            assert_eq!(t.find_index(|_| true), None);
+        }
+        summaries::<2>();
+        summaries::<3>();
+        summaries::<6>();
+    }
+
+    #[test]
+    fn split_off() {
+        fn split_off<const B: usize>() {
+            let mut rng = TestRng::new(5);
+            for len in (0..60).chain([300, 1000]) {
+                // build trees of uneven shapes by removing some of the inserted elements
+                let mut t = AugmentedList::<usize, Stats, B>::new();
+                let mut v = Vec::new();
+                for i in 0..len + len / 2 {
+                    let r = rng.next();
+                    if i >= len && !v.is_empty() {
+                        let index = r % v.len();
+                        assert_eq!(t.remove(index), Some(v.remove(index)));
+                    } else {
+                        let index = r % (v.len() + 1);
+                        t.insert(index, i).unwrap();
+                        v.insert(index, i);
+                    }
+                }
+                for at in (0..=v.len()).filter(|at| v.len() < 60 || at % 37 == 0) {
+                    let mut head = t.clone();
+                    let tail = head.split_off(at).unwrap();
+                    assert_shape(&head);
+                    assert_shape(&tail);
+                    assert_eq!(head.summary(), stats(&v[..at]));
+                    assert_eq!(tail.summary(), stats(&v[at..]));
+                    assert!(head.iter().eq(v[..at].iter()));
+                    assert!(tail.iter().eq(v[at..].iter()));
+                }
+                assert!(t.split_off(v.len() + 1).is_none());
+            }
+        }
+        split_off::<2>();
+        split_off::<3>();
+        split_off::<6>();
+    }
+
+    #[test]
+    fn sums() {
+        let mut t = (0..500i64).collect::<AugmentedList<i64, Sum, 3>>();
+        let mut v = (0..500i64).collect::<Vec<_>>();
+        for i in 0..500 {
+            let index = (i * 7) % v.len();
+            t.set(index, -(i as i64)).unwrap();
+            v[index] = -(i as i64);
+            if i % 3 == 1 {
+                assert_eq!(t.remove(index), Some(v.remove(index)));
+            }
+            let end = (i * 13) % (v.len() + 1);
+            assert_eq!(t.prefix_sum(end), Some(v[..end].iter().sum()));
+            assert_eq!(
+                t.range_sum(end / 2..end),
+                Some(v[end / 2..end].iter().sum())
+            );
+        }
+        assert_shape(&t);
+        assert_eq!(t.range_sum(..), Some(v.iter().sum()));
+    }
+
+    #[test]
+    fn choose_weighted() {
+        // element i has weight i, so it is chosen for the points from i(i - 1)/2 up to i(i + 1)/2
+        let mut t = (0..100).collect::<AugmentedList<usize, Sum, 2>>();
+        for point in 0..(99 * 100 / 2) {
+            let chosen = *t.choose_weighted(|_| point).unwrap();
+            assert!(chosen * (chosen - 1) / 2 <= point && point < chosen * (chosen + 1) / 2);
+        }
+        assert_eq!(t.choose_weighted(|total| *total), None);
+        for i in 0..99 {
+            t.set(i, 0).unwrap();
+        }
+        assert_eq!(t.choose_weighted(|_| 0), Some(&99));
+        assert_eq!(t.choose_weighted(|_| 98), Some(&99));
+        assert_eq!(
+            AugmentedList::<usize, Sum>::new().choose_weighted(|_| 0),
+            None
+        );
+    }
+
+    #[test]
+    fn split_off_at_measure() {
+        for len in 0..40 {
+            let v = (0..len).map(|i| i % 4).collect::<Vec<usize>>();
+            let total = v.iter().sum::<usize>();
+            for m in 0..=total + 1 {
+                let mut t = v.iter().copied().collect::<AugmentedList<usize, Sum, 2>>();
+                let split = t.split_off_at_measure(m);
+                if m > total {
+                    assert!(split.is_none());
+                    continue;
+                }
+                let (tail, offset) = split.unwrap();
+                assert_shape(&t);
+                assert_shape(&tail);
+                assert!(t.iter().chain(tail.iter()).eq(v.iter()));
+                assert_eq!(t.summary() + offset, m);
+                assert!(tail.first().map_or(offset == 0, |first| offset < *first));
+            }
+        }
+    }
+}
@@ -1,11 +1,15 @@
 use std::{
-    cmp::{min, Ordering},
-    iter::FromIterator,
+    borrow::Borrow,
+    cmp::{max, min, Ordering},
+    fmt,
+    hash::{Hash, Hasher},
+    iter::{self, FromIterator},
     mem,
-    ops::{Index, IndexMut},
+    ops::{Bound, ControlFlow, Index, IndexMut, Range, RangeBounds},
+    slice, vec,
 };
 
-use crate::{Iter, OwnedIter};
+use crate::{sort, Cursor, CursorMut, Iter, OwnedIter, Split};
 
 /// A list with efficient insert and removal in the middle.
 ///
@@ -16,7 +20,7 @@ use crate::{Iter, OwnedIter};
 /// ```
 /// # use btreelist::BTreeList;
 /// # use btreelist::btreelist;
-/// let mut list = BTreeList::default();
+/// let mut list = BTreeList::<_>::default();
 /// list.push(1);
 /// list.push(2);
 ///
@@ -36,19 +40,73 @@ use crate::{Iter, OwnedIter};
 /// }
 /// assert_eq!(list, btreelist![7, 1, 2, 3]);
 /// ```
-#[derive(Clone, Debug)]
+///
+/// `B` must be at least 2. Smaller values are rejected when the list's code is generated, so
+/// `cargo check` won't report them but a build will.
+///
+/// ```compile_fail
+/// # use btreelist::BTreeList;
+/// let list: BTreeList<i32, 1> = BTreeList::new();
+/// ```
 pub struct BTreeList<T, const B: usize = 6> {
     root_node: Option<BTreeListNode<T, B>>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Debug, PartialEq)]
 struct BTreeListNode<T, const B: usize> {
     elements: Vec<T>,
     children: Vec<BTreeListNode<T, B>>,
-    length: usize,
+    length: Length,
+}
+
+/// The number of elements in a subtree, stored in each node.
+#[cfg(not(feature = "u32-lengths"))]
+type Length = usize;
+
+/// The number of elements in a subtree, stored in each node.
+#[cfg(feature = "u32-lengths")]
+type Length = u32;
+
+#[cfg(not(feature = "u32-lengths"))]
+fn to_length(len: usize) -> Length {
+    len
+}
+
+#[cfg(feature = "u32-lengths")]
+fn to_length(len: usize) -> Length {
+    std::convert::TryFrom::try_from(len).expect("lists should hold fewer than 2^32 elements")
+}
+
+/// Add `extra` elements to a stored `length`, panicking rather than wrapping if it overflows.
+#[cfg(not(feature = "u32-lengths"))]
+fn add_length(length: Length, extra: Length) -> Length {
+    length.checked_add(extra).expect("list length overflowed")
+}
+
+/// Add `extra` elements to a stored `length`, panicking rather than wrapping if it overflows.
+#[cfg(feature = "u32-lengths")]
+fn add_length(length: Length, extra: Length) -> Length {
+    length
+        .checked_add(extra)
+        .expect("lists should hold fewer than 2^32 elements")
+}
+
+#[cfg(not(feature = "u32-lengths"))]
+fn from_length(length: Length) -> usize {
+    length
+}
+
+#[cfg(feature = "u32-lengths")]
+fn from_length(length: Length) -> usize {
+    length as usize
 }
 
 impl<T, const B: usize> BTreeList<T, B> {
+    /// Fails to compile when referenced from a list with a `B` too small for the nodes to split
+    /// and merge.
+    pub(crate) const VALID_B: () =
+        assert!(B >= 2, "the B parameter of a BTreeList must be at least 2");
+
     /// Construct a new, empty [`BTreeList`].
     ///
     /// No allocation occurs until elements are added.
@@ -59,11 +117,36 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// let mut list : BTreeList<i32> = BTreeList::new();
     /// // create a BTreeList with a custom B parameter
     /// let mut list : BTreeList<i32, 32> = BTreeList::new();
+    /// // or in a const context
+    /// static EMPTY: BTreeList<i32> = BTreeList::new();
+    /// assert!(EMPTY.is_empty());
     /// ```
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
+        let () = Self::VALID_B;
         Self { root_node: None }
     }
 
+    /// Construct a list of length `n` by calling `f` for each element in turn.
+    ///
+    /// The nodes are filled directly, taking `O(n)` time.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// # use btreelist::btreelist;
+    /// let mut next = 0;
+    /// let list: BTreeList<_> = BTreeList::repeat_with(3, || {
+    ///     next += 2;
+    ///     next
+    /// });
+    /// assert_eq!(list, btreelist![2, 4, 6]);
+    /// ```
+    pub fn repeat_with<F>(n: usize, f: F) -> Self
+    where
+        F: FnMut() -> T,
+    {
+        Self::from_exact_iter(iter::repeat_with(f).take(n), n)
+    }
+
     /// Get the length of the list.
     ///
     /// ```
@@ -128,17 +211,17 @@ impl<T, const B: usize> BTreeList<T, B> {
         }
 
         if let Some(root) = self.root_node.as_mut() {
-            #[cfg(debug_assertions)]
+            #[cfg(feature = "strict-checks")]
             root.check();
 
             if root.is_full() {
                 let original_len = root.len();
-                let new_root = BTreeListNode::new();
+                let new_root = BTreeListNode::with_capacity(false);
 
                 // move new_root to root position
                 let old_root = mem::replace(root, new_root);
 
-                root.length += old_root.len();
+                root.length = add_length(root.length, old_root.length);
                 root.children.push(old_root);
                 root.split_child(0);
 
@@ -152,17 +235,13 @@ impl<T, const B: usize> BTreeList<T, B> {
                 } else {
                     (&mut root.children[0], index)
                 };
-                root.length += 1;
+                root.length = add_length(root.length, 1);
                 child.insert_into_non_full_node(insertion_index, element)?
             } else {
                 root.insert_into_non_full_node(index, element)?
             }
         } else if index == 0 {
-            self.root_node = Some(BTreeListNode {
-                elements: vec![element],
-                children: Vec::new(),
-                length: 1,
-            });
+            self.root_node = Some(BTreeListNode::singleton(element));
         } else {
             return Err(element);
         }
@@ -184,6 +263,10 @@ impl<T, const B: usize> BTreeList<T, B> {
 
     /// Push the `element` onto the back of the list.
     ///
+    /// This walks straight down the right edge of the tree rather than searching for the index,
+    /// only splitting nodes once they fill up. The walk still starts from the root each time, so
+    /// it takes `O(log(n))` time.
+    ///
     /// ```
     /// # use btreelist::btreelist;
     /// let mut list = btreelist![1, 2];
@@ -191,13 +274,21 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(list, btreelist![1, 2, 3]);
     /// ```
     pub fn push_back(&mut self, element: T) {
-        let l = self.len();
-        // SAFETY: can always push onto the end of a list
-        let _ = self.insert(l, element);
+        match self.root_node.as_mut() {
+            Some(root) => {
+                if let Some((median, sibling)) = root.push_back(element) {
+                    root.grow(median, sibling);
+                }
+            }
+            None => self.root_node = Some(BTreeListNode::singleton(element)),
+        }
     }
 
     /// Push the `element` onto the front of the list.
     ///
+    /// As with [`push_back`](Self::push_back), this walks straight down the left edge of the
+    /// tree.
+    ///
     /// ```
     /// # use btreelist::btreelist;
     /// let mut list = btreelist![2, 3];
@@ -205,8 +296,54 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(list, btreelist![1, 2, 3]);
     /// ```
     pub fn push_front(&mut self, element: T) {
-        // SAFETY: can always push onto the start of a list
-        let _ = self.insert(0, element);
+        match self.root_node.as_mut() {
+            Some(root) => {
+                if let Some((median, sibling)) = root.push_front(element) {
+                    root.grow(median, sibling);
+                }
+            }
+            None => self.root_node = Some(BTreeListNode::singleton(element)),
+        }
+    }
+
+    /// Insert each `(index, element)` pair in turn, with each index interpreted against the list
+    /// as left by the previous insertions, such as when replaying a stream of positional edits.
+    /// Stops at the first pair whose index is out of bounds, returning it.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2];
+    /// assert_eq!(list.extend_positional([(0, 0), (3, 3), (2, 4)]), Ok(()));
+    /// assert_eq!(list, btreelist![0, 1, 4, 2, 3]);
+    /// assert_eq!(list.extend_positional([(6, 5)]), Err((6, 5)));
+    /// ```
+    pub fn extend_positional<I>(&mut self, iter: I) -> Result<(), (usize, T)>
+    where
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        for (index, element) in iter {
+            self.insert(index, element)
+                .map_err(|element| (index, element))?;
+        }
+        Ok(())
+    }
+
+    /// Build a list by inserting each `(index, element)` pair in turn, as with
+    /// [`extend_positional`](Self::extend_positional).
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// # use btreelist::BTreeList;
+    /// let list: BTreeList<_> = BTreeList::from_positional([(0, 'b'), (0, 'a'), (2, 'c')]).unwrap();
+    /// assert_eq!(list, btreelist!['a', 'b', 'c']);
+    /// ```
+    pub fn from_positional<I>(iter: I) -> Result<Self, (usize, T)>
+    where
+        I: IntoIterator<Item = (usize, T)>,
+    {
+        let mut list = Self::new();
+        list.extend_positional(iter)?;
+        Ok(list)
     }
 
     /// Remove and return the last element from the list, if there is one.
@@ -230,11 +367,9 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(list, btreelist![1, 2]);
     /// ```
     pub fn pop_back(&mut self) -> Option<T> {
-        if !self.is_empty() {
-            self.remove(self.len() - 1)
-        } else {
-            None
-        }
+        let element = self.root_node.as_mut()?.pop_back();
+        self.collapse_root();
+        Some(element)
     }
 
     /// Remove and return the first element from the list, if there is one.
@@ -246,11 +381,9 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(list, btreelist![2, 3]);
     /// ```
     pub fn pop_front(&mut self) -> Option<T> {
-        if !self.is_empty() {
-            self.remove(0)
-        } else {
-            None
-        }
+        let element = self.root_node.as_mut()?.pop_front();
+        self.collapse_root();
+        Some(element)
     }
 
     /// Removes the element at `index` from the list if it exists.
@@ -266,10 +399,71 @@ impl<T, const B: usize> BTreeList<T, B> {
             return None;
         }
         if let Some(root) = self.root_node.as_mut() {
-            #[cfg(debug_assertions)]
+            #[cfg(feature = "strict-checks")]
             let len = root.check();
             let old = root.remove(index)?;
+            self.collapse_root();
+
+            #[cfg(feature = "strict-checks")]
+            assert_eq!(len, self.root_node.as_ref().map_or(0, |r| r.check()) + 1);
+            Some(old)
+        } else {
+            None
+        }
+    }
 
+    /// Removes the element at `index` from the list if it exists, replacing it with the last
+    /// element.
+    ///
+    /// This doesn't preserve the order of the list but only changes the structure of the tree at
+    /// its back edge, as for [`Vec::swap_remove`].
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4];
+    /// assert_eq!(list.swap_remove(1), Some(2));
+    /// assert_eq!(list, btreelist![1, 4, 3]);
+    /// assert_eq!(list.swap_remove(3), None);
+    /// ```
+    pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let back = self.pop_back()?;
+        match self.get_mut(index) {
+            Some(element) => Some(mem::replace(element, back)),
+            None => Some(back),
+        }
+    }
+
+    /// Removes the element at `index` from the list if it exists, replacing it with the first
+    /// element.
+    ///
+    /// Like [`swap_remove`](Self::swap_remove) but sacrificing the order at the front of the list
+    /// instead of the back.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4];
+    /// assert_eq!(list.swap_remove_front(2), Some(3));
+    /// assert_eq!(list, btreelist![2, 1, 4]);
+    /// assert_eq!(list.swap_remove_front(3), None);
+    /// ```
+    pub fn swap_remove_front(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let front = self.pop_front()?;
+        match index.checked_sub(1) {
+            Some(index) => Some(mem::replace(self.get_mut(index)?, front)),
+            None => Some(front),
+        }
+    }
+
+    /// Replace the root with its only child once it has run out of elements, or drop it if it is
+    /// an empty leaf.
+    fn collapse_root(&mut self) {
+        if let Some(root) = self.root_node.as_mut() {
             if root.elements.is_empty() {
                 if root.is_leaf() {
                     self.root_node = None;
@@ -277,12 +471,152 @@ impl<T, const B: usize> BTreeList<T, B> {
                     self.root_node = Some(root.children.remove(0));
                 }
             }
+        }
+    }
 
-            #[cfg(debug_assertions)]
-            debug_assert_eq!(len, self.root_node.as_ref().map_or(0, |r| r.check()) + 1);
-            Some(old)
-        } else {
-            None
+    /// Move all the elements of `other` onto the back of this list, leaving `other` empty.
+    ///
+    /// The trees are joined directly, taking `O(log(n))` time rather than pushing each element.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3];
+    /// let mut other = btreelist![4, 5];
+    /// list.append(&mut other);
+    /// assert_eq!(list, btreelist![1, 2, 3, 4, 5]);
+    /// assert!(other.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        let mut right = mem::take(other);
+        if let Some(separator) = right.pop_front() {
+            let left = mem::take(self);
+            *self = Self::join_trees(left, separator, right);
+        }
+    }
+
+    /// Split the list in two at `at`, returning the elements from `at` onwards and leaving the
+    /// elements before it in this list. Returns `None` if `at` is out of bounds.
+    ///
+    /// The tree is cut along a single path, taking `O(log(n))` time.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4, 5];
+    /// assert_eq!(list.split_off(3), Some(btreelist![4, 5]));
+    /// assert_eq!(list, btreelist![1, 2, 3]);
+    /// assert_eq!(list.split_off(4), None);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Option<Self> {
+        if at > self.len() {
+            return None;
+        }
+        match self.root_node.take() {
+            Some(root) => {
+                let (left, right) = root.split_at(at);
+                *self = left;
+                Some(right)
+            }
+            None => Some(Self::new()),
+        }
+    }
+
+    /// Apply a batch of edits, each replacing a range of the list with new elements.
+    ///
+    /// The ranges refer to positions in the list before any of the edits are applied and must be
+    /// sorted by their start. Edits that overlap or touch are coalesced into one, replacing the
+    /// union of their ranges with their elements in order, and the edits are then applied from
+    /// right to left so that earlier positions stay valid. If any range is out of bounds or out of
+    /// order it is returned and the list is left unchanged.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist!['a', 'b', 'c', 'd', 'e'];
+    /// let edits = vec![(0..1, vec!['A']), (2..2, vec!['x', 'y']), (3..5, vec![])];
+    /// assert_eq!(list.apply_sorted_edits(edits), Ok(()));
+    /// assert_eq!(list, btreelist!['A', 'b', 'x', 'y', 'c']);
+    ///
+    /// assert_eq!(list.apply_sorted_edits(vec![(3..4, vec![]), (1..2, vec![])]), Err(1..2));
+    /// assert_eq!(list, btreelist!['A', 'b', 'x', 'y', 'c']);
+    /// ```
+    pub fn apply_sorted_edits<I, R>(&mut self, edits: I) -> Result<(), Range<usize>>
+    where
+        I: IntoIterator<Item = (Range<usize>, R)>,
+        R: IntoIterator<Item = T>,
+    {
+        let mut coalesced: Vec<(Range<usize>, Vec<T>)> = Vec::new();
+        let mut last_start = 0;
+        for (range, replacement) in edits {
+            if range.start < last_start || range.start > range.end || range.end > self.len() {
+                return Err(range);
+            }
+            last_start = range.start;
+            match coalesced.last_mut() {
+                Some((last, elements)) if range.start <= last.end => {
+                    last.end = max(last.end, range.end);
+                    elements.extend(replacement);
+                }
+                _ => coalesced.push((range, replacement.into_iter().collect())),
+            }
+        }
+        for (range, replacement) in coalesced.into_iter().rev() {
+            let mut tail = self.split_off(range.end).unwrap_or_default();
+            let _ = self.split_off(range.start);
+            let len = replacement.len();
+            self.append(&mut Self::from_exact_iter(replacement.into_iter(), len));
+            self.append(&mut tail);
+        }
+        Ok(())
+    }
+
+    /// Remove the elements in `range` from the list, returning them as a new list, or `None` if
+    /// the range is out of bounds.
+    pub(crate) fn remove_range(&mut self, range: Range<usize>) -> Option<Self> {
+        if range.start > range.end {
+            return None;
+        }
+        let mut removed = self.split_off(range.start)?;
+        match removed.split_off(range.len()) {
+            Some(mut rest) => {
+                self.append(&mut rest);
+                Some(removed)
+            }
+            None => {
+                // put things back as they were
+                self.append(&mut removed);
+                None
+            }
+        }
+    }
+
+    /// Join `left`, `separator` and `right` into a single list.
+    fn join_trees(mut left: Self, separator: T, mut right: Self) -> Self {
+        match (left.root_node.take(), right.root_node.take()) {
+            (Some(l), Some(r)) => Self {
+                root_node: Some(BTreeListNode::join(l, separator, r)),
+            },
+            (Some(l), None) => {
+                left.root_node = Some(l);
+                left.push_back(separator);
+                left
+            }
+            (None, r) => {
+                right.root_node = r;
+                right.push_front(separator);
+                right
+            }
+        }
+    }
+
+    /// Make a list from a node that may have been left empty or with only a single child.
+    fn from_node(mut node: BTreeListNode<T, B>) -> Self {
+        while node.elements.is_empty() {
+            match node.children.pop() {
+                Some(child) => node = child,
+                None => return Self::new(),
+            }
+        }
+        Self {
+            root_node: Some(node),
         }
     }
 
@@ -294,12 +628,12 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// let mut list = btreelist![1, 2, 3];
     /// list.set(1, 4);
     /// assert_eq!(list, btreelist![1, 4, 3]);
+    /// assert_eq!(list.set(3, 5), Err(5));
     /// ```
     pub fn set(&mut self, index: usize, element: T) -> Result<T, T> {
-        if let Some(node) = self.root_node.as_mut() {
-            node.set(index, element)
-        } else {
-            Err(element)
+        match self.root_node.as_mut() {
+            Some(node) if index < node.len() => node.set(index, element),
+            _ => Err(element),
         }
     }
 
@@ -334,6 +668,173 @@ impl<T, const B: usize> BTreeList<T, B> {
         true
     }
 
+    /// Move the elements in `range` by `offset` places, to the right if positive or the left if
+    /// negative. The elements displaced by the move fill the gap left behind, keeping their
+    /// order. Returns whether the shift was successful, it fails if either the range or its
+    /// destination are out of bounds.
+    ///
+    /// The moved and displaced pieces are split off and appended back in their new order, taking
+    /// `O(log(n))` time however many elements move.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![0, 1, 2, 3, 4, 5];
+    /// assert!(list.shift_elements(1..3, 2));
+    /// assert_eq!(list, btreelist![0, 3, 4, 1, 2, 5]);
+    /// assert!(list.shift_elements(3..5, -2));
+    /// assert_eq!(list, btreelist![0, 1, 2, 3, 4, 5]);
+    /// assert!(!list.shift_elements(3..5, 2));
+    /// ```
+    pub fn shift_elements<R>(&mut self, range: R, offset: isize) -> bool
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = match self.resolve_range(range) {
+            Some(range) => range,
+            None => return false,
+        };
+        let distance = offset.unsigned_abs();
+        // the affected span and the position of the first element to move to its front
+        let (span, mid) = if offset >= 0 {
+            match range.end.checked_add(distance) {
+                Some(end) if end <= self.len() => (range.start..end, range.start + range.len()),
+                _ => return false,
+            }
+        } else {
+            match range.start.checked_sub(distance) {
+                Some(start) => (start..range.end, range.start),
+                None => return false,
+            }
+        };
+        if span.start == mid || mid == span.end {
+            return true;
+        }
+
+        let mut tail = self.split_off(span.end).unwrap_or_default();
+        let mut back = self.split_off(mid).unwrap_or_default();
+        let mut front = self.split_off(span.start).unwrap_or_default();
+        self.append(&mut back);
+        self.append(&mut front);
+        self.append(&mut tail);
+        true
+    }
+
+    /// Copy the elements in `src` over the elements starting at `dest`, like
+    /// [`slice::copy_within`]. The ranges may overlap. Returns whether the copy was successful,
+    /// it fails if either the source or the destination are out of bounds.
+    ///
+    /// The source is read a node's elements at a time and the destination written in place, so
+    /// the structure of the tree is left untouched.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![0, 1, 2, 3, 4, 5];
+    /// assert!(list.copy_within(0..3, 2));
+    /// assert_eq!(list, btreelist![0, 1, 0, 1, 2, 5]);
+    /// assert!(list.copy_within(4.., 0));
+    /// assert_eq!(list, btreelist![2, 5, 0, 1, 2, 5]);
+    /// assert!(!list.copy_within(0..3, 4));
+    /// ```
+    pub fn copy_within<R>(&mut self, src: R, dest: usize) -> bool
+    where
+        R: RangeBounds<usize>,
+        T: Copy,
+    {
+        let src = match self.resolve_range(src) {
+            Some(src) => src,
+            None => return false,
+        };
+        let dest = match dest.checked_add(src.len()) {
+            Some(end) if end <= self.len() => dest..end,
+            _ => return false,
+        };
+        if src.is_empty() || src.start == dest.start {
+            return true;
+        }
+
+        let mut values = Vec::with_capacity(src.len());
+        let _ = self.try_for_each_chunk_from(src.start, |chunk| {
+            let wanted = src.len() - values.len();
+            if chunk.len() < wanted {
+                values.extend_from_slice(chunk);
+                ControlFlow::Continue(())
+            } else {
+                values.extend_from_slice(&chunk[..wanted]);
+                ControlFlow::Break(())
+            }
+        });
+        if let Some(root) = self.root_node.as_mut() {
+            let mut elements = Vec::with_capacity(dest.len());
+            root.collect_range_mut(dest, &mut elements);
+            for (element, value) in elements.into_iter().zip(values) {
+                *element = value;
+            }
+        }
+        true
+    }
+
+    /// Resolve `range` against the length of the list, if it is in bounds.
+    pub(crate) fn resolve_range<R>(&self, range: R) -> Option<Range<usize>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        if start <= end && end <= self.len() {
+            Some(start..end)
+        } else {
+            None
+        }
+    }
+
+    /// Create a cursor over the list, starting at `index`. The index may be equal to the length
+    /// of the list, placing the cursor past the last element.
+    ///
+    /// Moving the cursor takes `O(1)` amortized time as it keeps its path down the tree.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// let mut cursor = list.cursor_at(1).unwrap();
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// assert_eq!(cursor.peek_next(), Some(&3));
+    /// assert!(list.cursor_at(4).is_none());
+    /// ```
+    pub fn cursor_at(&self, index: usize) -> Option<Cursor<'_, T, B>> {
+        if index <= self.len() {
+            Some(Cursor::new(self, index))
+        } else {
+            None
+        }
+    }
+
+    /// Create a cursor that can edit the list, starting at `index`. The index may be equal to
+    /// the length of the list, placing the cursor past the last element.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3];
+    /// let mut cursor = list.cursor_mut_at(1).unwrap();
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// drop(cursor);
+    /// assert!(list.cursor_mut_at(4).is_none());
+    /// ```
+    pub fn cursor_mut_at(&mut self, index: usize) -> Option<CursorMut<'_, T, B>> {
+        if index <= self.len() {
+            Some(CursorMut::new(self, index))
+        } else {
+            None
+        }
+    }
+
     /// Get the `element` at `index` in the list.
     ///
     /// ```
@@ -343,7 +844,10 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(list.get(3), None);
     /// ```
     pub fn get(&self, index: usize) -> Option<&T> {
-        self.root_node.as_ref().and_then(|n| n.get(index))
+        if index >= self.len() {
+            return None;
+        }
+        self.root_node.as_ref().map(|n| n.get_in_bounds(index))
     }
 
     /// Get the and `element` at `index` in the list.
@@ -357,7 +861,10 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(*list, btreelist![0, 42, 2]);
     /// ```
     pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        self.root_node.as_mut().and_then(|n| n.get_mut(index))
+        if index >= self.len() {
+            return None;
+        }
+        self.root_node.as_mut().map(|n| n.get_in_bounds_mut(index))
     }
 
     /// Get the first element in the list if it exists.
@@ -393,7 +900,7 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(list.last(), Some(&30));
     /// ```
     pub fn last(&self) -> Option<&T> {
-        self.get(self.len() - 1)
+        self.get(self.len().checked_sub(1)?)
     }
 
     /// Get the last element in the list if it exists.
@@ -407,638 +914,4853 @@ impl<T, const B: usize> BTreeList<T, B> {
     /// assert_eq!(*list, btreelist![0, 1, 42]);
     /// ```
     pub fn last_mut(&mut self) -> Option<&mut T> {
-        self.get_mut(self.len() - 1)
+        self.get_mut(self.len().checked_sub(1)?)
     }
-}
 
-impl<T, const B: usize> BTreeListNode<T, B> {
-    fn new() -> Self {
-        Self {
-            elements: Vec::new(),
-            children: Vec::new(),
-            length: 0,
+    /// Swap the first and last elements of the list.
+    ///
+    /// Unlike [`swap`](Self::swap) this doesn't change the structure of the tree, it only walks
+    /// down both edges.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3];
+    /// list.swap_front_back();
+    /// assert_eq!(list, btreelist![3, 2, 1]);
+    /// ```
+    pub fn swap_front_back(&mut self) {
+        if let Some(root) = self.root_node.as_mut() {
+            if let Some((first, last)) = root.first_and_last_mut() {
+                mem::swap(first, last);
+            }
         }
     }
 
-    pub(crate) fn len(&self) -> usize {
-        self.length
+    /// Replace the first element in the list, returning the old value on success, or the given
+    /// value when the list is empty.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3];
+    /// assert_eq!(list.replace_front(4), Ok(1));
+    /// assert_eq!(list, btreelist![4, 2, 3]);
+    /// ```
+    pub fn replace_front(&mut self, element: T) -> Result<T, T> {
+        match self.root_node.as_mut() {
+            Some(root) => Ok(mem::replace(root.first_mut(), element)),
+            None => Err(element),
+        }
     }
 
-    fn is_leaf(&self) -> bool {
-        self.children.is_empty()
+    /// Replace the last element in the list, returning the old value on success, or the given
+    /// value when the list is empty.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3];
+    /// assert_eq!(list.replace_back(4), Ok(3));
+    /// assert_eq!(list, btreelist![1, 2, 4]);
+    /// ```
+    pub fn replace_back(&mut self, element: T) -> Result<T, T> {
+        match self.root_node.as_mut() {
+            Some(root) => Ok(mem::replace(root.last_mut(), element)),
+            None => Err(element),
+        }
     }
 
-    fn is_full(&self) -> bool {
-        let max = 2 * B - 1;
-        assert!(
-            self.elements.len() <= max,
-            "node shouldn't be over full-size"
-        );
-        self.elements.len() == max
+    /// Merge the sorted `other` list into this sorted list, keeping the result sorted.
+    ///
+    /// Both lists are walked once and the result is built bottom-up, so this takes `O(n + m)`
+    /// time. Elements of `self` are placed before equal elements of `other`.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 3, 5];
+    /// list.merge_sorted(btreelist![2, 3, 4]);
+    /// assert_eq!(list, btreelist![1, 2, 3, 3, 4, 5]);
+    /// ```
+    pub fn merge_sorted(&mut self, other: Self)
+    where
+        T: Ord,
+    {
+        let len = other.len();
+        self.merge_sorted_from(other.into_drain(), len);
     }
 
-    /// Returns the child index and the given index adjusted for the cumulative index before that
-    /// child.
-    fn find_child_index(&self, index: usize) -> Option<(usize, usize)> {
-        let mut cumulative_len = 0;
-        for (child_index, child) in self.children.iter().enumerate() {
-            if cumulative_len + child.len() >= index {
-                return Some((child_index, index - cumulative_len));
-            } else {
-                cumulative_len += child.len() + 1;
-            }
+    /// Merge the elements of the sorted `iter` into this sorted list, keeping the result sorted.
+    ///
+    /// Unlike calling [`insert_sorted`](Self::insert_sorted) for each element, the list is rebuilt
+    /// once, taking `O(n + k)` time for `k` new elements. Existing elements are placed before
+    /// equal new ones.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 4, 9];
+    /// list.extend_sorted(vec![0, 4, 5, 10]);
+    /// assert_eq!(list, btreelist![0, 1, 4, 4, 5, 9, 10]);
+    /// ```
+    pub fn extend_sorted<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = T>,
+        T: Ord,
+    {
+        let other = iter.into_iter().collect::<Vec<_>>();
+        let len = other.len();
+        self.merge_sorted_from(other.into_iter(), len);
+    }
+
+    /// Merge `len` sorted elements from `right` into this sorted list.
+    fn merge_sorted_from<I>(&mut self, right: I, len: usize)
+    where
+        I: Iterator<Item = T>,
+        T: Ord,
+    {
+        let len = self.len() + len;
+        let mut left = mem::take(self).into_drain().peekable();
+        let mut right = right.peekable();
+        let merged = iter::from_fn(|| match (left.peek(), right.peek()) {
+            (Some(l), Some(r)) if r < l => right.next(),
+            (Some(_), _) => left.next(),
+            (None, _) => right.next(),
+        });
+        *self = Self::from_exact_iter(merged, len);
+    }
+
+    /// Return the index of the first element for which `pred` returns false, assuming the list
+    /// is partitioned so that all elements matching `pred` come first.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 3, 5, 6, 7];
+    /// assert_eq!(list.partition_point(|&x| x < 5), 4);
+    /// ```
+    pub fn partition_point<P>(&self, mut pred: P) -> usize
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.root_node
+            .as_ref()
+            .map_or(0, |root| root.partition_point(&mut pred))
+    }
+
+    /// Binary search a sorted list for `x`, as with [`slice::binary_search`].
+    ///
+    /// If there are multiple matches the index of the first is returned.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 3, 5];
+    /// assert_eq!(list.binary_search(&3), Ok(2));
+    /// assert_eq!(list.binary_search(&4), Err(4));
+    /// ```
+    pub fn binary_search(&self, x: &T) -> Result<usize, usize>
+    where
+        T: Ord,
+    {
+        self.binary_search_by(|e| e.cmp(x))
+    }
+
+    /// Binary search a sorted list with a comparator function, as with
+    /// [`slice::binary_search_by`].
+    ///
+    /// If there are multiple matches the index of the first is returned.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 3, 5];
+    /// assert_eq!(list.binary_search_by(|e| e.cmp(&5)), Ok(4));
+    /// ```
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let index = self.partition_point(|e| f(e) == Ordering::Less);
+        match self.get(index) {
+            Some(e) if f(e) == Ordering::Equal => Ok(index),
+            _ => Err(index),
         }
-        None
     }
 
-    fn insert_into_non_full_node(&mut self, index: usize, element: T) -> Result<(), T> {
-        assert!(!self.is_full());
-        if self.is_leaf() {
-            self.length += 1;
-            if index <= self.elements.len() {
-                self.elements.insert(index, element);
-                Ok(())
-            } else {
-                Err(element)
-            }
-        } else if let Some((child_index, sub_index)) = self.find_child_index(index) {
-            let child = &mut self.children[child_index];
+    /// Binary search a list sorted by the key extracted by `f`, as with
+    /// [`slice::binary_search_by_key`].
+    ///
+    /// If there are multiple matches the index of the first is returned.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![(1, 'a'), (2, 'b'), (4, 'c')];
+    /// assert_eq!(list.binary_search_by_key(&2, |&(k, _)| k), Ok(1));
+    /// assert_eq!(list.binary_search_by_key(&3, |&(k, _)| k), Err(2));
+    /// ```
+    pub fn binary_search_by_key<K, F>(&self, key: &K, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.binary_search_by(|e| f(e).cmp(key))
+    }
 
-            if child.is_full() {
-                self.split_child(child_index);
+    /// Return a cursor on the first element that is not less than `x` in a sorted list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 4, 4, 5];
+    /// let mut cursor = list.lower_bound(&3);
+    /// assert_eq!(cursor.current(), Some(&4));
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current(), Some(&2));
+    /// assert_eq!(list.lower_bound(&6).current(), None);
+    /// ```
+    pub fn lower_bound(&self, x: &T) -> Cursor<'_, T, B>
+    where
+        T: Ord,
+    {
+        Cursor::new(self, self.partition_point(|e| e < x))
+    }
 
-                // child structure has changed so we need to find the index again
-                if let Some((child_index, sub_index)) = self.find_child_index(index) {
-                    let child = &mut self.children[child_index];
-                    child.insert_into_non_full_node(sub_index, element)?;
-                } else {
-                    return Err(element);
-                }
-            } else {
-                child.insert_into_non_full_node(sub_index, element)?;
+    /// Return a cursor on the first element that is greater than `x` in a sorted list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 4, 4, 5];
+    /// assert_eq!(list.upper_bound(&4).current(), Some(&5));
+    /// assert_eq!(list.upper_bound(&4).index(), 4);
+    /// ```
+    pub fn upper_bound(&self, x: &T) -> Cursor<'_, T, B>
+    where
+        T: Ord,
+    {
+        Cursor::new(self, self.partition_point(|e| e <= x))
+    }
+
+    /// Return a mutable cursor on the first element that is not less than `x` in a sorted list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 4, 4, 5];
+    /// let mut cursor = list.lower_bound_mut(&4);
+    /// assert_eq!(cursor.remove_forward_while(|&x| x == 4), btreelist![4, 4]);
+    /// drop(cursor);
+    /// assert_eq!(list, btreelist![1, 2, 5]);
+    /// ```
+    pub fn lower_bound_mut(&mut self, x: &T) -> CursorMut<'_, T, B>
+    where
+        T: Ord,
+    {
+        let index = self.partition_point(|e| e < x);
+        CursorMut::new(self, index)
+    }
+
+    /// Return a mutable cursor on the first element that is greater than `x` in a sorted list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 4, 4, 5];
+    /// assert_eq!(list.upper_bound_mut(&2).current(), Some(&4));
+    /// ```
+    pub fn upper_bound_mut(&mut self, x: &T) -> CursorMut<'_, T, B>
+    where
+        T: Ord,
+    {
+        let index = self.partition_point(|e| e <= x);
+        CursorMut::new(self, index)
+    }
+
+    /// Return the range of indices of elements equal to `x` in a sorted list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 3, 5];
+    /// assert_eq!(list.equal_range(&3), 2..4);
+    /// assert_eq!(list.equal_range(&4), 4..4);
+    /// ```
+    pub fn equal_range(&self, x: &T) -> Range<usize>
+    where
+        T: Ord,
+    {
+        self.equal_range_by(|e| e.cmp(x))
+    }
+
+    /// Return the range of indices of elements for which the comparator `f` returns
+    /// [`Ordering::Equal`] in a sorted list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 3, 5];
+    /// assert_eq!(list.equal_range_by(|e| e.cmp(&3)), 2..4);
+    /// ```
+    pub fn equal_range_by<F>(&self, mut f: F) -> Range<usize>
+    where
+        F: FnMut(&T) -> Ordering,
+    {
+        let start = self.partition_point(|e| f(e) == Ordering::Less);
+        let end = self.partition_point(|e| f(e) != Ordering::Greater);
+        start..end
+    }
+
+    /// Return the range of indices of elements whose key extracted by `f` is equal to `key` in a
+    /// list sorted by that key.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![(1, 'a'), (2, 'b'), (2, 'c'), (4, 'd')];
+    /// assert_eq!(list.equal_range_by_key(&2, |&(k, _)| k), 1..3);
+    /// ```
+    pub fn equal_range_by_key<K, F>(&self, key: &K, mut f: F) -> Range<usize>
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.equal_range_by(|e| f(e).cmp(key))
+    }
+
+    /// Insert `element` into a sorted list, keeping it sorted, and return the index it was
+    /// inserted at.
+    ///
+    /// The element is inserted after any equal elements.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 4];
+    /// assert_eq!(list.insert_sorted(3), 2);
+    /// assert_eq!(list, btreelist![1, 2, 3, 4]);
+    /// ```
+    pub fn insert_sorted(&mut self, element: T) -> usize
+    where
+        T: Ord,
+    {
+        self.insert_sorted_by(element, |a, b| a.cmp(b))
+    }
+
+    /// Insert `element` into a list sorted by the comparator `compare`, keeping it sorted, and
+    /// return the index it was inserted at.
+    ///
+    /// The comparator is called with an existing element and the new element. The element is
+    /// inserted after any equal elements.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![4, 2, 1];
+    /// assert_eq!(list.insert_sorted_by(3, |a, b| b.cmp(a)), 1);
+    /// assert_eq!(list, btreelist![4, 3, 2, 1]);
+    /// ```
+    pub fn insert_sorted_by<F>(&mut self, element: T, mut compare: F) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let index = self.partition_point(|e| compare(e, &element) != Ordering::Greater);
+        // SAFETY: the partition point is always within the list
+        let _ = self.insert(index, element);
+        index
+    }
+
+    /// Insert `element` into a list sorted by the key extracted by `f`, keeping it sorted, and
+    /// return the index it was inserted at.
+    ///
+    /// The element is inserted after any elements with an equal key.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![(1, 'a'), (2, 'b'), (4, 'c')];
+    /// assert_eq!(list.insert_sorted_by_key((2, 'd'), |&(k, _)| k), 2);
+    /// assert_eq!(list, btreelist![(1, 'a'), (2, 'b'), (2, 'd'), (4, 'c')]);
+    /// ```
+    pub fn insert_sorted_by_key<K, F>(&mut self, element: T, mut f: F) -> usize
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.insert_sorted_by(element, |a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Sort the list, without preserving the order of equal elements.
+    ///
+    /// The elements are sorted in place within the existing nodes. Partitions spanning several
+    /// nodes are split up across them, and once a partition lies within the elements of a single
+    /// node it is sorted there directly. The only temporary allocation is an index of the runs of
+    /// elements in the nodes, taking a few words per node rather than per element.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![3, 1, 2, 1];
+    /// list.sort_unstable();
+    /// assert_eq!(list, btreelist![1, 1, 2, 3]);
+    /// ```
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        self.sort_unstable_by(|a, b| a.cmp(b))
+    }
+
+    /// Sort the list with a comparator function, without preserving the order of equal elements.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![3, 1, 2, 1];
+    /// list.sort_unstable_by(|a, b| b.cmp(a));
+    /// assert_eq!(list, btreelist![3, 2, 1, 1]);
+    /// ```
+    pub fn sort_unstable_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        sort::NodeRuns::new(self.chunks_mut()).sort_by(&mut compare);
+    }
+
+    /// Sort the list by the key extracted by `f`, without preserving the order of equal elements.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![-3, 1, -2, 0];
+    /// list.sort_unstable_by_key(|x: &i32| x.abs());
+    /// assert_eq!(list, btreelist![0, 1, -2, -3]);
+    /// ```
+    pub fn sort_unstable_by_key<K, F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.sort_unstable_by(|a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Reorder the list so that the element at `index` is the one that would be there if the list
+    /// were sorted, with no greater elements before it and no lesser elements after it, returning
+    /// it. Returns `None` if `index` is out of bounds.
+    ///
+    /// This uses quickselect, taking `O(n)` time on average rather than sorting the whole list.
+    /// Like [`sort_unstable`](Self::sort_unstable) the elements are reordered within the existing
+    /// nodes, with a temporary index of a few words per node.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![5, 1, 4, 2, 3];
+    /// assert_eq!(list.select_nth_unstable(1), Some(&mut 2));
+    /// assert!(list.iter().take(1).all(|&x| x <= 2));
+    /// assert!(list.iter().skip(2).all(|&x| x >= 2));
+    /// ```
+    pub fn select_nth_unstable(&mut self, index: usize) -> Option<&mut T>
+    where
+        T: Ord,
+    {
+        self.select_nth_unstable_by(index, |a, b| a.cmp(b))
+    }
+
+    /// Reorder the list with a comparator function so that the element at `index` is in its
+    /// sorted position, returning it, as with
+    /// [`select_nth_unstable`](Self::select_nth_unstable).
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![5, 1, 4, 2, 3];
+    /// assert_eq!(list.select_nth_unstable_by(0, |a, b| b.cmp(a)), Some(&mut 5));
+    /// ```
+    pub fn select_nth_unstable_by<F>(&mut self, index: usize, mut compare: F) -> Option<&mut T>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if index >= self.len() {
+            return None;
+        }
+        sort::NodeRuns::new(self.chunks_mut()).select_nth_by(index, &mut compare);
+        self.get_mut(index)
+    }
+
+    /// Reorder the list by the key extracted by `f` so that the element at `index` is in its
+    /// sorted position, returning it, as with
+    /// [`select_nth_unstable`](Self::select_nth_unstable).
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![-5, 1, -4, 2, 3];
+    /// assert_eq!(list.select_nth_unstable_by_key(4, |x: &i32| x.abs()), Some(&mut -5));
+    /// ```
+    pub fn select_nth_unstable_by_key<K, F>(&mut self, index: usize, mut f: F) -> Option<&mut T>
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        self.select_nth_unstable_by(index, |a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Check whether the list is sorted.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// assert!(btreelist![1, 2, 2, 3].is_sorted());
+    /// assert!(!btreelist![1, 3, 2].is_sorted());
+    /// ```
+    pub fn is_sorted(&self) -> bool
+    where
+        T: PartialOrd,
+    {
+        self.is_sorted_by(|a, b| a <= b)
+    }
+
+    /// Check whether the list is sorted with `compare` returning whether each pair of adjacent
+    /// elements is in order, as with [`slice::is_sorted_by`].
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// assert!(btreelist![3, 2, 2, 1].is_sorted_by(|a, b| a >= b));
+    /// ```
+    pub fn is_sorted_by<F>(&self, mut compare: F) -> bool
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let root = match self.root_node.as_ref() {
+            Some(root) => root,
+            None => return true,
+        };
+        let mut previous = None;
+        root.try_for_each(&mut |e| {
+            match previous {
+                Some(p) if !compare(p, e) => return ControlFlow::Break(()),
+                _ => previous = Some(e),
             }
-            self.length += 1;
-            Ok(())
-        } else {
-            Err(element)
+            ControlFlow::Continue(())
+        })
+        .is_continue()
+    }
+
+    /// Check whether the list is sorted by the key extracted by `f`.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// assert!(btreelist![0, -1, 2, -3].is_sorted_by_key(|x: &i32| x.abs()));
+    /// ```
+    pub fn is_sorted_by_key<K, F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&T) -> K,
+        K: PartialOrd,
+    {
+        self.is_sorted_by(|a, b| f(a) <= f(b))
+    }
+
+    /// Fold over each window of `size` consecutive elements, maintaining `state` incrementally.
+    ///
+    /// `add` is called as each element enters the window and `remove` as it leaves, then `f` is
+    /// called with the state of each full window in turn, so the whole traversal takes `O(n)`
+    /// time regardless of `size`. Returns the final state. Nothing is called if `size` is zero
+    /// or larger than the list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 4, 5];
+    /// let mut sums = Vec::new();
+    /// list.windows_fold(3, 0, |s, x| *s += x, |s, x| *s -= x, |s| sums.push(*s));
+    /// assert_eq!(sums, vec![6, 9, 12]);
+    /// ```
+    pub fn windows_fold<S, A, R, F>(
+        &self,
+        size: usize,
+        mut state: S,
+        mut add: A,
+        mut remove: R,
+        mut f: F,
+    ) -> S
+    where
+        A: FnMut(&mut S, &T),
+        R: FnMut(&mut S, &T),
+        F: FnMut(&S),
+    {
+        if size == 0 || size > self.len() {
+            return state;
+        }
+        let mut leading = self.elements();
+        for element in leading.by_ref().take(size) {
+            add(&mut state, element);
+        }
+        f(&state);
+        for (entering, leaving) in leading.zip(self.elements()) {
+            remove(&mut state, leaving);
+            add(&mut state, entering);
+            f(&state);
         }
+        state
     }
 
-    // A utility function to split the child `full_child_index` of this node
-    // Note that `full_child_index` must be full when this function is called.
-    fn split_child(&mut self, full_child_index: usize) {
-        let original_len_self = self.len();
+    /// Check whether the list contains an element equal to `x`.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// assert!(list.contains(&2));
+    /// assert!(!list.contains(&4));
+    /// ```
+    pub fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        matches!(self.root_node.as_ref(), Some(root) if root.contains(x))
+    }
 
-        // Create a new node which is going to store (B-1) keys
-        // of the full child.
-        let mut successor_sibling = BTreeListNode::new();
+    /// Return the index of the first element for which `pred` returns true.
+    ///
+    /// Each node's elements are scanned as a slice, rather than looking up each index in turn.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 4];
+    /// assert_eq!(list.position(|&x| x > 2), Some(2));
+    /// assert_eq!(list.position(|&x| x > 4), None);
+    /// ```
+    pub fn position<P>(&self, mut pred: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.root_node.as_ref()?.position(&mut pred).ok()
+    }
 
-        let full_child = &mut self.children[full_child_index];
-        let original_len = full_child.len();
-        assert!(full_child.is_full());
+    /// Return the index of the first element equal to `x`.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 2];
+    /// assert_eq!(list.index_of(&2), Some(1));
+    /// assert_eq!(list.index_of(&4), None);
+    /// ```
+    pub fn index_of(&self, x: &T) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        self.position(|e| e == x)
+    }
+
+    /// Create an iterator over the runs of elements separated by elements matching `pred`, as
+    /// with [`slice::split`].
+    ///
+    /// Each run is borrowed from the list as an [`Iter`] over it, which can be collected into a
+    /// new list if needed. The list is walked with a [`Cursor`] so finding all the runs takes
+    /// `O(n)` time.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let text = b"one\ntwo\n\nthree".iter().copied().collect::<BTreeList<u8>>();
+    /// let lines = text
+    ///     .split(|&b| b == b'\n')
+    ///     .map(|line| line.copied().collect::<Vec<_>>())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(lines, [&b"one"[..], b"two", b"", b"three"]);
+    /// ```
+    pub fn split<P>(&self, pred: P) -> Split<'_, T, P, B>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        Split {
+            list: self,
+            cursor: Some(Cursor::new(self, 0)),
+            pred,
+        }
+    }
+
+    /// Check whether the list starts with the elements in `needle`.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// assert!(list.starts_with(&[1, 2]));
+    /// assert!(!list.starts_with(&[2, 3]));
+    /// ```
+    pub fn starts_with(&self, needle: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        needle.len() <= self.len() && self.matches_at(0, needle)
+    }
+
+    /// Check whether the list ends with the elements in `needle`.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// assert!(list.ends_with(&[2, 3]));
+    /// assert!(!list.ends_with(&[1, 2]));
+    /// ```
+    pub fn ends_with(&self, needle: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        needle.len() <= self.len() && self.matches_at(self.len() - needle.len(), needle)
+    }
+
+    /// Check whether the list starts with the elements of the `needle` list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// # use btreelist::BTreeList;
+    /// let list = btreelist![1, 2, 3];
+    /// let mut needle: BTreeList<_, 32> = BTreeList::new();
+    /// needle.push(1);
+    /// needle.push(2);
+    /// assert!(list.starts_with_list(&needle));
+    /// assert!(!list.starts_with_list(&btreelist![2, 3]));
+    /// ```
+    pub fn starts_with_list<const C: usize>(&self, needle: &BTreeList<T, C>) -> bool
+    where
+        T: PartialEq,
+    {
+        needle.len() <= self.len() && self.matches_list_at(0, needle)
+    }
+
+    /// Check whether the list ends with the elements of the `needle` list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// assert!(list.ends_with_list(&btreelist![2, 3]));
+    /// assert!(!list.ends_with_list(&btreelist![1, 2]));
+    /// ```
+    pub fn ends_with_list<const C: usize>(&self, needle: &BTreeList<T, C>) -> bool
+    where
+        T: PartialEq,
+    {
+        needle.len() <= self.len() && self.matches_list_at(self.len() - needle.len(), needle)
+    }
+
+    /// Check whether the list holds the same elements as `other` yields, in order, without
+    /// collecting them.
+    ///
+    /// When the iterator's size hint is exact and differs from the length of the list no
+    /// elements are compared, otherwise comparison stops at the first difference.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// assert!(list.eq_iter(1..4));
+    /// assert!(list.eq_iter(&[1, 2, 3]));
+    /// assert!(!list.eq_iter((1..).take_while(|&i| i < 3)));
+    /// assert!(!list.eq_iter(vec![1, 2, 3, 4]));
+    /// ```
+    pub fn eq_iter<I>(&self, other: I) -> bool
+    where
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+        T: PartialEq,
+    {
+        let mut other = other.into_iter();
+        match other.size_hint() {
+            (lower, Some(upper)) if lower == upper && lower != self.len() => return false,
+            (lower, _) if lower > self.len() => return false,
+            _ => {}
+        }
+        self.elements()
+            .all(|element| matches!(other.next(), Some(o) if o.borrow() == element))
+            && other.next().is_none()
+    }
+
+    /// Check whether this list is a prefix of `other`, comparing node chunks at a time and
+    /// stopping at the first difference.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2];
+    /// assert!(list.is_prefix_of(&btreelist![1, 2, 3]));
+    /// assert!(!list.is_prefix_of(&btreelist![2, 3]));
+    /// ```
+    pub fn is_prefix_of<const C: usize>(&self, other: &BTreeList<T, C>) -> bool
+    where
+        T: PartialEq,
+    {
+        other.starts_with_list(self)
+    }
+
+    /// Check whether this list is a suffix of `other`, comparing node chunks at a time and
+    /// stopping at the first difference.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![2, 3];
+    /// assert!(list.is_suffix_of(&btreelist![1, 2, 3]));
+    /// assert!(!list.is_suffix_of(&btreelist![1, 2]));
+    /// ```
+    pub fn is_suffix_of<const C: usize>(&self, other: &BTreeList<T, C>) -> bool
+    where
+        T: PartialEq,
+    {
+        other.ends_with_list(self)
+    }
+
+    /// Get the range of indices of the contiguous run of elements stored with the one at
+    /// `index`, if it is in bounds.
+    ///
+    /// Runs are the elements of a leaf, or a single element between two children of an internal
+    /// node, and are the chunks visited by [`try_for_each_chunk`](Self::try_for_each_chunk).
+    /// Callers batching work over the list can line their batches up with these boundaries.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list = (0..100).collect::<BTreeList<_>>();
+    /// let chunk = list.nearest_boundary(42).unwrap();
+    /// assert!(chunk.contains(&42));
+    /// assert_eq!(list.nearest_boundary(chunk.end - 1), Some(chunk));
+    /// assert_eq!(list.nearest_boundary(100), None);
+    /// ```
+    pub fn nearest_boundary(&self, index: usize) -> Option<Range<usize>> {
+        self.root_node.as_ref()?.chunk_around(index)
+    }
+
+    /// Call `f` on each contiguous run of elements in order, stopping at and returning the first
+    /// error.
+    ///
+    /// The runs are the slices stored in the nodes, so this avoids the per-element lookups of
+    /// [`iter`](Self::iter) when the elements can be handled in bulk.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// # use std::io::Write;
+    /// let list = b"hello world".iter().copied().collect::<BTreeList<_>>();
+    /// let mut out = Vec::new();
+    /// list.try_for_each_chunk(|chunk| out.write_all(chunk)).unwrap();
+    /// assert_eq!(out, b"hello world");
+    ///
+    /// let mut seen = 0;
+    /// let found = list.try_for_each_chunk(|chunk| match chunk.iter().position(|&b| b == b' ') {
+    ///     Some(i) => Err(seen + i),
+    ///     None => {
+    ///         seen += chunk.len();
+    ///         Ok(())
+    ///     }
+    /// });
+    /// assert_eq!(found, Err(5));
+    /// ```
+    pub fn try_for_each_chunk<E, F>(&self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&[T]) -> Result<(), E>,
+    {
+        match self.try_for_each_chunk_from(0, |chunk| match f(chunk) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(e) => ControlFlow::Break(e),
+        }) {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(e) => Err(e),
+        }
+    }
+
+    /// Call `f` on each contiguous run of elements from `start` onwards, stopping at the first
+    /// break.
+    pub(crate) fn try_for_each_chunk_from<'a, R, F>(
+        &'a self,
+        start: usize,
+        mut f: F,
+    ) -> ControlFlow<R>
+    where
+        F: FnMut(&'a [T]) -> ControlFlow<R>,
+    {
+        match self.root_node.as_ref() {
+            Some(root) => root.try_for_each_chunk_from(start, &mut f),
+            None => ControlFlow::Continue(()),
+        }
+    }
+
+    /// The contiguous runs of elements in the list, in order, for editing in place.
+    pub(crate) fn chunks_mut(&mut self) -> Vec<&mut [T]> {
+        self.range_chunks_mut(0..self.len())
+    }
+
+    /// The contiguous runs of elements within `range`, which must be in the list, in order.
+    ///
+    /// This takes `O(k / B + log(n))` time for `k` elements in the range.
+    pub(crate) fn range_chunks_mut(&mut self, range: Range<usize>) -> Vec<&mut [T]> {
+        let mut chunks = Vec::new();
+        if let Some(root) = self.root_node.as_mut() {
+            root.collect_range_chunks_mut(range, &mut chunks);
+        }
+        chunks
+    }
+
+    /// Check whether the elements from `start` onwards begin with `needle`, comparing whole node
+    /// chunks at a time.
+    pub(crate) fn matches_at(&self, start: usize, mut needle: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        if needle.is_empty() {
+            return true;
+        }
+        let root = match self.root_node.as_ref() {
+            Some(root) => root,
+            None => return false,
+        };
+        let matched = root.try_for_each_chunk_from(start, &mut |chunk| {
+            let n = min(chunk.len(), needle.len());
+            if chunk[..n] != needle[..n] {
+                return ControlFlow::Break(false);
+            }
+            needle = &needle[n..];
+            if needle.is_empty() {
+                ControlFlow::Break(true)
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        matches!(matched, ControlFlow::Break(true))
+    }
+
+    /// Check whether the elements from `start` onwards begin with the `needle` list, comparing
+    /// each of its node chunks in turn.
+    fn matches_list_at<const C: usize>(&self, mut start: usize, needle: &BTreeList<T, C>) -> bool
+    where
+        T: PartialEq,
+    {
+        let root = match needle.root_node.as_ref() {
+            Some(root) => root,
+            None => return true,
+        };
+        root.try_for_each_chunk_from(0, &mut |chunk| {
+            if !self.matches_at(start, chunk) {
+                return ControlFlow::Break(());
+            }
+            start += chunk.len();
+            ControlFlow::Continue(())
+        })
+        .is_continue()
+    }
+
+    /// Rebuild the list with a different `B` parameter, moving each element out of the tree once
+    /// and filling the new nodes bottom-up in `O(n)` time.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let small: BTreeList<_, 2> = (0..100).collect();
+    /// let large = small.rechunk::<32>();
+    /// assert_eq!(large, (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn rechunk<const C: usize>(self) -> BTreeList<T, C> {
+        let len = self.len();
+        BTreeList::from_exact_iter(self.into_drain(), len)
+    }
+
+    /// Convert the list into a boxed slice, moving each element out of the tree once.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// assert_eq!(&*list.into_boxed_slice(), &[1, 2, 3]);
+    /// ```
+    pub fn into_boxed_slice(self) -> Box<[T]> {
+        self.into_drain().collect::<Vec<_>>().into_boxed_slice()
+    }
+
+    /// Format the nodes of the tree, with their elements, children and lengths, rather than just
+    /// the elements as [`Debug`](fmt::Debug) on the list does.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2];
+    /// assert!(format!("{:?}", list.debug_nodes()).contains("length: 2"));
+    /// ```
+    pub fn debug_nodes(&self) -> impl fmt::Debug + '_
+    where
+        T: fmt::Debug,
+    {
+        DebugNodes(self)
+    }
+
+    /// Iterate over the contiguous runs of elements stored in the nodes, in order.
+    ///
+    /// Each leaf gives a run, as does each element between two children of an internal node, so
+    /// the slices can be passed to APIs wanting slices without copying the list out first.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list = (0..100).collect::<BTreeList<_>>();
+    /// assert!(list.node_chunks().count() > 1);
+    /// assert_eq!(list.node_chunks().collect::<Vec<_>>().concat(), (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn node_chunks(&self) -> NodeChunks<'_, T, B> {
+        NodeChunks {
+            stack: Vec::new(),
+            next_node: self.root_node.as_ref(),
+        }
+    }
+
+    /// Consume the list, yielding its elements a leaf at a time as owned buffers.
+    ///
+    /// Each chunk is the `Vec` of a leaf node with the element that follows it from the node
+    /// above pushed onto the end, so the chunks concatenate to the list in order and the storage
+    /// can be taken over without moving elements one by one.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list = (0..100).collect::<BTreeList<_>>();
+    /// let chunks = list.into_leaf_chunks().collect::<Vec<_>>();
+    /// assert!(chunks.len() > 1);
+    /// assert_eq!(chunks.concat(), (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn into_leaf_chunks(self) -> LeafChunks<T, B> {
+        LeafChunks {
+            stack: Vec::new(),
+            next_node: self.root_node,
+        }
+    }
+
+    /// Copy the list, copying each node's elements as a single block of memory.
+    ///
+    /// This is equivalent to [`clone`](Clone::clone) but avoids going through [`Clone`] for each
+    /// element, which makes duplicating large lists of plain data faster.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3];
+    /// let copy = list.copy_list();
+    /// assert_eq!(list, copy);
+    /// ```
+    pub fn copy_list(&self) -> Self
+    where
+        T: Copy,
+    {
+        Self {
+            root_node: self.root_node.as_ref().map(|root| root.copy_node()),
+        }
+    }
+
+    /// Create a list holding the elements of this one `n` times over, as with [`slice::repeat`].
+    ///
+    /// The result is built by doubling, appending a copy of the list so far to itself, so the work
+    /// is proportional to the length of the result while the trees are only joined `O(log(n))`
+    /// times.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2];
+    /// assert_eq!(list.repeat(3), btreelist![1, 2, 1, 2, 1, 2]);
+    /// assert!(list.repeat(0).is_empty());
+    /// ```
+    pub fn repeat(&self, mut n: usize) -> Self
+    where
+        T: Clone,
+    {
+        let mut repeated = Self::new();
+        let mut doubled = self.clone();
+        while n > 0 {
+            if n & 1 == 1 {
+                repeated.append(&mut doubled.clone());
+            }
+            n >>= 1;
+            if n > 0 {
+                let mut copy = doubled.clone();
+                doubled.append(&mut copy);
+            }
+        }
+        repeated
+    }
+
+    /// Estimate the number of bytes the list has allocated on the heap, covering the nodes, the
+    /// element storage and the spare capacity reserved in each node.
+    ///
+    /// Only the list's own allocations are counted, not any heap memory the elements own.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list = (0..1000u64).collect::<BTreeList<_>>();
+    /// let before = list.memory_usage();
+    /// assert!(before >= 1000 * std::mem::size_of::<u64>());
+    /// list.shrink_to_fit();
+    /// assert!(list.memory_usage() < before);
+    /// assert_eq!(BTreeList::<u64>::new().memory_usage(), 0);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        self.root_node
+            .as_ref()
+            .map_or(0, |root| root.memory_usage())
+    }
+
+    /// Gather statistics about the shape of the tree, for choosing `B` or checking how full the
+    /// nodes of a workload are kept.
+    ///
+    /// The fill of a node is the fraction of its `2 * B - 1` element slots in use. This walks
+    /// every node so takes `O(n / B)` time.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list: BTreeList<u64, 2> = (0..100).collect();
+    /// let stats = list.stats();
+    /// assert!(stats.depth > 1);
+    /// assert!(stats.leaf_count < stats.node_count);
+    /// assert!(stats.min_fill >= 1.0 / 3.0 && stats.max_fill <= 1.0);
+    /// assert!(stats.min_fill <= stats.median_fill && stats.median_fill <= stats.max_fill);
+    /// assert_eq!(stats.element_bytes, 100 * 8);
+    /// assert_eq!(stats.element_bytes + stats.structure_bytes, list.memory_usage());
+    ///
+    /// let empty = BTreeList::<u64>::new().stats();
+    /// assert_eq!(empty.node_count, 0);
+    /// ```
+    pub fn stats(&self) -> TreeStats {
+        let mut fills = Vec::new();
+        let mut leaf_count = 0;
+        if let Some(root) = self.root_node.as_ref() {
+            root.collect_fills(&mut fills, &mut leaf_count);
+        }
+        fills.sort_unstable();
+        let slots = (2 * B - 1) as f64;
+        // the fill of the node at the given fraction through the sorted fills
+        let percentile = |p: f64| match fills.len() {
+            0 => 0.0,
+            len => fills[((len - 1) as f64 * p).round() as usize] as f64 / slots,
+        };
+        let element_bytes = self.len() * mem::size_of::<T>();
+        let allocated = self.memory_usage();
+        TreeStats {
+            depth: self.root_node.as_ref().map_or(0, |root| root.height() + 1),
+            node_count: fills.len(),
+            leaf_count,
+            mean_fill: match fills.len() {
+                0 => 0.0,
+                len => fills.iter().sum::<usize>() as f64 / (len as f64 * slots),
+            },
+            min_fill: percentile(0.0),
+            low_fill: percentile(0.1),
+            median_fill: percentile(0.5),
+            max_fill: percentile(1.0),
+            element_bytes,
+            structure_bytes: allocated - element_bytes,
+            element_ratio: match allocated {
+                0 => 0.0,
+                allocated => element_bytes as f64 / allocated as f64,
+            },
+        }
+    }
+
+    /// Render the structure of the tree as indented text, one node per line, giving each node's
+    /// length, number of elements and fill. The elements themselves are left out.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list: BTreeList<u8, 2> = (0..5).collect();
+    /// assert_eq!(
+    ///     list.dump_tree(),
+    ///     "internal: len 5, 1/3 elements (33% full)
+    /// |-- leaf: len 2, 2/3 elements (66% full)
+    /// `-- leaf: len 2, 2/3 elements (66% full)
+    /// "
+    /// );
+    /// ```
+    #[cfg(feature = "debug-tree")]
+    pub fn dump_tree(&self) -> String {
+        let mut out = String::new();
+        if let Some(root) = self.root_node.as_ref() {
+            root.dump_tree(&mut String::new(), "", &mut out);
+        }
+        out
+    }
+
+    /// Render the structure of the tree as a [Graphviz](https://graphviz.org) DOT graph, with a
+    /// vertex for each node labelled with its length, number of elements and fill.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list: BTreeList<u8, 2> = (0..5).collect();
+    /// let dot = list.to_dot();
+    /// assert!(dot.starts_with("digraph btreelist {"));
+    /// assert_eq!(dot.matches(" -> ").count(), 2);
+    /// ```
+    #[cfg(feature = "debug-tree")]
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph btreelist {\n    node [shape=box];\n");
+        if let Some(root) = self.root_node.as_ref() {
+            root.to_dot(&mut 0, &mut out);
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Check the invariants of the tree, returning the first one found broken.
+    ///
+    /// This checks that each node's stored length matches the elements below it, that nodes
+    /// other than the root hold between `B - 1` and `2 * B - 1` elements, that internal nodes
+    /// have one more child than elements, and that all leaves are at the same depth. It walks
+    /// every node so takes `O(n)` time, and is meant for tests and fuzzers asserting the health
+    /// of a list.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list: BTreeList<u32, 2> = (0..100).collect();
+    /// for i in (0..100).step_by(3) {
+    ///     list.remove(i / 3);
+    /// }
+    /// assert_eq!(list.validate(), Ok(()));
+    /// ```
+    #[cfg(feature = "validate")]
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        match self.root_node.as_ref() {
+            Some(root) => root.validate(true, &mut 0).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Repack the elements into as few nodes as the tree's height allows, undoing the drift
+    /// towards half empty nodes after a long run of inserts and removes.
+    ///
+    /// The tree is rebuilt in place in `O(n)` time, with no elements cloned. Follow this with
+    /// [`shrink_to_fit`](Self::shrink_to_fit) to also give back the room reserved in each node.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list: BTreeList<usize, 4> = (0..1000).collect();
+    /// for i in (0..1000).rev().step_by(2) {
+    ///     list.remove(i);
+    /// }
+    /// let before = list.stats();
+    /// list.compact();
+    /// let after = list.stats();
+    /// assert!(after.node_count < before.node_count);
+    /// assert!(after.mean_fill > before.mean_fill);
+    /// assert!(list.iter().copied().eq((0..1000).step_by(2)));
+    /// ```
+    pub fn compact(&mut self) {
+        let len = self.len();
+        let list = mem::take(self);
+        *self = Self::from_exact_iter(list.into_drain(), len);
+    }
+
+    /// Give back the memory the nodes have reserved beyond the elements they hold.
+    ///
+    /// Nodes allocate room for their maximum size up front, so lists that are mostly read after
+    /// being built or edited can use this to reclaim it. The shape of the tree is left as it is,
+    /// and nodes that are edited afterwards grow again as needed.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list = (0..100).collect::<BTreeList<_>>();
+    /// list.shrink_to_fit();
+    /// list.push(100);
+    /// assert!(list.iter().copied().eq(0..101));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        if let Some(root) = self.root_node.as_mut() {
+            root.shrink_to_fit();
+        }
+    }
+
+    /// Build a list from an iterator that yields exactly `len` elements.
+    ///
+    /// Nodes are filled bottom-up so this takes `O(len)` time, rather than the `O(len log(len))`
+    /// of pushing each element.
+    pub(crate) fn from_exact_iter<I: Iterator<Item = T>>(mut iter: I, len: usize) -> Self {
+        if len == 0 {
+            return Self::new();
+        }
+        let mut height = 0;
+        while BTreeListNode::<T, B>::capacity(height) < len {
+            height += 1;
+        }
+        Self {
+            root_node: Some(BTreeListNode::build(&mut iter, len, height, true)),
+        }
+    }
+
+    /// Consume the list, yielding the elements in order in `O(1)` amortized time each.
+    pub(crate) fn into_drain(self) -> NodeDrain<T, B> {
+        let mut drain = NodeDrain {
+            stack: Vec::new(),
+            remaining: self.len(),
+        };
+        if let Some(root) = self.root_node {
+            drain.push_leftmost(root);
+        }
+        drain
+    }
+
+    /// Walk the elements in order in `O(1)` amortized time each.
+    pub(crate) fn elements(&self) -> Elements<'_, T, B> {
+        let mut elements = Elements {
+            stack: Vec::new(),
+            remaining: self.len(),
+        };
+        if let Some(root) = self.root_node.as_ref() {
+            elements.push_leftmost(root);
+        }
+        elements
+    }
+}
+
+impl<T, const B: usize, const C: usize> BTreeList<BTreeList<T, C>, B> {
+    /// Join all of the lists into one, as with [`slice::concat`].
+    ///
+    /// The trees are joined directly with [`append`](BTreeList::append), taking `O(log(n))` time
+    /// for each list rather than pushing each element.
+    ///
+    /// ```
+    /// # use btreelist::{btreelist, BTreeList};
+    /// let lists: BTreeList<BTreeList<i32>> = btreelist![btreelist![1, 2], btreelist![], btreelist![3]];
+    /// assert_eq!(lists.concat(), btreelist![1, 2, 3]);
+    /// ```
+    pub fn concat(self) -> BTreeList<T, C> {
+        let mut joined = BTreeList::new();
+        for mut list in self {
+            joined.append(&mut list);
+        }
+        joined
+    }
+
+    /// Join all of the lists into one with a copy of `separator` between each, as with
+    /// [`slice::join`].
+    ///
+    /// Each separator is used as the join point between the trees, so this takes `O(log(n))`
+    /// time for each list.
+    ///
+    /// ```
+    /// # use btreelist::{btreelist, BTreeList};
+    /// let lists: BTreeList<BTreeList<i32>> = btreelist![btreelist![1, 2], btreelist![], btreelist![3]];
+    /// assert_eq!(lists.join(&0), btreelist![1, 2, 0, 0, 3]);
+    /// ```
+    pub fn join(self, separator: &T) -> BTreeList<T, C>
+    where
+        T: Clone,
+    {
+        let mut lists = self.into_iter();
+        let mut joined = lists.next().unwrap_or_default();
+        for list in lists {
+            joined = BTreeList::join_trees(joined, separator.clone(), list);
+        }
+        joined
+    }
+
+    /// Join all of the lists into one with a copy of the `separator` list between each.
+    ///
+    /// ```
+    /// # use btreelist::{btreelist, BTreeList};
+    /// let path: BTreeList<BTreeList<char>> = btreelist![btreelist!['a'], btreelist!['b', 'c']];
+    /// assert_eq!(path.join_list(&btreelist![':', ':']), btreelist!['a', ':', ':', 'b', 'c']);
+    /// ```
+    pub fn join_list(self, separator: &BTreeList<T, C>) -> BTreeList<T, C>
+    where
+        T: Clone,
+    {
+        let mut lists = self.into_iter();
+        let mut joined = lists.next().unwrap_or_default();
+        for mut list in lists {
+            joined.append(&mut separator.clone());
+            joined.append(&mut list);
+        }
+        joined
+    }
+}
+
+impl<T: Clone, const B: usize> Clone for BTreeListNode<T, B> {
+    /// Clones keep the full capacity of the nodes so that they can be edited without
+    /// reallocating.
+    fn clone(&self) -> Self {
+        let mut node = Self::with_capacity(self.is_leaf());
+        node.elements.extend_from_slice(&self.elements);
+        node.children.extend(self.children.iter().cloned());
+        node.length = self.length;
+        node
+    }
+
+    /// Clone the elements into the existing nodes, recursing into the children that are already
+    /// there and cloning the rest.
+    fn clone_from(&mut self, source: &Self) {
+        self.elements.clone_from(&source.elements);
+        self.children.clone_from(&source.children);
+        self.length = source.length;
+    }
+}
+
+impl<T, const B: usize> BTreeListNode<T, B> {
+    /// An empty node without any storage, for standing in while a node is moved out.
+    fn new() -> Self {
+        let () = BTreeList::<T, B>::VALID_B;
+        Self {
+            elements: Vec::new(),
+            children: Vec::new(),
+            length: 0,
+        }
+    }
+
+    /// An empty node with room for as many elements, and children if it isn't a leaf, as it can
+    /// hold before being split, so that filling it up never reallocates.
+    ///
+    /// Nodes go one element over the maximum of `2 * B - 1` before being split.
+    fn with_capacity(leaf: bool) -> Self {
+        let () = BTreeList::<T, B>::VALID_B;
+        Self {
+            elements: Vec::with_capacity(2 * B),
+            children: if leaf {
+                Vec::new()
+            } else {
+                Vec::with_capacity(2 * B + 1)
+            },
+            length: 0,
+        }
+    }
+
+    /// A leaf holding just `element`.
+    fn singleton(element: T) -> Self {
+        let mut node = Self::with_capacity(true);
+        node.elements.push(element);
+        node.length = 1;
+        node
+    }
+
+    /// Move the elements, and children if this isn't a leaf, from `element_index` and
+    /// `child_index` onwards into a new node with room to fill up. The lengths are left for the
+    /// caller to fix up.
+    fn split_off_sibling(&mut self, element_index: usize, child_index: usize) -> Self {
+        let mut sibling = Self::with_capacity(self.is_leaf());
+        move_tail(&mut self.elements, element_index, &mut sibling.elements);
+        if !self.is_leaf() {
+            move_tail(&mut self.children, child_index, &mut sibling.children);
+        }
+        sibling
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        from_length(self.length)
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// The index within this subtree of the first element under the child at `child_index`.
+    fn child_offset(&self, child_index: usize) -> usize {
+        self.children[..child_index]
+            .iter()
+            .map(Self::len)
+            .sum::<usize>()
+            + child_index
+    }
+
+    /// The index within this subtree of the element at `element_index`.
+    fn element_offset(&self, element_index: usize) -> usize {
+        if self.is_leaf() {
+            element_index
+        } else {
+            self.child_offset(element_index + 1) - 1
+        }
+    }
+
+    fn is_full(&self) -> bool {
+        let max = 2 * B - 1;
+        assert!(
+            self.elements.len() <= max,
+            "node shouldn't be over full-size"
+        );
+        self.elements.len() == max
+    }
+
+    /// Returns the child index and the given index adjusted for the cumulative index before that
+    /// child.
+    fn find_child_index(&self, index: usize) -> Option<(usize, usize)> {
+        let mut cumulative_len = 0;
+        for (child_index, child) in self.children.iter().enumerate() {
+            if cumulative_len + child.len() >= index {
+                return Some((child_index, index - cumulative_len));
+            } else {
+                cumulative_len += child.len() + 1;
+            }
+        }
+        None
+    }
+
+    fn insert_into_non_full_node(&mut self, index: usize, element: T) -> Result<(), T> {
+        assert!(!self.is_full());
+        if self.is_leaf() {
+            self.length = add_length(self.length, 1);
+            if index <= self.elements.len() {
+                self.elements.insert(index, element);
+                Ok(())
+            } else {
+                Err(element)
+            }
+        } else if let Some((child_index, sub_index)) = self.find_child_index(index) {
+            let child = in_bounds_mut(&mut self.children, child_index);
+
+            if child.is_full() {
+                self.split_child(child_index);
+
+                // child structure has changed so we need to find the index again
+                if let Some((child_index, sub_index)) = self.find_child_index(index) {
+                    let child = in_bounds_mut(&mut self.children, child_index);
+                    child.insert_into_non_full_node(sub_index, element)?;
+                } else {
+                    return Err(element);
+                }
+            } else {
+                child.insert_into_non_full_node(sub_index, element)?;
+            }
+            self.length = add_length(self.length, 1);
+            Ok(())
+        } else {
+            Err(element)
+        }
+    }
+
+    // A utility function to split the child `full_child_index` of this node
+    // Note that `full_child_index` must be full when this function is called.
+    fn split_child(&mut self, full_child_index: usize) {
+        let original_len_self = self.len();
+
+        let full_child = in_bounds_mut(&mut self.children, full_child_index);
+        let original_len = full_child.len();
+        assert!(full_child.is_full());
+
+        // Create a new node which is going to store (B-1) keys
+        // of the full child.
+        let mut successor_sibling = full_child.split_off_sibling(B, B);
+
+        let middle = full_child.elements.pop().unwrap();
+
+        full_child.recompute_len();
+        successor_sibling.recompute_len();
+
+        let z_len = successor_sibling.len();
+
+        let full_child_len = full_child.len();
+
+        self.children
+            .insert(full_child_index + 1, successor_sibling);
+
+        self.elements.insert(full_child_index, middle);
+
+        assert_eq!(full_child_len + z_len + 1, original_len);
+
+        assert_eq!(original_len_self, self.len());
+    }
+
+    fn remove_from_leaf(&mut self, index: usize) -> Option<T> {
+        self.length -= 1;
+        if index < self.elements.len() {
+            Some(self.elements.remove(index))
+        } else {
+            None
+        }
+    }
+
+    fn remove_element_from_non_leaf(&mut self, index: usize, element_index: usize) -> Option<T> {
+        self.length -= 1;
+        if self.children[element_index].elements.len() >= B {
+            let total_index = self.cumulative_index(element_index);
+            // recursively delete index - 1 in predecessor_node
+            let predecessor = self.children[element_index].remove(index - 1 - total_index)?;
+            // replace element with that one
+            Some(mem::replace(&mut self.elements[element_index], predecessor))
+        } else if self.children[element_index + 1].elements.len() >= B {
+            // recursively delete index + 1 in successor_node
+            let total_index = self.cumulative_index(element_index + 1);
+            let successor = self.children[element_index + 1].remove(index + 1 - total_index)?;
+            // replace element with that one
+            Some(mem::replace(&mut self.elements[element_index], successor))
+        } else {
+            let middle_element = self.elements.remove(element_index);
+            let successor_child = self.children.remove(element_index + 1);
+            self.children[element_index].merge(middle_element, successor_child);
+
+            let total_index = self.cumulative_index(element_index);
+            self.children[element_index].remove(index - total_index)
+        }
+    }
+
+    fn cumulative_index(&self, child_index: usize) -> usize {
+        self.children[0..child_index]
+            .iter()
+            .map(|c| c.len() + 1)
+            .sum()
+    }
+
+    fn remove_from_internal_child(&mut self, index: usize, mut child_index: usize) -> Option<T> {
+        if self.children[child_index].elements.len() < B
+            && if child_index > 0 {
+                self.children[child_index - 1].elements.len() < B
+            } else {
+                true
+            }
+            && if child_index + 1 < self.children.len() {
+                self.children[child_index + 1].elements.len() < B
+            } else {
+                true
+            }
+        {
+            // if the child and its immediate siblings have B-1 elements merge the child
+            // with one sibling, moving an element from this node into the new merged node
+            // to be the median
+
+            if child_index > 0 {
+                let middle = self.elements.remove(child_index - 1);
+
+                // use the predessor sibling
+                let successor = self.children.remove(child_index);
+                child_index -= 1;
+
+                self.children[child_index].merge(middle, successor);
+            } else {
+                let middle = self.elements.remove(child_index);
+
+                // use the sucessor sibling
+                let successor = self.children.remove(child_index + 1);
+
+                self.children[child_index].merge(middle, successor);
+            }
+        } else if self.children[child_index].elements.len() < B {
+            if child_index > 0
+                && matches!(self.children.get(child_index - 1), Some(c) if c.elements.len() >= B)
+            {
+                let last_element = self.children[child_index - 1].elements.pop().unwrap();
+                assert!(!self.children[child_index - 1].elements.is_empty());
+                self.children[child_index - 1].length -= 1;
+
+                let parent_element =
+                    mem::replace(&mut self.elements[child_index - 1], last_element);
+
+                self.children[child_index]
+                    .elements
+                    .insert(0, parent_element);
+                self.children[child_index].length =
+                    add_length(self.children[child_index].length, 1);
+
+                if let Some(last_child) = self.children[child_index - 1].children.pop() {
+                    self.children[child_index - 1].length -= last_child.length;
+                    self.children[child_index].length =
+                        add_length(self.children[child_index].length, last_child.length);
+                    self.children[child_index].children.insert(0, last_child);
+                }
+            } else if matches!(self.children.get(child_index + 1), Some(c) if c.elements.len() >= B)
+            {
+                let first_element = self.children[child_index + 1].elements.remove(0);
+                self.children[child_index + 1].length -= 1;
+
+                assert!(!self.children[child_index + 1].elements.is_empty());
+
+                let parent_element = mem::replace(&mut self.elements[child_index], first_element);
+
+                self.children[child_index].length =
+                    add_length(self.children[child_index].length, 1);
+                self.children[child_index].elements.push(parent_element);
+
+                if !self.children[child_index + 1].is_leaf() {
+                    let first_child = self.children[child_index + 1].children.remove(0);
+                    self.children[child_index + 1].length -= first_child.length;
+                    self.children[child_index].length =
+                        add_length(self.children[child_index].length, first_child.length);
+
+                    self.children[child_index].children.push(first_child);
+                }
+            }
+        }
+        self.length -= 1;
+        let total_index = self.cumulative_index(child_index);
+        self.children[child_index].remove(index - total_index)
+    }
+
+    /// Call `f` on each element in this subtree in order, stopping at the first break.
+    fn try_for_each<'a, R, F>(&'a self, f: &mut F) -> ControlFlow<R>
+    where
+        F: FnMut(&'a T) -> ControlFlow<R>,
+    {
+        let mut elements = self.elements.iter();
+        for child in &self.children {
+            child.try_for_each(f)?;
+            if let Some(element) = elements.next() {
+                f(element)?;
+            }
+        }
+        elements.try_for_each(f)
+    }
+
+    fn contains(&self, x: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.elements.contains(x) || self.children.iter().any(|c| c.contains(x))
+    }
+
+    /// Returns the index of the first element matching `pred` in this subtree, or the length of
+    /// the subtree if there isn't one.
+    fn position<P>(&self, pred: &mut P) -> Result<usize, usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        if self.is_leaf() {
+            return self
+                .elements
+                .iter()
+                .position(pred)
+                .ok_or(self.elements.len());
+        }
+        let mut offset = 0;
+        let mut elements = self.elements.iter();
+        for child in &self.children {
+            offset += match child.position(pred) {
+                Ok(index) => return Ok(offset + index),
+                Err(len) => len,
+            };
+            match elements.next() {
+                Some(element) if pred(element) => return Ok(offset),
+                _ => offset += 1,
+            }
+        }
+        Err(self.len())
+    }
+
+    /// Call `f` on each contiguous run of elements in this subtree in order, starting from the
+    /// element at `start`, stopping at the first break.
+    fn try_for_each_chunk_from<'a, R, F>(&'a self, start: usize, f: &mut F) -> ControlFlow<R>
+    where
+        F: FnMut(&'a [T]) -> ControlFlow<R>,
+    {
+        if self.is_leaf() {
+            return match self.elements.get(start..) {
+                Some(chunk) if !chunk.is_empty() => f(chunk),
+                _ => ControlFlow::Continue(()),
+            };
+        }
+        let mut child_start = 0;
+        for (child_index, child) in self.children.iter().enumerate() {
+            let child_end = child_start + child.len();
+            if start < child_end {
+                child.try_for_each_chunk_from(start.saturating_sub(child_start), f)?;
+            }
+            match self.elements.get(child_index) {
+                Some(element) if start <= child_end => f(slice::from_ref(element))?,
+                _ => {}
+            }
+            child_start = child_end + 1;
+        }
+        ControlFlow::Continue(())
+    }
+
+    /// Push each contiguous run of elements of this subtree within `range` onto `out`, in order.
+    fn collect_range_chunks_mut<'a>(&'a mut self, range: Range<usize>, out: &mut Vec<&'a mut [T]>) {
+        if range.is_empty() {
+            return;
+        }
+        if self.is_leaf() {
+            out.push(&mut self.elements[range]);
+            return;
+        }
+        let mut elements = self.elements.iter_mut();
+        let mut child_start = 0;
+        for child in self.children.iter_mut() {
+            let child_end = child_start + child.len();
+            if range.start < child_end && child_start < range.end {
+                let sub_range = range.start.saturating_sub(child_start)
+                    ..min(range.end, child_end) - child_start;
+                child.collect_range_chunks_mut(sub_range, out);
+            }
+            match elements.next() {
+                Some(element) if range.contains(&child_end) => out.push(slice::from_mut(element)),
+                _ => {}
+            }
+            child_start = child_end + 1;
+            if child_start >= range.end {
+                break;
+            }
+        }
+    }
+
+    /// Push a reference to each element of this subtree within `range` onto `out`, in order.
+    fn collect_range_mut<'a>(&'a mut self, range: Range<usize>, out: &mut Vec<&'a mut T>) {
+        if range.is_empty() {
+            return;
+        }
+        if self.is_leaf() {
+            out.extend(self.elements[range].iter_mut());
+            return;
+        }
+        let mut elements = self.elements.iter_mut();
+        let mut child_start = 0;
+        for child in self.children.iter_mut() {
+            let child_end = child_start + child.len();
+            if range.start < child_end && child_start < range.end {
+                let sub_range = range.start.saturating_sub(child_start)
+                    ..min(range.end, child_end) - child_start;
+                child.collect_range_mut(sub_range, out);
+            }
+            match elements.next() {
+                Some(element) if range.contains(&child_end) => out.push(element),
+                _ => {}
+            }
+            child_start = child_end + 1;
+            if child_start >= range.end {
+                break;
+            }
+        }
+    }
+
+    fn partition_point<P>(&self, pred: &mut P) -> usize
+    where
+        P: FnMut(&T) -> bool,
+    {
+        let mut node = self;
+        let mut offset = 0;
+        loop {
+            let element_index = node.elements.partition_point(|e| pred(e));
+            if node.is_leaf() {
+                return offset + element_index;
+            }
+            offset += node.cumulative_index(element_index);
+            node = &node.children[element_index];
+        }
+    }
+
+    fn copy_node(&self) -> Self
+    where
+        T: Copy,
+    {
+        let mut node = Self::with_capacity(self.is_leaf());
+        node.elements.extend_from_slice(&self.elements);
+        node.children
+            .extend(self.children.iter().map(|c| c.copy_node()));
+        node.length = self.length;
+        node
+    }
+
+    /// Describe this node's length, number of elements and fill on one line.
+    #[cfg(feature = "debug-tree")]
+    fn describe(&self) -> String {
+        let slots = 2 * B - 1;
+        format!(
+            "{}: len {}, {}/{} elements ({}% full)",
+            if self.is_leaf() { "leaf" } else { "internal" },
+            self.len(),
+            self.elements.len(),
+            slots,
+            self.elements.len() * 100 / slots,
+        )
+    }
+
+    /// Write a line for this node and each node below it to `out`, drawing the branches with
+    /// `prefix` and this node's `branch`.
+    #[cfg(feature = "debug-tree")]
+    fn dump_tree(&self, prefix: &mut String, branch: &str, out: &mut String) {
+        out.push_str(prefix);
+        out.push_str(branch);
+        out.push_str(&self.describe());
+        out.push('\n');
+        let indent = prefix.len();
+        prefix.push_str(match branch {
+            "|-- " => "|   ",
+            "`-- " => "    ",
+            _ => "",
+        });
+        for (child_index, child) in self.children.iter().enumerate() {
+            let last = child_index + 1 == self.children.len();
+            child.dump_tree(prefix, if last { "`-- " } else { "|-- " }, out);
+        }
+        prefix.truncate(indent);
+    }
+
+    /// Write a vertex for this node and each node below it to `out`, along with the edges to
+    /// their children, numbering them from `next_id`. Returns this node's id.
+    #[cfg(feature = "debug-tree")]
+    fn to_dot(&self, next_id: &mut usize, out: &mut String) -> usize {
+        let id = *next_id;
+        *next_id += 1;
+        out.push_str(&format!("    n{} [label=\"{}\"];\n", id, self.describe()));
+        for child in &self.children {
+            let child_id = child.to_dot(next_id, out);
+            out.push_str(&format!("    n{} -> n{};\n", id, child_id));
+        }
+        id
+    }
+
+    /// Check the invariants of this subtree, numbering the nodes in pre-order from `next_id`.
+    /// Returns the height of the subtree.
+    #[cfg(feature = "validate")]
+    fn validate(&self, root: bool, next_id: &mut usize) -> Result<usize, ValidationError> {
+        let node = *next_id;
+        *next_id += 1;
+        let elements = self.elements.len();
+        if elements >= 2 * B {
+            return Err(ValidationError::Overfull { node, elements });
+        }
+        if elements == 0 || (!root && elements < B - 1) {
+            return Err(ValidationError::Underfull { node, elements });
+        }
+        if !self.is_leaf() && self.children.len() != elements + 1 {
+            return Err(ValidationError::ChildCount {
+                node,
+                elements,
+                children: self.children.len(),
+            });
+        }
+        let mut actual = elements;
+        let mut height = None;
+        for child in &self.children {
+            let child_height = child.validate(false, next_id)?;
+            if matches!(height, Some(height) if height != child_height) {
+                return Err(ValidationError::UnevenDepth { node });
+            }
+            height = Some(child_height);
+            actual += child.len();
+        }
+        if self.len() != actual {
+            return Err(ValidationError::Length {
+                node,
+                stored: self.len(),
+                actual,
+            });
+        }
+        Ok(height.map_or(0, |height| height + 1))
+    }
+
+    /// Push the number of elements in each node of this subtree onto `fills`, counting the
+    /// leaves as they are found.
+    fn collect_fills(&self, fills: &mut Vec<usize>, leaf_count: &mut usize) {
+        fills.push(self.elements.len());
+        if self.is_leaf() {
+            *leaf_count += 1;
+        }
+        for child in &self.children {
+            child.collect_fills(fills, leaf_count);
+        }
+    }
+
+    /// The number of bytes allocated on the heap for this subtree's elements and children.
+    fn memory_usage(&self) -> usize {
+        self.elements.capacity() * mem::size_of::<T>()
+            + self.children.capacity() * mem::size_of::<Self>()
+            + self
+                .children
+                .iter()
+                .map(|child| child.memory_usage())
+                .sum::<usize>()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.elements.shrink_to_fit();
+        self.children.shrink_to_fit();
+        for child in &mut self.children {
+            child.shrink_to_fit();
+        }
+    }
+
+    /// The maximum number of elements a subtree of the given `height` can hold, where leaves have
+    /// height 0.
+    fn capacity(height: usize) -> usize {
+        (2 * B)
+            .checked_pow(height as u32 + 1)
+            .map_or(usize::MAX, |c| c - 1)
+    }
+
+    /// Build a node of the given `height` from the next `len` elements of `iter`, spreading them
+    /// as evenly as possible over its children.
+    fn build<I: Iterator<Item = T>>(iter: &mut I, len: usize, height: usize, root: bool) -> Self {
+        let () = BTreeList::<T, B>::VALID_B;
+        if height == 0 {
+            let mut leaf = Self::with_capacity(true);
+            leaf.elements.extend(iter.by_ref().take(len));
+            assert_eq!(leaf.elements.len(), len, "iterator ended early");
+            leaf.length = to_length(len);
+            return leaf;
+        }
+
+        // each child accounts for its own length plus the separating element after it
+        let units = len + 1;
+        let child_units = Self::capacity(height - 1).saturating_add(1);
+        let min_children = if root { 2 } else { B };
+        let num_children = max(min_children, (units - 1) / child_units + 1);
+        let (base, extra) = (units / num_children, units % num_children);
+
+        let mut node = Self::with_capacity(false);
+        node.length = to_length(len);
+        for child_index in 0..num_children {
+            let child_len = base + usize::from(child_index < extra) - 1;
+            node.children
+                .push(Self::build(iter, child_len, height - 1, false));
+            if child_index + 1 < num_children {
+                node.elements
+                    .push(iter.next().expect("iterator ended early"));
+            }
+        }
+        node
+    }
+
+    /// Check the stored lengths of this subtree, returning its number of elements.
+    #[cfg(any(test, feature = "strict-checks"))]
+    fn check(&self) -> usize {
+        let l = self.elements.len() + self.children.iter().map(|c| c.check()).sum::<usize>();
+        assert_eq!(self.len(), l);
+
+        l
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> Option<T> {
+        let original_len = self.len();
+        if self.is_leaf() {
+            let v = self.remove_from_leaf(index);
+            assert_eq!(original_len, self.len() + 1);
+            #[cfg(feature = "strict-checks")]
+            assert_eq!(self.check(), self.len());
+            v
+        } else {
+            let mut total_index = 0;
+            for (child_index, child) in self.children.iter().enumerate() {
+                match (total_index + child.len()).cmp(&index) {
+                    Ordering::Less => {
+                        // should be later on in the loop
+                        total_index += child.len() + 1;
+                        continue;
+                    }
+                    Ordering::Equal => {
+                        let v = self.remove_element_from_non_leaf(
+                            index,
+                            min(child_index, self.elements.len() - 1),
+                        );
+                        assert_eq!(original_len, self.len() + 1);
+                        #[cfg(feature = "strict-checks")]
+                        assert_eq!(self.check(), self.len());
+                        return v;
+                    }
+                    Ordering::Greater => {
+                        let v = self.remove_from_internal_child(index, child_index);
+                        assert_eq!(original_len, self.len() + 1);
+                        #[cfg(feature = "strict-checks")]
+                        assert_eq!(self.check(), self.len());
+                        return v;
+                    }
+                }
+            }
+            None
+        }
+    }
+
+    fn merge(&mut self, middle: T, successor_sibling: BTreeListNode<T, B>) {
+        self.join_sibling(middle, successor_sibling);
+        assert!(self.is_full());
+    }
+
+    /// Append `middle` and then the contents of `successor_sibling` to this node.
+    fn join_sibling(&mut self, middle: T, successor_sibling: BTreeListNode<T, B>) {
+        self.elements.push(middle);
+        self.elements.extend(successor_sibling.elements);
+        self.children.extend(successor_sibling.children);
+        self.length = add_length(self.length, add_length(successor_sibling.length, 1));
+    }
+
+    /// The number of levels below this node.
+    fn height(&self) -> usize {
+        let mut height = 0;
+        let mut node = self;
+        while let Some(child) = node.children.first() {
+            height += 1;
+            node = child;
+        }
+        height
+    }
+
+    fn recompute_len(&mut self) {
+        self.length =
+            to_length(self.elements.len() + self.children.iter().map(|c| c.len()).sum::<usize>());
+    }
+
+    /// Move the first element (and child) of child `child_index + 1` to the end of child
+    /// `child_index`, through the separating element.
+    fn rotate_left(&mut self, child_index: usize) {
+        let right = &mut self.children[child_index + 1];
+        let element = right.elements.remove(0);
+        let child = if right.is_leaf() {
+            None
+        } else {
+            Some(right.children.remove(0))
+        };
+        let moved = add_length(1, child.as_ref().map_or(0, |c| c.length));
+        right.length -= moved;
+
+        let separator = mem::replace(&mut self.elements[child_index], element);
+        let left = &mut self.children[child_index];
+        left.elements.push(separator);
+        left.children.extend(child);
+        left.length = add_length(left.length, moved);
+    }
+
+    /// Move the last element (and child) of child `child_index` to the start of child
+    /// `child_index + 1`, through the separating element.
+    fn rotate_right(&mut self, child_index: usize) {
+        let left = &mut self.children[child_index];
+        let element = left.elements.pop().unwrap();
+        let child = left.children.pop();
+        let moved = add_length(1, child.as_ref().map_or(0, |c| c.length));
+        left.length -= moved;
+
+        let separator = mem::replace(&mut self.elements[child_index], element);
+        let right = &mut self.children[child_index + 1];
+        right.elements.insert(0, separator);
+        if let Some(child) = child {
+            right.children.insert(0, child);
+        }
+        right.length = add_length(right.length, moved);
+    }
+
+    /// Merge child `child_index + 1` and the element before it into child `child_index`.
+    fn merge_children(&mut self, child_index: usize) {
+        let middle = self.elements.remove(child_index);
+        let successor = self.children.remove(child_index + 1);
+        self.children[child_index].join_sibling(middle, successor);
+    }
+
+    /// Bring child `child_index` back up to the minimum size if it is below it, by merging with
+    /// or borrowing from a sibling.
+    fn fix_underfull_child(&mut self, child_index: usize) {
+        if self.children[child_index].elements.len() >= B - 1 {
+            return;
+        }
+        let left_index = child_index.saturating_sub(1);
+        if self.children[left_index].elements.len() + self.children[left_index + 1].elements.len()
+            < 2 * B - 1
+        {
+            self.merge_children(left_index);
+        } else if left_index == child_index {
+            while self.children[child_index].elements.len() < B - 1 {
+                self.rotate_left(child_index);
+            }
+        } else {
+            while self.children[child_index].elements.len() < B - 1 {
+                self.rotate_right(left_index);
+            }
+        }
+    }
+
+    /// Split this node if it has gone over the maximum size, returning the median and the new
+    /// successor sibling.
+    fn split_overfull(&mut self) -> Option<(T, Self)> {
+        if self.elements.len() < 2 * B {
+            return None;
+        }
+        let mut sibling = self.split_off_sibling(B + 1, B + 1);
+        let median = self.elements.pop().unwrap();
+        self.recompute_len();
+        sibling.recompute_len();
+        Some((median, sibling))
+    }
+
+    /// Join two trees and a separator between them into a single tree.
+    fn join(left: Self, separator: T, right: Self) -> Self {
+        let (left_height, right_height) = (left.height(), right.height());
+        let (mut root, overflow) = match left_height.cmp(&right_height) {
+            Ordering::Equal => {
+                if left.elements.len() + right.elements.len() < 2 * B - 1 {
+                    let mut root = left;
+                    root.join_sibling(separator, right);
+                    return root;
+                }
+                let mut root = Self::with_capacity(false);
+                root.length = add_length(add_length(left.length, right.length), 1);
+                root.elements.push(separator);
+                root.children.push(left);
+                root.children.push(right);
+                root.fix_underfull_child(0);
+                root.fix_underfull_child(1);
+                return root;
+            }
+            Ordering::Greater => {
+                let mut root = left;
+                let overflow = root.join_right(left_height, separator, right, right_height);
+                (root, overflow)
+            }
+            Ordering::Less => {
+                let mut root = right;
+                let overflow = root.join_left(right_height, left, separator, left_height);
+                (root, overflow)
+            }
+        };
+        if let Some((median, sibling)) = overflow {
+            root.grow(median, sibling);
+        }
+        root
+    }
+
+    /// Make this root node the first child of a new root, with `median` and `sibling` after it.
+    fn grow(&mut self, median: T, sibling: Self) {
+        let old_root = mem::replace(self, Self::with_capacity(false));
+        self.length = add_length(add_length(old_root.length, sibling.length), 1);
+        self.elements.push(median);
+        self.children.push(old_root);
+        self.children.push(sibling);
+    }
+
+    /// Push `element` onto the right edge of this subtree, returning the median and new sibling
+    /// if this node had to be split.
+    fn push_back(&mut self, element: T) -> Option<(T, Self)> {
+        self.length = add_length(self.length, 1);
+        match self.children.last_mut() {
+            Some(last) => {
+                if let Some((median, sibling)) = last.push_back(element) {
+                    self.elements.push(median);
+                    self.children.push(sibling);
+                }
+            }
+            None => self.elements.push(element),
+        }
+        self.split_overfull()
+    }
+
+    /// Remove the last element of this subtree, topping the last child back up if it runs low.
+    fn pop_back(&mut self) -> T {
+        self.length -= 1;
+        if self.is_leaf() {
+            return self
+                .elements
+                .pop()
+                .expect("non-root nodes shouldn't be empty");
+        }
+        let last = self.children.len() - 1;
+        let element = self.children[last].pop_back();
+        self.fix_underfull_child(last);
+        element
+    }
+
+    /// Remove the first element of this subtree, topping the first child back up if it runs low.
+    fn pop_front(&mut self) -> T {
+        self.length -= 1;
+        if self.is_leaf() {
+            return self.elements.remove(0);
+        }
+        let element = self.children[0].pop_front();
+        self.fix_underfull_child(0);
+        element
+    }
+
+    /// Push `element` onto the left edge of this subtree, returning the median and new sibling
+    /// if this node had to be split.
+    fn push_front(&mut self, element: T) -> Option<(T, Self)> {
+        self.length = add_length(self.length, 1);
+        match self.children.first_mut() {
+            Some(first) => {
+                if let Some((median, sibling)) = first.push_front(element) {
+                    self.elements.insert(0, median);
+                    self.children.insert(1, sibling);
+                }
+            }
+            None => self.elements.insert(0, element),
+        }
+        self.split_overfull()
+    }
+
+    /// Join the shorter tree `right` onto the right edge of this subtree of `height`, returning
+    /// the median and new sibling if this node had to be split.
+    fn join_right(
+        &mut self,
+        height: usize,
+        separator: T,
+        right: Self,
+        right_height: usize,
+    ) -> Option<(T, Self)> {
+        self.length = add_length(self.length, add_length(right.length, 1));
+        if height == right_height + 1 {
+            self.elements.push(separator);
+            self.children.push(right);
+            self.fix_underfull_child(self.children.len() - 1);
+        } else {
+            let last = self.children.len() - 1;
+            if let Some((median, sibling)) =
+                self.children[last].join_right(height - 1, separator, right, right_height)
+            {
+                self.elements.push(median);
+                self.children.push(sibling);
+            }
+        }
+        self.split_overfull()
+    }
+
+    /// Join the shorter tree `left` onto the left edge of this subtree of `height`, returning
+    /// the median and new sibling if this node had to be split.
+    fn join_left(
+        &mut self,
+        height: usize,
+        left: Self,
+        separator: T,
+        left_height: usize,
+    ) -> Option<(T, Self)> {
+        self.length = add_length(self.length, add_length(left.length, 1));
+        if height == left_height + 1 {
+            self.elements.insert(0, separator);
+            self.children.insert(0, left);
+            self.fix_underfull_child(0);
+        } else if let Some((median, sibling)) =
+            self.children[0].join_left(height - 1, left, separator, left_height)
+        {
+            self.elements.insert(0, median);
+            self.children.insert(1, sibling);
+        }
+        self.split_overfull()
+    }
+
+    /// Split this subtree into the elements before `at` and those from `at` onwards.
+    fn split_at(mut self, at: usize) -> (BTreeList<T, B>, BTreeList<T, B>) {
+        if self.is_leaf() {
+            let mut right = self.split_off_sibling(at, 0);
+            self.length = to_length(self.elements.len());
+            right.length = to_length(right.elements.len());
+            return (BTreeList::from_node(self), BTreeList::from_node(right));
+        }
+
+        // find the child that `at` falls in, or just after
+        let mut child_index = 0;
+        let mut child_start = 0;
+        while at > child_start + self.children[child_index].len() {
+            child_start += self.children[child_index].len() + 1;
+            child_index += 1;
+        }
+
+        let suffix_children = self.children.split_off(child_index + 1);
+        let mut suffix_elements = self.elements.split_off(child_index);
+        let child = self.children.pop().unwrap();
+        let (child_left, child_right) = child.split_at(at - child_start);
+
+        let left = match self.elements.pop() {
+            Some(separator) => {
+                self.recompute_len();
+                BTreeList::join_trees(BTreeList::from_node(self), separator, child_left)
+            }
+            None => child_left,
+        };
+        let right = if suffix_elements.is_empty() {
+            child_right
+        } else {
+            let separator = suffix_elements.remove(0);
+            let mut suffix = Self {
+                elements: suffix_elements,
+                children: suffix_children,
+                length: 0,
+            };
+            suffix.recompute_len();
+            BTreeList::join_trees(child_right, separator, BTreeList::from_node(suffix))
+        };
+        (left, right)
+    }
+
+    pub(crate) fn set(&mut self, index: usize, element: T) -> Result<T, T> {
+        if self.is_leaf() {
+            let old_element = self.elements.get_mut(index).unwrap();
+            Ok(mem::replace(old_element, element))
+        } else {
+            let mut cumulative_len = 0;
+            for (child_index, child) in self.children.iter_mut().enumerate() {
+                match (cumulative_len + child.len()).cmp(&index) {
+                    Ordering::Less => {
+                        cumulative_len += child.len() + 1;
+                    }
+                    Ordering::Equal => {
+                        let old_element = self.elements.get_mut(child_index).unwrap();
+                        return Ok(mem::replace(old_element, element));
+                    }
+                    Ordering::Greater => {
+                        return child.set(index - cumulative_len, element);
+                    }
+                }
+            }
+            // can't set so return the original element
+            Err(element)
+        }
+    }
+
+    /// Get the element at `index`, which must be less than the length of this subtree.
+    fn get_in_bounds(&self, index: usize) -> &T {
+        if self.is_leaf() {
+            return in_bounds(&self.elements, index);
+        }
+        let mut cumulative_len = 0;
+        for (child_index, child) in self.children.iter().enumerate() {
+            match (cumulative_len + child.len()).cmp(&index) {
+                Ordering::Less => {
+                    cumulative_len += child.len() + 1;
+                }
+                Ordering::Equal => {
+                    return in_bounds(&self.elements, child_index);
+                }
+                Ordering::Greater => {
+                    return child.get_in_bounds(index - cumulative_len);
+                }
+            }
+        }
+        unreachable!("index should be within the subtree")
+    }
+
+    /// The range of indices in this subtree of the contiguous run of elements holding `index`.
+    fn chunk_around(&self, index: usize) -> Option<Range<usize>> {
+        let mut node = self;
+        let mut offset = 0;
+        let mut index = index;
+        'descend: while !node.is_leaf() {
+            let mut cumulative_len = 0;
+            for (child_index, child) in node.children.iter().enumerate() {
+                match (cumulative_len + child.len()).cmp(&index) {
+                    Ordering::Less => cumulative_len += child.len() + 1,
+                    Ordering::Equal if child_index < node.elements.len() => {
+                        let start = offset + index;
+                        return Some(start..start + 1);
+                    }
+                    Ordering::Equal => return None,
+                    Ordering::Greater => {
+                        node = child;
+                        offset += cumulative_len;
+                        index -= cumulative_len;
+                        continue 'descend;
+                    }
+                }
+            }
+            return None;
+        }
+        if index < node.elements.len() {
+            Some(offset..offset + node.elements.len())
+        } else {
+            None
+        }
+    }
+
+    /// The leftmost element in this subtree, nodes are never empty.
+    fn first_mut(&mut self) -> &mut T {
+        let mut node = self;
+        while !node.is_leaf() {
+            node = &mut node.children[0];
+        }
+        &mut node.elements[0]
+    }
+
+    /// The rightmost element in this subtree, nodes are never empty.
+    fn last_mut(&mut self) -> &mut T {
+        let mut node = self;
+        while let Some(child) = node.children.last_mut() {
+            node = child;
+        }
+        node.elements.last_mut().unwrap()
+    }
+
+    /// The leftmost and rightmost elements in this subtree, if they are different.
+    fn first_and_last_mut(&mut self) -> Option<(&mut T, &mut T)> {
+        if self.is_leaf() {
+            let (first, rest) = self.elements.split_first_mut()?;
+            Some((first, rest.last_mut()?))
+        } else {
+            let (first, rest) = self.children.split_first_mut()?;
+            Some((first.first_mut(), rest.last_mut()?.last_mut()))
+        }
+    }
+
+    /// Get the element at `index` mutably, which must be less than the length of this subtree.
+    fn get_in_bounds_mut(&mut self, index: usize) -> &mut T {
+        if self.is_leaf() {
+            return in_bounds_mut(&mut self.elements, index);
+        }
+        let mut cumulative_len = 0;
+        for (child_index, child) in self.children.iter_mut().enumerate() {
+            match (cumulative_len + child.len()).cmp(&index) {
+                Ordering::Less => {
+                    cumulative_len += child.len() + 1;
+                }
+                Ordering::Equal => {
+                    return in_bounds_mut(&mut self.elements, child_index);
+                }
+                Ordering::Greater => {
+                    return child.get_in_bounds_mut(index - cumulative_len);
+                }
+            }
+        }
+        unreachable!("index should be within the subtree")
+    }
+}
+
+/// Index into the elements or children of a node at an index found from the lengths of the
+/// tree.
+#[cfg(not(feature = "unsafe"))]
+fn in_bounds<T>(elements: &[T], index: usize) -> &T {
+    &elements[index]
+}
+
+/// Index into the elements or children of a node at an index found from the lengths of the
+/// tree.
+#[cfg(feature = "unsafe")]
+#[allow(unsafe_code)]
+fn in_bounds<T>(elements: &[T], index: usize) -> &T {
+    debug_assert!(index < elements.len());
+    // SAFETY: the index was found by descending from a root whose length was checked against,
+    // and the length of each node is kept equal to the number of elements under it.
+    unsafe { elements.get_unchecked(index) }
+}
+
+/// Index into the elements or children of a node mutably at an index found from the lengths of
+/// the tree.
+#[cfg(not(feature = "unsafe"))]
+fn in_bounds_mut<T>(elements: &mut [T], index: usize) -> &mut T {
+    &mut elements[index]
+}
+
+/// Index into the elements or children of a node mutably at an index found from the lengths of
+/// the tree.
+#[cfg(feature = "unsafe")]
+#[allow(unsafe_code)]
+fn in_bounds_mut<T>(elements: &mut [T], index: usize) -> &mut T {
+    debug_assert!(index < elements.len());
+    // SAFETY: as for `in_bounds`.
+    unsafe { elements.get_unchecked_mut(index) }
+}
+
+/// Move the items of `from` from index `at` onwards onto the end of `to`, keeping their order.
+#[cfg(not(feature = "unsafe"))]
+fn move_tail<T>(from: &mut Vec<T>, at: usize, to: &mut Vec<T>) {
+    to.extend(from.drain(at..));
+}
+
+/// Move the items of `from` from index `at` onwards onto the end of `to`, keeping their order.
+///
+/// Nodes reserve room for their maximum size up front, so the items are copied straight into the
+/// uninitialized spare capacity of `to` in one go.
+#[cfg(feature = "unsafe")]
+#[allow(unsafe_code)]
+fn move_tail<T>(from: &mut Vec<T>, at: usize, to: &mut Vec<T>) {
+    assert!(at <= from.len());
+    let count = from.len() - at;
+    to.reserve(count);
+    let spare = to.spare_capacity_mut();
+    // SAFETY: `at..at + count` holds initialized items of `from`, and `spare` has room for at
+    // least `count` items after reserving. Shortening `from` first hands ownership of the moved
+    // items over to `to` without either vector dropping them, and nothing in between can panic.
+    unsafe {
+        from.set_len(at);
+        std::ptr::copy_nonoverlapping(from.as_ptr().add(at), spare.as_mut_ptr().cast::<T>(), count);
+        to.set_len(to.len() + count);
+    }
+}
+
+/// Consumes the nodes of a tree, yielding their elements in order.
+pub(crate) struct NodeDrain<T, const B: usize> {
+    stack: Vec<(vec::IntoIter<T>, vec::IntoIter<BTreeListNode<T, B>>)>,
+    remaining: usize,
+}
+
+impl<T, const B: usize> NodeDrain<T, B> {
+    fn push_leftmost(&mut self, mut node: BTreeListNode<T, B>) {
+        loop {
+            let mut children = mem::take(&mut node.children).into_iter();
+            let first_child = children.next();
+            self.stack
+                .push((mem::take(&mut node.elements).into_iter(), children));
+            match first_child {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+    }
+}
+
+impl<T, const B: usize> Iterator for NodeDrain<T, B> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (elements, children) = self.stack.last_mut()?;
+            if let Some(element) = elements.next() {
+                // the child after this element holds the next elements in order
+                if let Some(child) = children.next() {
+                    self.push_leftmost(child);
+                }
+                self.remaining -= 1;
+                return Some(element);
+            }
+            self.stack.pop();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for NodeDrain<T, B> {}
+
+/// Suggest a `B` parameter for lists of `T` so that a full node's elements fit in about
+/// `target_node_bytes`, along with the shape of nodes it gives.
+///
+/// The suggestion is the largest `B` whose full nodes of `2 * B - 1` elements fit in the target,
+/// and never less than 2. Zero-sized elements are counted as a byte each.
+///
+/// ```
+/// # use btreelist::{estimate_b_for, BTreeList};
+/// let estimate = estimate_b_for::<u64>(256);
+/// assert_eq!(estimate.b, 16);
+/// assert_eq!(estimate.min_elements, 15);
+/// assert_eq!(estimate.max_elements, 31);
+/// assert_eq!(estimate.max_element_bytes, 248);
+///
+/// let list: BTreeList<u64, 16> = BTreeList::new();
+/// ```
+pub fn estimate_b_for<T>(target_node_bytes: usize) -> NodeEstimate {
+    let element_bytes = max(mem::size_of::<T>(), 1);
+    // the largest b with 2 * b - 1 <= n, rounding n / 2 up
+    let n = target_node_bytes / element_bytes;
+    let b = max(n - n / 2, 2);
+    NodeEstimate {
+        b,
+        min_elements: b - 1,
+        max_elements: 2 * b - 1,
+        max_element_bytes: (2 * b - 1) * mem::size_of::<T>(),
+    }
+}
+
+/// A suggested `B` parameter and the shape of the nodes it gives, from [`estimate_b_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeEstimate {
+    /// The suggested `B` parameter.
+    pub b: usize,
+    /// The fewest elements a node other than the root holds.
+    pub min_elements: usize,
+    /// The most elements a node holds.
+    pub max_elements: usize,
+    /// The size in bytes of the elements of a full node.
+    pub max_element_bytes: usize,
+}
+
+/// Statistics about the shape of a [`BTreeList`], from [`BTreeList::stats`].
+///
+/// Fills are the fraction of a node's `2 * B - 1` element slots in use, and are all zero for an
+/// empty list.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeStats {
+    /// The number of levels of nodes, zero for an empty list.
+    pub depth: usize,
+    /// The number of nodes in the tree.
+    pub node_count: usize,
+    /// The number of nodes without children.
+    pub leaf_count: usize,
+    /// The mean fill over all nodes.
+    pub mean_fill: f64,
+    /// The fill of the emptiest node.
+    pub min_fill: f64,
+    /// The 10th percentile fill.
+    pub low_fill: f64,
+    /// The median fill.
+    pub median_fill: f64,
+    /// The fill of the fullest node.
+    pub max_fill: f64,
+    /// The bytes taken by the elements themselves.
+    pub element_bytes: usize,
+    /// The bytes allocated for nodes and spare capacity, on top of the elements.
+    pub structure_bytes: usize,
+    /// The fraction of the allocated bytes taken by the elements.
+    pub element_ratio: f64,
+}
+
+/// A broken invariant of a [`BTreeList`], from [`BTreeList::validate`].
+///
+/// Nodes are identified by their position in a pre-order walk of the tree, with the root at 0.
+#[cfg(feature = "validate")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The length stored in a node doesn't match the number of elements in its subtree.
+    Length {
+        /// The position of the node.
+        node: usize,
+        /// The length stored in the node.
+        stored: usize,
+        /// The number of elements in the node's subtree.
+        actual: usize,
+    },
+    /// A node holds more than `2 * B - 1` elements.
+    Overfull {
+        /// The position of the node.
+        node: usize,
+        /// The number of elements in the node.
+        elements: usize,
+    },
+    /// A node holds no elements, or fewer than `B - 1` when it isn't the root.
+    Underfull {
+        /// The position of the node.
+        node: usize,
+        /// The number of elements in the node.
+        elements: usize,
+    },
+    /// An internal node doesn't have one more child than elements.
+    ChildCount {
+        /// The position of the node.
+        node: usize,
+        /// The number of elements in the node.
+        elements: usize,
+        /// The number of children of the node.
+        children: usize,
+    },
+    /// The children of a node have subtrees of different heights.
+    UnevenDepth {
+        /// The position of the node.
+        node: usize,
+    },
+}
+
+#[cfg(feature = "validate")]
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::Length {
+                node,
+                stored,
+                actual,
+            } => write!(
+                f,
+                "node {} stores length {} but holds {} elements",
+                node, stored, actual
+            ),
+            ValidationError::Overfull { node, elements } => {
+                write!(f, "node {} is overfull with {} elements", node, elements)
+            }
+            ValidationError::Underfull { node, elements } => {
+                write!(f, "node {} is underfull with {} elements", node, elements)
+            }
+            ValidationError::ChildCount {
+                node,
+                elements,
+                children,
+            } => write!(
+                f,
+                "node {} has {} children for {} elements",
+                node, children, elements
+            ),
+            ValidationError::UnevenDepth { node } => {
+                write!(f, "the children of node {} have different heights", node)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "validate")]
+impl std::error::Error for ValidationError {}
+
+/// An iterator over the contiguous runs of elements in the nodes of a [`BTreeList`].
+///
+/// Created by [`BTreeList::node_chunks`].
+#[derive(Debug, Clone)]
+pub struct NodeChunks<'a, T, const B: usize> {
+    /// The internal nodes above the next leaf, with their remaining elements and children.
+    stack: Vec<(slice::Iter<'a, T>, slice::Iter<'a, BTreeListNode<T, B>>)>,
+    /// The subtree holding the next leaf, if it comes before the next element up the stack.
+    next_node: Option<&'a BTreeListNode<T, B>>,
+}
+
+impl<'a, T, const B: usize> Iterator for NodeChunks<'a, T, B> {
+    type Item = &'a [T];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(mut node) = self.next_node.take() {
+            while !node.is_leaf() {
+                let mut children = node.children.iter();
+                let first_child = children.next();
+                self.stack.push((node.elements.iter(), children));
+                node = first_child.expect("internal nodes should have children");
+            }
+            return Some(&node.elements);
+        }
+        // after a leaf comes the first remaining element up the stack, with the subtree after it
+        // holding the next leaf
+        while let Some((elements, children)) = self.stack.last_mut() {
+            if let Some(separator) = elements.next() {
+                self.next_node = children.next();
+                return Some(slice::from_ref(separator));
+            }
+            self.stack.pop();
+        }
+        None
+    }
+}
+
+/// An iterator over the leaves of a [`BTreeList`], yielding each as an owned buffer.
+///
+/// Created by [`BTreeList::into_leaf_chunks`].
+#[derive(Debug)]
+pub struct LeafChunks<T, const B: usize> {
+    /// The internal nodes above the next leaf, with their remaining elements and children.
+    stack: Vec<(vec::IntoIter<T>, vec::IntoIter<BTreeListNode<T, B>>)>,
+    /// The subtree holding the next leaf.
+    next_node: Option<BTreeListNode<T, B>>,
+}
+
+impl<T, const B: usize> Iterator for LeafChunks<T, B> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut node = self.next_node.take()?;
+        while !node.is_leaf() {
+            let mut children = mem::take(&mut node.children).into_iter();
+            let first_child = children.next();
+            self.stack
+                .push((mem::take(&mut node.elements).into_iter(), children));
+            node = first_child.expect("internal nodes should have children");
+        }
+        let mut chunk = node.elements;
+        // the next element in order is the first remaining one up the stack, with the subtree
+        // after it holding the next leaf
+        while let Some((elements, children)) = self.stack.last_mut() {
+            if let Some(separator) = elements.next() {
+                chunk.push(separator);
+                self.next_node = children.next();
+                break;
+            }
+            self.stack.pop();
+        }
+        Some(chunk)
+    }
+}
+
+/// The path from the root of a tree down to one of its elements.
+///
+/// Each entry is a node and the index of the child it was descended through, apart from the last
+/// which is the node holding the element and the index of the element in it. An empty path is
+/// past the end of the list.
+#[derive(Debug, Clone)]
+pub(crate) struct Path<'a, T, const B: usize> {
+    stack: Vec<(&'a BTreeListNode<T, B>, usize)>,
+}
+
+impl<'a, T, const B: usize> Path<'a, T, B> {
+    /// Find the path to the element at `index`, or an empty path if it is out of bounds.
+    pub(crate) fn seek(list: &'a BTreeList<T, B>, index: usize) -> Self {
+        let mut path = Self { stack: Vec::new() };
+        match list.root_node.as_ref() {
+            Some(root) if index < root.len() => path.descend(root, index),
+            _ => {}
+        }
+        path
+    }
+
+    /// Move the path from the element at `from` to the element at `index`, only walking up as far
+    /// as the lowest node holding both.
+    pub(crate) fn seek_from(&mut self, list: &'a BTreeList<T, B>, from: usize, index: usize) {
+        let (mut node, element_index) = match self.stack.pop() {
+            Some(top) if index < list.len() => top,
+            _ => {
+                *self = Self::seek(list, index);
+                return;
+            }
+        };
+        let mut start = from - node.element_offset(element_index);
+        while index < start || index >= start + node.len() {
+            let (parent, child_index) = self
+                .stack
+                .pop()
+                .expect("root should hold every index in bounds");
+            start -= parent.child_offset(child_index);
+            node = parent;
+        }
+        self.descend(node, index - start);
+    }
+
+    /// Push the path from `node` down to the element at `index` within it.
+    fn descend(&mut self, mut node: &'a BTreeListNode<T, B>, mut index: usize) {
+        loop {
+            if node.is_leaf() {
+                self.stack.push((node, index));
+                return;
+            }
+            let mut child_start = 0;
+            for (child_index, child) in node.children.iter().enumerate() {
+                let child_end = child_start + child.len();
+                if index <= child_end {
+                    self.stack.push((node, child_index));
+                    if index == child_end {
+                        return;
+                    }
+                    index -= child_start;
+                    node = child;
+                    break;
+                }
+                child_start = child_end + 1;
+            }
+        }
+    }
+
+    pub(crate) fn get(&self) -> Option<&'a T> {
+        let (node, index) = self.stack.last()?;
+        node.elements.get(*index)
+    }
+
+    /// Move to the next element in order, leaving the path empty after the last.
+    pub(crate) fn move_next(&mut self) {
+        let (node, index) = match self.stack.last_mut() {
+            Some(top) => top,
+            None => return,
+        };
+        if !node.is_leaf() {
+            *index += 1;
+            let child = &node.children[*index];
+            self.push_leftmost(child);
+        } else if *index + 1 < node.elements.len() {
+            *index += 1;
+        } else {
+            self.stack.pop();
+            // find the first ancestor with an element after the child we came up from
+            while let Some((node, index)) = self.stack.last() {
+                if *index < node.elements.len() {
+                    return;
+                }
+                self.stack.pop();
+            }
+        }
+    }
+
+    /// Move to the previous element in order, returning false if there isn't one.
+    pub(crate) fn move_prev(&mut self) -> bool {
+        let (node, index) = match self.stack.last_mut() {
+            Some(top) => top,
+            None => return false,
+        };
+        if !node.is_leaf() {
+            let child = &node.children[*index];
+            self.push_rightmost(child);
+            return true;
+        } else if *index > 0 {
+            *index -= 1;
+            return true;
+        }
+        // find the first ancestor with an element before the child we came up from
+        let ancestors = &self.stack[..self.stack.len() - 1];
+        if ancestors.iter().all(|(_, index)| *index == 0) {
+            return false;
+        }
+        self.stack.pop();
+        while let Some((_, index)) = self.stack.last_mut() {
+            if *index > 0 {
+                *index -= 1;
+                return true;
+            }
+            self.stack.pop();
+        }
+        false
+    }
+
+    /// Get the next element in order without moving.
+    pub(crate) fn peek_next(&self) -> Option<&'a T> {
+        let (node, index) = self.stack.last()?;
+        if !node.is_leaf() {
+            let mut child = &node.children[*index + 1];
+            while let Some(first) = child.children.first() {
+                child = first;
+            }
+            return child.elements.first();
+        }
+        if let Some(element) = node.elements.get(*index + 1) {
+            return Some(element);
+        }
+        self.stack[..self.stack.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|(node, index)| node.elements.get(*index))
+    }
+
+    /// Get the previous element in order without moving.
+    pub(crate) fn peek_prev(&self) -> Option<&'a T> {
+        let (node, index) = self.stack.last()?;
+        if !node.is_leaf() {
+            let mut child = &node.children[*index];
+            while let Some(last) = child.children.last() {
+                child = last;
+            }
+            return child.elements.last();
+        }
+        if *index > 0 {
+            return node.elements.get(*index - 1);
+        }
+        self.stack[..self.stack.len() - 1]
+            .iter()
+            .rev()
+            .find_map(|(node, index)| index.checked_sub(1).map(|i| &node.elements[i]))
+    }
+
+    /// Descend to the first element of `node`, the top of the stack having been moved to it.
+    fn push_leftmost(&mut self, mut node: &'a BTreeListNode<T, B>) {
+        while let Some(first) = node.children.first() {
+            self.stack.push((node, 0));
+            node = first;
+        }
+        self.stack.push((node, 0));
+    }
+
+    /// Descend to the last element of `node`, the top of the stack having been moved to it.
+    fn push_rightmost(&mut self, mut node: &'a BTreeListNode<T, B>) {
+        while let Some(last) = node.children.last() {
+            self.stack.push((node, node.children.len() - 1));
+            node = last;
+        }
+        self.stack.push((node, node.elements.len() - 1));
+    }
+}
+
+/// The path from the root of a tree down to one of its elements, with each node along it taken
+/// out of its parent so that the focused node can be edited without descending again.
+///
+/// The taken out children are left as empty nodes in their parents until the zipper moves back up
+/// through them, at which point the parent's length is adjusted by however much the child changed.
+#[derive(Debug)]
+pub(crate) struct Zipper<T, const B: usize> {
+    /// The ancestors of the focused node, each with the index of the child taken out of it and
+    /// the length that child had when it was taken.
+    ancestors: Vec<(BTreeListNode<T, B>, usize, usize)>,
+    focus: BTreeListNode<T, B>,
+    /// The index of the element within the focused node.
+    element_index: usize,
+}
+
+impl<T, const B: usize> Zipper<T, B> {
+    /// Take the tree out of `list` and open it at the element at `index`, which must be in
+    /// bounds.
+    pub(crate) fn open(list: &mut BTreeList<T, B>, index: usize) -> Self {
+        let root = list.root_node.take().expect("opened an empty list");
+        debug_assert!(index < root.len());
+        let mut zipper = Self {
+            ancestors: Vec::new(),
+            focus: root,
+            element_index: 0,
+        };
+        zipper.descend_to(index);
+        zipper
+    }
+
+    /// Move from the element at `from` to the element at `index`, which must be in bounds, only
+    /// walking up as far as the lowest node holding both.
+    pub(crate) fn seek_from(&mut self, from: usize, index: usize) {
+        let mut start = from - self.focus.element_offset(self.element_index);
+        while index < start || index >= start + self.focus.len() {
+            let child_index = self
+                .ascend()
+                .expect("root should hold every index in bounds");
+            start -= self.focus.child_offset(child_index);
+        }
+        self.descend_to(index - start);
+    }
+
+    /// Descend from the focused node to the element at `index` within it.
+    fn descend_to(&mut self, mut index: usize) {
+        while !self.focus.is_leaf() {
+            let mut child_index = 0;
+            for child in &self.focus.children {
+                if index <= child.len() {
+                    break;
+                }
+                index -= child.len() + 1;
+                child_index += 1;
+            }
+            if index == self.focus.children[child_index].len() {
+                self.element_index = child_index;
+                return;
+            }
+            self.descend(child_index);
+        }
+        self.element_index = index;
+    }
+
+    /// Put the tree back together in `list`.
+    pub(crate) fn close(mut self, list: &mut BTreeList<T, B>) {
+        while self.ascend().is_some() {}
+        list.root_node = Some(self.focus);
+    }
+
+    /// Take the child at `child_index` out of the focused node and focus on it.
+    fn descend(&mut self, child_index: usize) {
+        let child = mem::replace(&mut self.focus.children[child_index], BTreeListNode::new());
+        let child_len = child.len();
+        let parent = mem::replace(&mut self.focus, child);
+        self.ancestors.push((parent, child_index, child_len));
+    }
+
+    /// Put the focused node back in its parent and focus on that, returning the index of the
+    /// child we came up from.
+    fn ascend(&mut self) -> Option<usize> {
+        let (parent, child_index, child_len) = self.ancestors.pop()?;
+        let mut child = mem::replace(&mut self.focus, parent);
+        self.focus.length = to_length(self.focus.len() - child_len + child.len());
+        mem::swap(&mut self.focus.children[child_index], &mut child);
+        Some(child_index)
+    }
+
+    pub(crate) fn get(&self) -> &T {
+        &self.focus.elements[self.element_index]
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut T {
+        &mut self.focus.elements[self.element_index]
+    }
+
+    /// Move to the next element in order, returning false if there isn't one in which case the
+    /// zipper is left at the root and should be closed.
+    pub(crate) fn move_next(&mut self) -> bool {
+        if self.focus.is_leaf() {
+            self.element_index += 1;
+            return self.settle();
+        }
+        self.descend(self.element_index + 1);
+        while !self.focus.is_leaf() {
+            self.descend(0);
+        }
+        self.element_index = 0;
+        true
+    }
+
+    /// Move to the previous element in order, returning false if there isn't one in which case
+    /// the zipper is left at the root and should be closed.
+    pub(crate) fn move_prev(&mut self) -> bool {
+        if !self.focus.is_leaf() {
+            self.descend(self.element_index);
+            while !self.focus.is_leaf() {
+                self.descend(self.focus.children.len() - 1);
+            }
+            self.element_index = self.focus.elements.len() - 1;
+            return true;
+        }
+        if self.element_index > 0 {
+            self.element_index -= 1;
+            return true;
+        }
+        // find the first ancestor with an element before the child we came up from
+        while let Some(child_index) = self.ascend() {
+            if child_index > 0 {
+                self.element_index = child_index - 1;
+                return true;
+            }
+        }
+        false
+    }
+
+    /// If the element index has run off the end of the focused leaf, move up to the first
+    /// ancestor with an element after it, returning false if there isn't one.
+    fn settle(&mut self) -> bool {
+        while self.element_index >= self.focus.elements.len() {
+            match self.ascend() {
+                Some(child_index) => self.element_index = child_index,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Insert `element` next to the focused element, before it or after it, if the focused node
+    /// is a leaf with room for it. The zipper stays on the same element.
+    pub(crate) fn insert_in_leaf(&mut self, element: T, after: bool) -> Result<(), T> {
+        if !self.focus.is_leaf() || self.focus.is_full() {
+            return Err(element);
+        }
+        if after {
+            self.focus.elements.insert(self.element_index + 1, element);
+        } else {
+            self.focus.elements.insert(self.element_index, element);
+            self.element_index += 1;
+        }
+        self.focus.length = add_length(self.focus.length, 1);
+        Ok(())
+    }
+
+    /// Remove the focused element if it is in a leaf that can spare it, moving on to the next
+    /// element. Also returns whether there was a next element to move to.
+    pub(crate) fn remove_from_leaf(&mut self) -> Option<(T, bool)> {
+        let min = if self.ancestors.is_empty() { 1 } else { B - 1 };
+        if !self.focus.is_leaf() || self.focus.elements.len() <= min {
+            return None;
+        }
+        let element = self.focus.elements.remove(self.element_index);
+        self.focus.length -= 1;
+        Some((element, self.settle()))
+    }
+}
+
+/// Walks the nodes of a tree, yielding references to their elements in order.
+pub(crate) struct Elements<'a, T, const B: usize> {
+    stack: Vec<(slice::Iter<'a, T>, slice::Iter<'a, BTreeListNode<T, B>>)>,
+    remaining: usize,
+}
+
+impl<'a, T, const B: usize> Elements<'a, T, B> {
+    fn push_leftmost(&mut self, mut node: &'a BTreeListNode<T, B>) {
+        loop {
+            let mut children = node.children.iter();
+            let first_child = children.next();
+            self.stack.push((node.elements.iter(), children));
+            match first_child {
+                Some(child) => node = child,
+                None => break,
+            }
+        }
+    }
+}
+
+impl<'a, T, const B: usize> Iterator for Elements<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (elements, children) = self.stack.last_mut()?;
+            if let Some(element) = elements.next() {
+                // the child after this element holds the next elements in order
+                if let Some(child) = children.next() {
+                    self.push_leftmost(child);
+                }
+                self.remaining -= 1;
+                return Some(element);
+            }
+            self.stack.pop();
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T, const B: usize> ExactSizeIterator for Elements<'a, T, B> {}
+
+impl<'a, T, const B: usize> Clone for Elements<'a, T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            stack: self.stack.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+impl<T, const B: usize> From<BTreeList<T, B>> for Box<[T]> {
+    fn from(list: BTreeList<T, B>) -> Self {
+        list.into_boxed_slice()
+    }
+}
+
+/// `clone_from` reuses the nodes already in the destination list, cloning the source's elements
+/// into them and only allocating for the parts of the tree the destination is missing.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// let source = (0..100).collect::<BTreeList<_>>();
+/// let mut frame = BTreeList::<_>::new();
+/// for _ in 0..3 {
+///     frame.clone_from(&source);
+///     assert_eq!(frame, source);
+/// }
+/// ```
+impl<T, const B: usize> Clone for BTreeList<T, B>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            root_node: self.root_node.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.root_node.clone_from(&source.root_node);
+    }
+}
+
+impl<T, const B: usize> Default for BTreeList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Compares the elements of the lists in order, whatever their `B` parameters.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::BTreeList;
+/// let mut small: BTreeList<_, 2> = BTreeList::new();
+/// small.push(1);
+/// small.push(2);
+/// assert!(small == btreelist![1, 2]);
+/// ```
+impl<T, const B: usize, const C: usize> PartialEq<BTreeList<T, C>> for BTreeList<T, B>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &BTreeList<T, C>) -> bool {
+        self.len() == other.len() && self.elements().zip(other.elements()).all(|(a, b)| a == b)
+    }
+}
+
+/// Implement equality both ways between lists and sequence types with slice-like `len` and
+/// `iter` methods, given as the type holding `U` to compare a list against and the same type
+/// holding `T` to compare against a list.
+macro_rules! impl_slice_eq {
+    ($($other:ty => $this:ty),* $(,)?) => {
+        $(
+            impl<T, U, const B: usize> PartialEq<$other> for BTreeList<T, B>
+            where
+                T: PartialEq<U>,
+            {
+                fn eq(&self, other: &$other) -> bool {
+                    self.len() == other.len()
+                        && self.elements().zip(other.iter()).all(|(a, b)| a == b)
+                }
+            }
+
+            impl<T, U, const B: usize> PartialEq<BTreeList<U, B>> for $this
+            where
+                T: PartialEq<U>,
+            {
+                fn eq(&self, other: &BTreeList<U, B>) -> bool {
+                    self.len() == other.len()
+                        && self.iter().zip(other.elements()).all(|(a, b)| a == b)
+                }
+            }
+        )*
+    };
+}
+
+impl_slice_eq! {
+    [U] => [T],
+    &[U] => &[T],
+    &mut [U] => &mut [T],
+    Vec<U> => Vec<T>,
+}
+
+/// Together with [`Hash`] this lets lists be used as keys in hash maps and sets.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::BTreeList;
+/// # use std::collections::HashMap;
+/// let mut memo = HashMap::new();
+/// memo.insert(btreelist![1, 2], "first");
+/// let mut key = BTreeList::new();
+/// key.push(2);
+/// key.push_front(1);
+/// assert_eq!(memo.get(&key), Some(&"first"));
+/// ```
+impl<T, const B: usize> Eq for BTreeList<T, B> where T: Eq {}
+
+/// Compares the lists lexicographically, as for [`Vec`].
+///
+/// ```
+/// # use btreelist::btreelist;
+/// assert!(btreelist![1, 2, 3] < btreelist![1, 3]);
+/// assert!(btreelist![1, 2] < btreelist![1, 2, 0]);
+/// assert_eq!(btreelist![btreelist![2], btreelist![1, 5]].iter().min(), Some(&btreelist![1, 5]));
+/// ```
+impl<T, const B: usize> PartialOrd for BTreeList<T, B>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.elements().partial_cmp(other.elements())
+    }
+}
+
+impl<T, const B: usize> Ord for BTreeList<T, B>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.elements().cmp(other.elements())
+    }
+}
+
+/// Formats a list as its tree of nodes, from [`BTreeList::debug_nodes`].
+struct DebugNodes<'a, T, const B: usize>(&'a BTreeList<T, B>);
+
+impl<'a, T, const B: usize> fmt::Debug for DebugNodes<'a, T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BTreeList")
+            .field("root_node", &self.0.root_node)
+            .finish()
+    }
+}
+
+/// Formats the elements as a list, as for [`Vec`]. Use
+/// [`debug_nodes`](BTreeList::debug_nodes) to see the structure of the tree instead.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// assert_eq!(format!("{:?}", btreelist![1, 2, 3]), "[1, 2, 3]");
+/// ```
+impl<T, const B: usize> fmt::Debug for BTreeList<T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.elements()).finish()
+    }
+}
+
+/// Hashes the length and then each element in order, so lists with equal contents hash equally
+/// whatever the shape of their trees.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::BTreeList;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::{Hash, Hasher};
+/// fn hash_of(list: &BTreeList<i32>) -> u64 {
+///     let mut hasher = DefaultHasher::new();
+///     list.hash(&mut hasher);
+///     hasher.finish()
+/// }
+///
+/// let mut pushed = BTreeList::new();
+/// pushed.push(2);
+/// pushed.push_front(1);
+/// assert_eq!(hash_of(&pushed), hash_of(&btreelist![1, 2]));
+/// ```
+impl<T, const B: usize> Hash for BTreeList<T, B>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for element in self.elements() {
+            element.hash(state);
+        }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a BTreeList<T, B> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            inner: self,
+            index: 0,
+            index_back: self.len(),
+        }
+    }
+}
+
+impl<T, const B: usize> IntoIterator for BTreeList<T, B> {
+    type Item = T;
+
+    type IntoIter = OwnedIter<T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        OwnedIter { inner: self }
+    }
+}
+
+impl<T, const B: usize> Extend<T> for BTreeList<T, B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push_back(item)
+        }
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for BTreeList<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut l = BTreeList::new();
+        for item in iter {
+            l.push_back(item);
+        }
+        l
+    }
+}
+
+impl<T, const B: usize> Index<usize> for BTreeList<T, B> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T, const B: usize> IndexMut<usize> for BTreeList<T, B> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        self.get_mut(index).unwrap()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use crate::btreelist;
+
+    use super::*;
+
+    #[test]
+    fn push_back() {
+        let mut t = BTreeList::<_>::default();
+
+        t.push_back(());
+        t.push_back(());
+        t.push_back(());
+        t.push_back(());
+        t.push_back(());
+        t.push_back(());
+        t.push_back(());
+        t.push_back(());
+    }
+
+    #[test]
+    fn insert() {
+        let mut t = BTreeList::<_>::default();
+
+        t.insert(0, ()).unwrap();
+        t.insert(1, ()).unwrap();
+        t.insert(0, ()).unwrap();
+        t.insert(0, ()).unwrap();
+        t.insert(0, ()).unwrap();
+        t.insert(3, ()).unwrap();
+        t.insert(4, ()).unwrap();
+    }
+
+    #[test]
+    fn insert_book() {
+        let mut t = BTreeList::<_>::default();
+
+        for i in 0..100 {
+            t.insert(i % 2, ()).unwrap();
+        }
+    }
+
+    #[test]
+    fn insert_book_vec() {
+        let mut t = BTreeList::<_>::default();
+        let mut v = Vec::new();
+
+        for i in 0..100 {
+            t.insert(i % 3, ()).unwrap();
+            v.insert(i % 3, ());
+
+            assert_eq!(v, t.iter().copied().collect::<Vec<_>>())
+        }
+    }
+
+    #[test]
+    fn iter_forth_back() {
+        let mut t = BTreeList::<_>::default();
+
+        t.push_back(1);
+        t.push_back(2);
+        t.push_back(3);
+        t.push_back(4);
+        t.push_back(5);
+
+        let mut i = t.iter();
+        assert_eq!(i.next(), Some(&1));
+        assert_eq!(i.next(), Some(&2));
+        assert_eq!(i.next(), Some(&3));
+        assert_eq!(i.next(), Some(&4));
+        assert_eq!(i.next(), Some(&5));
+        assert_eq!(i.next(), None);
+
+        let mut i = t.iter();
+        assert_eq!(i.next_back(), Some(&5));
+        assert_eq!(i.next_back(), Some(&4));
+        assert_eq!(i.next_back(), Some(&3));
+        assert_eq!(i.next_back(), Some(&2));
+        assert_eq!(i.next_back(), Some(&1));
+        assert_eq!(i.next_back(), None);
+
+        let mut i = t.iter();
+        assert_eq!(i.next(), Some(&1));
+        assert_eq!(i.next_back(), Some(&5));
+        assert_eq!(i.next_back(), Some(&4));
+        assert_eq!(i.next_back(), Some(&3));
+        assert_eq!(i.next_back(), Some(&2));
+        assert_eq!(i.next_back(), None);
+
+        let mut i = t.iter();
+        assert_eq!(i.next(), Some(&1));
+        assert_eq!(i.next(), Some(&2));
+        assert_eq!(i.next(), Some(&3));
+        assert_eq!(i.next(), Some(&4));
+        assert_eq!(i.next_back(), Some(&5));
+        assert_eq!(i.next_back(), None);
+    }
+
+    #[test]
+    fn first_last() {
+        let mut t = BTreeList::<_>::default();
+        t.push_back(1);
+        t.push_back(2);
+        t.push_back(3);
+
+        assert_eq!(t.first(), Some(&1));
+        assert_eq!(t.first_mut(), Some(&mut 1));
+        assert_eq!(t.last(), Some(&3));
+        assert_eq!(t.last_mut(), Some(&mut 3));
+    }
+
+    #[test]
+    fn first_last_empty() {
+        let mut t = BTreeList::<usize>::default();
+        assert_eq!(t.last(), None);
+        assert_eq!(t.last_mut(), None);
+        t.push_back(1);
+        t.pop_back();
+        assert_eq!(t.first(), None);
+        assert_eq!(t.last(), None);
+        assert_eq!(t.last_mut(), None);
+    }
+
+    #[test]
+    fn pop() {
+        let mut t = BTreeList::<_>::default();
+
+        t.push_back(1);
+        t.push_back(2);
+        t.push_back(3);
+
+        assert_eq!(t.pop_back(), Some(3));
+        assert_eq!(t.pop_back(), Some(2));
+        assert_eq!(t.pop_back(), Some(1));
+        assert_eq!(t.pop_back(), None);
+    }
+
+    #[test]
+    fn set_no_panic() {
+        let mut t = BTreeList::<_>::default();
+        assert_eq!(t.set(0, 1), Err(1));
+        t.push(1);
+        assert_eq!(t.set(0, 2), Ok(1));
+        assert_eq!(t.set(1, 3), Err(3));
+
+        // past the end of a leaf root and of a deeper tree
+        let mut t = BTreeList::<usize, 2>::from_exact_iter(0..3, 3);
+        assert_eq!(t.set(3, 10), Err(10));
+        let mut t = BTreeList::<usize, 2>::from_exact_iter(0..100, 100);
+        assert_eq!(t.set(100, 10), Err(10));
+        assert_eq!(t.set(1000, 10), Err(10));
+        assert_eq!(t.set(99, 10), Ok(99));
+    }
+
+    #[test]
+    fn remove_no_panic() {
+        let mut t = BTreeList::<_>::default();
+        assert_eq!(t.remove(0), None);
+        assert_eq!(t.remove(1), None);
+        t.push(1);
+        assert_eq!(t.remove(0), Some(1));
+    }
+
+    #[test]
+    fn insert_no_panic() {
+        let mut t = BTreeList::<_>::default();
+        assert_eq!(t.insert(10, 1), Err(1));
+        assert_eq!(t.insert(1, 1), Err(1));
+        assert_eq!(t.insert(0, 1), Ok(()));
+        assert_eq!(t.insert(10, 1), Err(1));
+    }
+
+    #[test]
+    fn generic_b() {
+        fn push_a_few<const B: usize>(mut t: BTreeList<usize, B>) {
+            t.push(1);
+            t.push(2);
+            t.push(3);
+        }
+
+        let t: BTreeList<usize> = BTreeList::new();
+        let t6: BTreeList<usize, 6> = BTreeList::new();
+        let t3: BTreeList<usize, 3> = BTreeList::new();
+        let t7: BTreeList<usize, 7> = BTreeList::new();
+        let t32: BTreeList<usize, 32> = BTreeList::new();
+
+        push_a_few(t);
+        push_a_few(t6);
+        push_a_few(t3);
+        push_a_few(t7);
+        push_a_few(t32);
+    }
+
+    #[test]
+    fn swap() {
+        let mut t = BTreeList::<_>::default();
+
+        t.push(1);
+        t.push(2);
+        t.push(3);
+
+        t.swap(0, 2);
+        assert_eq!(t, btreelist![3, 2, 1]);
+        t.swap(2, 0);
+        assert_eq!(t, btreelist![1, 2, 3]);
+        t.swap(1, 0);
+        assert_eq!(t, btreelist![2, 1, 3]);
+        t.swap(0, 1);
+        assert_eq!(t, btreelist![1, 2, 3]);
+        assert!(!t.swap(0, 4));
+        assert!(!t.swap(5, 4));
+    }
+
+    /// Assert the node fill and depth invariants of the tree.
+    pub(crate) fn assert_shape<T, const B: usize>(list: &BTreeList<T, B>) {
+        fn depth<T, const B: usize>(node: &BTreeListNode<T, B>, root: bool) -> usize {
+            assert!(node.elements.len() < 2 * B);
+            assert!(root || node.elements.len() >= B - 1);
+            assert!(!node.elements.is_empty());
+            if node.is_leaf() {
+                return 0;
+            }
+            assert_eq!(node.children.len(), node.elements.len() + 1);
+            let depths = node
+                .children
+                .iter()
+                .map(|c| depth(c, false))
+                .collect::<Vec<_>>();
+            assert!(depths.iter().all(|d| *d == depths[0]));
+            depths[0] + 1
+        }
+        if let Some(root) = list.root_node.as_ref() {
+            root.check();
+            depth(root, true);
+        }
+    }
 
-        successor_sibling.elements = full_child.elements.split_off(B);
+    /// A linear congruential generator for the randomised tests, so they make the same edits on
+    /// every run.
+    pub(crate) struct TestRng(usize);
 
-        if !full_child.is_leaf() {
-            successor_sibling.children = full_child.children.split_off(B);
+    impl TestRng {
+        pub(crate) fn new(seed: usize) -> Self {
+            Self(seed)
         }
 
-        let middle = full_child.elements.pop().unwrap();
-
-        full_child.length =
-            full_child.elements.len() + full_child.children.iter().map(|c| c.len()).sum::<usize>();
+        /// Get the next number, from the high bits of the state.
+        pub(crate) fn next(&mut self) -> usize {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0 >> 33
+        }
+    }
 
-        successor_sibling.length = successor_sibling.elements.len()
-            + successor_sibling
-                .children
-                .iter()
-                .map(|c| c.len())
-                .sum::<usize>();
+    #[test]
+    fn node_capacity() {
+        fn full_capacity<T, const B: usize>(node: &BTreeListNode<T, B>) -> bool {
+            node.elements.capacity() >= 2 * B
+                && (node.is_leaf() || node.children.capacity() > 2 * B)
+                && node.children.iter().all(full_capacity)
+        }
+        fn capacity<const B: usize>() {
+            let built = BTreeList::<usize, B>::from_exact_iter(0..500, 500);
+            let mut pushed = BTreeList::<usize, B>::new();
+            let mut inserted = BTreeList::<usize, B>::new();
+            for i in 0..500 {
+                pushed.push_back(i);
+                pushed.push_front(i);
+                inserted.insert(inserted.len() / 2, i).unwrap();
+            }
+            for list in [&built, &pushed, &inserted, &inserted.clone()] {
+                assert!(full_capacity(list.root_node.as_ref().unwrap()));
+            }
+        }
+        capacity::<2>();
+        capacity::<3>();
+        capacity::<6>();
+    }
 
-        let z_len = successor_sibling.len();
+    #[test]
+    fn clone_from() {
+        fn clone_into<const B: usize>() {
+            let lens = [0, 1, 2 * B, 100, 1000];
+            for source_len in lens {
+                for dest_len in lens {
+                    let source = BTreeList::<usize, B>::from_exact_iter(0..source_len, source_len);
+                    let mut dest = BTreeList::<usize, B>::from_exact_iter(
+                        (0..dest_len).map(|i| i + 1),
+                        dest_len,
+                    );
+                    dest.clone_from(&source);
+                    assert_shape(&dest);
+                    assert_eq!(dest, source);
+                }
+            }
+        }
+        clone_into::<2>();
+        clone_into::<6>();
+
+        // a destination with the same shape keeps its nodes
+        let source = BTreeList::<usize, 3>::from_exact_iter(0..500, 500);
+        let mut dest = source.clone();
+        for i in 0..dest.len() {
+            dest[i] += 1;
+        }
+        let root = dest.root_node.as_ref().unwrap().elements.as_ptr();
+        dest.clone_from(&source);
+        assert_eq!(dest, source);
+        assert_eq!(dest.root_node.as_ref().unwrap().elements.as_ptr(), root);
+    }
 
-        let full_child_len = full_child.len();
+    #[test]
+    fn shrink_to_fit() {
+        fn shrunk<T, const B: usize>(node: &BTreeListNode<T, B>) -> bool {
+            node.elements.capacity() == node.elements.len()
+                && node.children.capacity() == node.children.len()
+                && node.children.iter().all(shrunk)
+        }
+        let mut t = BTreeList::<usize, 3>::new();
+        for i in 0..500 {
+            t.insert(t.len() / 2, i).unwrap();
+        }
+        let expected = t.iter().copied().collect::<Vec<_>>();
+        t.shrink_to_fit();
+        assert_shape(&t);
+        assert!(shrunk(t.root_node.as_ref().unwrap()));
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), expected);
+        t.insert(100, 0).unwrap();
+        assert_shape(&t);
+        assert_eq!(t.len(), 501);
+    }
 
-        self.children
-            .insert(full_child_index + 1, successor_sibling);
+    #[test]
+    fn from_exact_iter() {
+        fn build_all<const B: usize>() {
+            for len in 0..300 {
+                let mut t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+                assert_shape(&t);
+                assert_eq!(
+                    t.iter().copied().collect::<Vec<_>>(),
+                    (0..len).collect::<Vec<_>>()
+                );
+                assert_eq!(
+                    t.clone().into_drain().collect::<Vec<_>>(),
+                    (0..len).collect::<Vec<_>>()
+                );
+
+                // the built tree should stay valid under further edits
+                t.insert(len / 2, len).unwrap();
+                assert_shape(&t);
+                while t.pop_front().is_some() {}
+            }
+        }
+        build_all::<2>();
+        build_all::<3>();
+        build_all::<6>();
+        build_all::<32>();
+    }
 
-        self.elements.insert(full_child_index, middle);
+    #[test]
+    fn ends() {
+        let mut t: BTreeList<usize> = btreelist![];
+        assert_eq!(t.replace_front(1), Err(1));
+        assert_eq!(t.replace_back(1), Err(1));
+        t.swap_front_back();
 
-        assert_eq!(full_child_len + z_len + 1, original_len);
+        t.push(1);
+        t.swap_front_back();
+        assert_eq!(t, btreelist![1]);
+
+        t.extend(2..100);
+        t.swap_front_back();
+        assert_eq!(t.first(), Some(&99));
+        assert_eq!(t.last(), Some(&1));
+        assert_eq!(t.replace_front(0), Ok(99));
+        assert_eq!(t.replace_back(100), Ok(1));
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), {
+            let mut v = (0..101).collect::<Vec<_>>();
+            v.remove(1);
+            v.remove(98);
+            v
+        });
+    }
 
-        assert_eq!(original_len_self, self.len());
+    #[test]
+    fn copy_list() {
+        let t = (0..1000).collect::<BTreeList<_>>();
+        let c = t.copy_list();
+        assert_shape(&c);
+        assert_eq!(t, c);
+        assert_eq!(t.root_node, c.root_node);
     }
 
-    fn remove_from_leaf(&mut self, index: usize) -> Option<T> {
-        self.length -= 1;
-        if index < self.elements.len() {
-            Some(self.elements.remove(index))
-        } else {
-            None
+    #[test]
+    fn sorted() {
+        let mut t = BTreeList::<usize, 2>::new();
+        let mut v = Vec::new();
+        for i in 0..500 {
+            let x = (i * 7919) % 101;
+            let index = t.insert_sorted(x);
+            assert_eq!(t.get(index), Some(&x));
+            v.insert(v.partition_point(|&e| e <= x), x);
+        }
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+
+        for x in 0..102 {
+            let start = v.partition_point(|&e| e < x);
+            let end = v.partition_point(|&e| e <= x);
+            assert_eq!(t.equal_range(&x), start..end);
+            assert_eq!(t.partition_point(|&e| e < x), start);
+            if start == end {
+                assert_eq!(t.binary_search(&x), Err(start));
+            } else {
+                assert_eq!(t.binary_search(&x), Ok(start));
+            }
         }
     }
 
-    fn remove_element_from_non_leaf(&mut self, index: usize, element_index: usize) -> Option<T> {
-        self.length -= 1;
-        if self.children[element_index].elements.len() >= B {
-            let total_index = self.cumulative_index(element_index);
-            // recursively delete index - 1 in predecessor_node
-            let predecessor = self.children[element_index].remove(index - 1 - total_index)?;
-            // replace element with that one
-            Some(mem::replace(&mut self.elements[element_index], predecessor))
-        } else if self.children[element_index + 1].elements.len() >= B {
-            // recursively delete index + 1 in successor_node
-            let total_index = self.cumulative_index(element_index + 1);
-            let successor = self.children[element_index + 1].remove(index + 1 - total_index)?;
-            // replace element with that one
-            Some(mem::replace(&mut self.elements[element_index], successor))
-        } else {
-            let middle_element = self.elements.remove(element_index);
-            let successor_child = self.children.remove(element_index + 1);
-            self.children[element_index].merge(middle_element, successor_child);
-
-            let total_index = self.cumulative_index(element_index);
-            self.children[element_index].remove(index - total_index)
+    #[test]
+    fn sort_unstable() {
+        for len in [0, 1, 2, 10, 100, 1000, 10000] {
+            let mut t = (0..len).map(|i| (i * 7919) % 211).collect::<BTreeList<_>>();
+            let mut v = t.iter().copied().collect::<Vec<_>>();
+            t.sort_unstable();
+            v.sort_unstable();
+            assert_shape(&t);
+            assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+        }
+        // equal, sorted and reversed elements shouldn't degrade
+        for v in [
+            vec![1; 5000],
+            (0..5000).collect(),
+            (0..5000).rev().collect::<Vec<_>>(),
+        ] {
+            let mut t = BTreeList::<_, 2>::from_exact_iter(v.iter().copied(), v.len());
+            t.sort_unstable_by(|a, b| b.cmp(a));
+            assert_shape(&t);
+            let sorted = t.iter().copied().collect::<Vec<_>>();
+            assert!(sorted.windows(2).all(|w| w[0] >= w[1]));
         }
     }
 
-    fn cumulative_index(&self, child_index: usize) -> usize {
-        self.children[0..child_index]
-            .iter()
-            .map(|c| c.len() + 1)
-            .sum()
+    #[test]
+    fn is_sorted() {
+        let mut t = (0..500).collect::<BTreeList<_>>();
+        assert!(t.is_sorted());
+        for i in 0..499 {
+            t.swap(i, i + 1);
+            assert!(!t.is_sorted());
+            t.swap(i, i + 1);
+        }
+        assert!(BTreeList::<usize>::new().is_sorted());
     }
 
-    fn remove_from_internal_child(&mut self, index: usize, mut child_index: usize) -> Option<T> {
-        if self.children[child_index].elements.len() < B
-            && if child_index > 0 {
-                self.children[child_index - 1].elements.len() < B
+    #[test]
+    fn shift_elements() {
+        let len = 200;
+        let mut t = BTreeList::<_, 2>::from_exact_iter(0..len, len);
+        let mut v = (0..len).collect::<Vec<_>>();
+        for (start, end, offset) in [
+            (0, 10, 5),
+            (5, 150, 50),
+            (100, 200, -100),
+            (1, 2, -1),
+            (7, 7, 3),
+            (0, 200, 0),
+        ] {
+            assert!(t.shift_elements(start..end, offset));
+            if offset >= 0 {
+                v[start..end + offset as usize].rotate_right(offset as usize);
             } else {
-                true
+                v[start - offset.unsigned_abs()..end].rotate_left(offset.unsigned_abs());
             }
-            && if child_index + 1 < self.children.len() {
-                self.children[child_index + 1].elements.len() < B
+            assert_shape(&t);
+            assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+        }
+        assert!(!t.shift_elements(0..10, -1));
+        assert!(!t.shift_elements(190..200, 1));
+        assert!(!t.shift_elements(190..201, 0));
+
+        let mut rng = TestRng::new(4);
+        for _ in 0..200 {
+            let start = rng.next() % len;
+            let end = start + rng.next() % (len - start + 1);
+            let offset = (rng.next() % (len + 1 - end + start)) as isize - start as isize;
+            assert!(t.shift_elements(start..end, offset));
+            if offset >= 0 {
+                v[start..end + offset as usize].rotate_right(offset as usize);
             } else {
-                true
+                v[start - offset.unsigned_abs()..end].rotate_left(offset.unsigned_abs());
             }
-        {
-            // if the child and its immediate siblings have B-1 elements merge the child
-            // with one sibling, moving an element from this node into the new merged node
-            // to be the median
+            assert_shape(&t);
+        }
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+    }
 
-            if child_index > 0 {
-                let middle = self.elements.remove(child_index - 1);
+    #[test]
+    fn stats() {
+        fn count<T, const B: usize>(node: &BTreeListNode<T, B>) -> (usize, usize) {
+            node.children
+                .iter()
+                .map(count)
+                .fold((1, node.is_leaf() as usize), |(n, l), (cn, cl)| {
+                    (n + cn, l + cl)
+                })
+        }
 
-                // use the predessor sibling
-                let successor = self.children.remove(child_index);
-                child_index -= 1;
+        let mut t = BTreeList::<u64, 3>::new();
+        assert_eq!(t.stats().depth, 0);
+        assert_eq!(t.stats().mean_fill, 0.0);
+        for i in 0..500 {
+            t.insert(i as usize / 2, i).unwrap();
+            let stats = t.stats();
+            let root = t.root_node.as_ref().unwrap();
+            assert_eq!(stats.depth, root.height() + 1);
+            assert_eq!((stats.node_count, stats.leaf_count), count(root));
+            assert!(stats.min_fill <= stats.low_fill && stats.low_fill <= stats.median_fill);
+            assert!(stats.median_fill <= stats.max_fill && stats.max_fill <= 1.0);
+            assert!(stats.min_fill <= stats.mean_fill && stats.mean_fill <= stats.max_fill);
+            assert_eq!(stats.element_bytes, t.len() * 8);
+            assert_eq!(
+                stats.element_bytes + stats.structure_bytes,
+                t.memory_usage()
+            );
+        }
+    }
 
-                self.children[child_index].merge(middle, successor);
-            } else {
-                let middle = self.elements.remove(child_index);
+    #[cfg(feature = "debug-tree")]
+    #[test]
+    fn dump_tree() {
+        let t = BTreeList::<usize, 3>::from_exact_iter(0..1000, 1000);
+        let stats = t.stats();
+        let dump = t.dump_tree();
+        assert_eq!(dump.lines().count(), stats.node_count);
+        assert_eq!(dump.matches("leaf:").count(), stats.leaf_count);
+        let dot = t.to_dot();
+        assert_eq!(dot.matches(" -> ").count(), stats.node_count - 1);
+        assert!(BTreeList::<usize>::new().dump_tree().is_empty());
+    }
 
-                // use the sucessor sibling
-                let successor = self.children.remove(child_index + 1);
+    #[cfg(feature = "validate")]
+    #[test]
+    fn validate() {
+        let mut t = BTreeList::<usize, 3>::from_exact_iter(0..1000, 1000);
+        assert_eq!(t.validate(), Ok(()));
+        let root = t.root_node.as_mut().unwrap();
+        root.length = to_length(999);
+        assert_eq!(
+            t.validate(),
+            Err(ValidationError::Length {
+                node: 0,
+                stored: 999,
+                actual: 1000
+            })
+        );
+        let root = t.root_node.as_mut().unwrap();
+        root.length = to_length(1000);
+        root.children[0].children[0].elements.truncate(1);
+        assert_eq!(
+            t.validate(),
+            Err(ValidationError::Underfull {
+                node: 2,
+                elements: 1
+            })
+        );
+        let mut t = BTreeList::<usize, 3>::from_exact_iter(0..1000, 1000);
+        let root = t.root_node.as_mut().unwrap();
+        let child = root.children.pop().unwrap();
+        root.children
+            .push(child.children.into_iter().next().unwrap());
+        assert!(matches!(
+            t.validate(),
+            Err(ValidationError::UnevenDepth { node: 0 })
+        ));
+    }
 
-                self.children[child_index].merge(middle, successor);
+    #[test]
+    fn compact() {
+        let mut t = BTreeList::<usize, 3>::new();
+        let mut v = Vec::new();
+        let mut rng = TestRng::new(29);
+        for i in 0..5000 {
+            let r = rng.next();
+            if v.is_empty() || r % 5 < 3 {
+                let index = (r >> 4) % (v.len() + 1);
+                t.insert(index, i).unwrap();
+                v.insert(index, i);
+            } else {
+                let index = (r >> 4) % v.len();
+                assert_eq!(t.remove(index), Some(v.remove(index)));
             }
-        } else if self.children[child_index].elements.len() < B {
-            if child_index > 0
-                && self
-                    .children
-                    .get(child_index - 1)
-                    .map_or(false, |c| c.elements.len() >= B)
-            {
-                let last_element = self.children[child_index - 1].elements.pop().unwrap();
-                assert!(!self.children[child_index - 1].elements.is_empty());
-                self.children[child_index - 1].length -= 1;
+        }
+        let before = t.stats();
+        t.compact();
+        assert_shape(&t);
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+        let after = t.stats();
+        assert!(after.depth <= before.depth);
+        assert!(after.node_count <= before.node_count);
+        assert!(after.mean_fill >= before.mean_fill);
+
+        let mut empty = BTreeList::<usize>::new();
+        empty.compact();
+        assert!(empty.is_empty());
+    }
 
-                let parent_element =
-                    mem::replace(&mut self.elements[child_index - 1], last_element);
+    #[test]
+    fn copy_within() {
+        let len = 200;
+        let mut t = BTreeList::<_, 2>::from_exact_iter(0..len, len);
+        let mut v = (0..len).collect::<Vec<_>>();
+        for (start, end, dest) in [
+            (0, 10, 5),
+            (5, 150, 50),
+            (100, 200, 0),
+            (1, 2, 0),
+            (7, 7, 3),
+            (0, 200, 0),
+            (13, 77, 130),
+        ] {
+            assert!(t.copy_within(start..end, dest));
+            v.copy_within(start..end, dest);
+            assert_shape(&t);
+            assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+        }
+        assert!(!t.copy_within(0..10, 191));
+        assert!(!t.copy_within(190..201, 0));
+        assert!(t.copy_within(0..0, 200));
+    }
 
-                self.children[child_index]
-                    .elements
-                    .insert(0, parent_element);
-                self.children[child_index].length += 1;
+    #[test]
+    fn windows_fold() {
+        let t = (0..100).collect::<BTreeList<usize>>();
+        let v = (0..100).collect::<Vec<_>>();
+        assert_eq!(t.elements().copied().collect::<Vec<_>>(), v);
+        for size in [1, 2, 7, 99, 100] {
+            let mut sums = Vec::new();
+            let state = t.windows_fold(size, 0, |s, x| *s += x, |s, x| *s -= x, |s| sums.push(*s));
+            let expected = v
+                .windows(size)
+                .map(|w| w.iter().sum())
+                .collect::<Vec<usize>>();
+            assert_eq!(sums, expected);
+            assert_eq!(state, *expected.last().unwrap());
+        }
+        let mut called = false;
+        t.windows_fold(0, (), |_, _| called = true, |_, _| {}, |_| {});
+        t.windows_fold(101, (), |_, _| called = true, |_, _| {}, |_| {});
+        assert!(!called);
+    }
 
-                if let Some(last_child) = self.children[child_index - 1].children.pop() {
-                    self.children[child_index - 1].length -= last_child.len();
-                    self.children[child_index].length += last_child.len();
-                    self.children[child_index].children.insert(0, last_child);
-                }
-            } else if self
-                .children
-                .get(child_index + 1)
-                .map_or(false, |c| c.elements.len() >= B)
-            {
-                let first_element = self.children[child_index + 1].elements.remove(0);
-                self.children[child_index + 1].length -= 1;
+    #[test]
+    fn boxed_slice() {
+        let t = (0..100).collect::<BTreeList<_>>();
+        let b: Box<[usize]> = t.into();
+        assert_eq!(b, (0..100).collect::<Vec<_>>().into_boxed_slice());
+        assert!(BTreeList::<usize>::new().into_boxed_slice().is_empty());
+    }
 
-                assert!(!self.children[child_index + 1].elements.is_empty());
+    #[test]
+    fn search() {
+        let t = (0..300).collect::<BTreeList<usize>>();
+        for x in 0..300 {
+            assert!(t.contains(&x));
+            assert_eq!(t.index_of(&x), Some(x));
+            assert_eq!(t.position(|&e| e >= x), Some(x));
+        }
+        assert!(!t.contains(&300));
+        assert_eq!(t.index_of(&300), None);
+        assert_eq!(BTreeList::<usize>::new().position(|_| true), None);
+    }
 
-                let parent_element = mem::replace(&mut self.elements[child_index], first_element);
+    #[test]
+    fn positional() {
+        let edits = (0..200).map(|i| (i % 7 % (i + 1), i)).collect::<Vec<_>>();
+        let mut v = Vec::new();
+        for &(index, element) in &edits {
+            v.insert(index, element);
+        }
+        let t = BTreeList::<_, 2>::from_positional(edits).unwrap();
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
 
-                self.children[child_index].length += 1;
-                self.children[child_index].elements.push(parent_element);
+        assert_eq!(
+            BTreeList::<_, 2>::from_positional([(0, 0), (2, 1), (1, 2)]).unwrap_err(),
+            (2, 1)
+        );
+    }
 
-                if !self.children[child_index + 1].is_leaf() {
-                    let first_child = self.children[child_index + 1].children.remove(0);
-                    self.children[child_index + 1].length -= first_child.len();
-                    self.children[child_index].length += first_child.len();
+    #[test]
+    fn starts_ends_with() {
+        let t = (0..200).collect::<BTreeList<usize>>();
+        let v = (0..200).collect::<Vec<_>>();
+        for i in 0..=200 {
+            assert!(t.starts_with(&v[..i]));
+            assert!(t.ends_with(&v[i..]));
+            let prefix = BTreeList::<_, 2>::from_exact_iter(0..i, i);
+            let suffix = BTreeList::<_, 2>::from_exact_iter(i..200, 200 - i);
+            assert!(t.starts_with_list(&prefix));
+            assert!(t.ends_with_list(&suffix));
+            assert!(prefix.is_prefix_of(&t));
+            assert!(suffix.is_suffix_of(&t));
+            // the empty list is both
+            assert_eq!(suffix.is_prefix_of(&t), i == 0 || i == 200);
+            assert_eq!(prefix.is_suffix_of(&t), i == 0 || i == 200);
+        }
+        assert!(!t.starts_with(&[1]));
+        assert!(!t.ends_with(&[198]));
+        assert!(!t.starts_with(&(0..201).collect::<Vec<_>>()));
+        let mut u = t.clone();
+        u.set(100, 0).unwrap();
+        assert!(!u.starts_with(&v[..150]));
+        assert!(!u.ends_with_list(&BTreeList::<_, 3>::from_exact_iter(50..200, 150)));
+    }
 
-                    self.children[child_index].children.push(first_child);
+    #[test]
+    fn split_off_append() {
+        fn split_append<const B: usize>() {
+            for len in [0, 1, 5, 2 * B, 50, 300] {
+                for at in 0..=len {
+                    let mut t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+                    let mut tail = t.split_off(at).unwrap();
+                    assert_shape(&t);
+                    assert_shape(&tail);
+                    assert_eq!(
+                        t.iter().copied().collect::<Vec<_>>(),
+                        (0..at).collect::<Vec<_>>()
+                    );
+                    assert_eq!(
+                        tail.iter().copied().collect::<Vec<_>>(),
+                        (at..len).collect::<Vec<_>>()
+                    );
+                    t.append(&mut tail);
+                    assert_shape(&t);
+                    assert!(tail.is_empty());
+                    assert_eq!(
+                        t.iter().copied().collect::<Vec<_>>(),
+                        (0..len).collect::<Vec<_>>()
+                    );
+                }
+            }
+
+            // lists of very different heights
+            for (l, r) in [(1, 1000), (1000, 1), (3, 200), (200, 3), (2 * B, 2 * B)] {
+                let mut t = BTreeList::<usize, B>::new();
+                for i in 0..l {
+                    t.push(i);
+                }
+                let mut u = BTreeList::<usize, B>::new();
+                for i in l..l + r {
+                    u.push(i);
+                }
+                t.append(&mut u);
+                assert_shape(&t);
+                assert_eq!(
+                    t.iter().copied().collect::<Vec<_>>(),
+                    (0..l + r).collect::<Vec<_>>()
+                );
+                // keep working after the join
+                for i in 0..l + r {
+                    assert_eq!(t.remove(0), Some(i));
                 }
             }
         }
-        self.length -= 1;
-        let total_index = self.cumulative_index(child_index);
-        self.children[child_index].remove(index - total_index)
+        split_append::<2>();
+        split_append::<3>();
+        split_append::<6>();
+
+        let mut t: BTreeList<usize> = btreelist![1];
+        assert_eq!(t.split_off(2), None);
+        assert_eq!(t.remove_range(0..2), None);
+        assert_eq!(t, btreelist![1]);
     }
 
-    fn check(&self) -> usize {
-        let l = self.elements.len() + self.children.iter().map(|c| c.check()).sum::<usize>();
-        assert_eq!(self.len(), l);
-
-        l
+    #[test]
+    fn cursor_remove_range_forward() {
+        let mut t = (0..500).collect::<BTreeList<usize>>();
+        let mut v = (0..500).collect::<Vec<_>>();
+        for (index, n) in [(0, 10), (100, 200), (250, 1000), (3, 0)] {
+            let mut cursor = t.cursor_mut_at(index).unwrap();
+            let removed = cursor.remove_range_forward(n);
+            assert_eq!(cursor.index(), index);
+            drop(cursor);
+            let end = min(index + n, v.len());
+            let expected = v.drain(index..end).collect::<Vec<_>>();
+            assert_eq!(removed.iter().copied().collect::<Vec<_>>(), expected);
+            assert_shape(&t);
+            assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+        }
     }
 
-    pub(crate) fn remove(&mut self, index: usize) -> Option<T> {
-        let original_len = self.len();
-        if self.is_leaf() {
-            let v = self.remove_from_leaf(index);
-            assert_eq!(original_len, self.len() + 1);
-            debug_assert_eq!(self.check(), self.len());
-            v
-        } else {
-            let mut total_index = 0;
-            for (child_index, child) in self.children.iter().enumerate() {
-                match (total_index + child.len()).cmp(&index) {
-                    Ordering::Less => {
-                        // should be later on in the loop
-                        total_index += child.len() + 1;
-                        continue;
-                    }
-                    Ordering::Equal => {
-                        let v = self.remove_element_from_non_leaf(
-                            index,
-                            min(child_index, self.elements.len() - 1),
-                        );
-                        assert_eq!(original_len, self.len() + 1);
-                        debug_assert_eq!(self.check(), self.len());
-                        return v;
-                    }
-                    Ordering::Greater => {
-                        let v = self.remove_from_internal_child(index, child_index);
-                        assert_eq!(original_len, self.len() + 1);
-                        debug_assert_eq!(self.check(), self.len());
-                        return v;
-                    }
-                }
+    #[test]
+    fn select_nth_unstable() {
+        for len in [1, 2, 3, 10, 100, 1000] {
+            let mut sorted = (0..len).map(|i| (i * 7919) % 97).collect::<Vec<_>>();
+            sorted.sort_unstable();
+            for index in [0, len / 3, len / 2, len - 1] {
+                let mut t = (0..len).map(|i| (i * 7919) % 97).collect::<BTreeList<_>>();
+                assert_eq!(t.select_nth_unstable(index), Some(&mut sorted[index]));
+                assert!(t.iter().take(index).all(|x| *x <= sorted[index]));
+                assert!(t.iter().skip(index).all(|x| *x >= sorted[index]));
             }
-            None
         }
+        // all equal elements shouldn't degrade
+        let mut t = (0..2000).map(|_| 1).collect::<BTreeList<_>>();
+        assert_eq!(t.select_nth_unstable(1000), Some(&mut 1));
+        assert_eq!(t.select_nth_unstable(2000), None);
     }
 
-    fn merge(&mut self, middle: T, successor_sibling: BTreeListNode<T, B>) {
-        self.elements.push(middle);
-        self.elements.extend(successor_sibling.elements);
-        self.children.extend(successor_sibling.children);
-        self.length += successor_sibling.length + 1;
-        assert!(self.is_full());
-    }
-
-    pub(crate) fn set(&mut self, index: usize, element: T) -> Result<T, T> {
-        if self.is_leaf() {
-            let old_element = self.elements.get_mut(index).unwrap();
-            Ok(mem::replace(old_element, element))
-        } else {
-            let mut cumulative_len = 0;
-            for (child_index, child) in self.children.iter_mut().enumerate() {
-                match (cumulative_len + child.len()).cmp(&index) {
-                    Ordering::Less => {
-                        cumulative_len += child.len() + 1;
-                    }
-                    Ordering::Equal => {
-                        let old_element = self.elements.get_mut(child_index).unwrap();
-                        return Ok(mem::replace(old_element, element));
-                    }
-                    Ordering::Greater => {
-                        return child.set(index - cumulative_len, element);
-                    }
+    #[test]
+    fn cursor() {
+        fn walk<const B: usize>() {
+            let len = 300;
+            let t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+            for start in [0, 1, len / 2, len - 1, len] {
+                let mut cursor = t.cursor_at(start).unwrap();
+                for i in start..len {
+                    assert_eq!(cursor.index(), i);
+                    assert_eq!(cursor.current(), Some(&i));
+                    assert_eq!(cursor.peek_next(), t.get(i + 1));
+                    assert_eq!(cursor.peek_prev(), i.checked_sub(1).and_then(|i| t.get(i)));
+                    assert!(cursor.move_next());
+                }
+                assert_eq!(cursor.current(), None);
+                assert_eq!(cursor.peek_prev(), Some(&(len - 1)));
+                assert!(!cursor.move_next());
+                for i in (0..len).rev() {
+                    assert!(cursor.move_prev());
+                    assert_eq!(cursor.index(), i);
+                    assert_eq!(cursor.current(), Some(&i));
                 }
+                assert!(!cursor.move_prev());
+                assert_eq!(cursor.current(), Some(&0));
             }
-            // can't set so return the original element
-            Err(element)
         }
+        walk::<2>();
+        walk::<3>();
+        walk::<6>();
     }
 
-    pub(crate) fn get(&self, index: usize) -> Option<&T> {
-        if self.is_leaf() {
-            return self.elements.get(index);
-        } else {
-            let mut cumulative_len = 0;
-            for (child_index, child) in self.children.iter().enumerate() {
-                match (cumulative_len + child.len()).cmp(&index) {
-                    Ordering::Less => {
-                        cumulative_len += child.len() + 1;
+    #[test]
+    fn cursor_mut_edits() {
+        fn edit<const B: usize>() {
+            let mut t = BTreeList::<usize, B>::from_exact_iter(0..100, 100);
+            let mut v = (0..100).collect::<Vec<_>>();
+            let mut cursor = t.cursor_mut_at(50).unwrap();
+            let mut index = 50;
+            let mut rng = TestRng::new(1);
+            for i in 100..5000 {
+                match rng.next() % 7 {
+                    0 | 1 => {
+                        cursor.insert_before(i);
+                        v.insert(index, i);
+                        index += 1;
                     }
-                    Ordering::Equal => {
-                        return self.elements.get(child_index);
+                    2 => {
+                        cursor.insert_after(i);
+                        if index == v.len() {
+                            v.push(i);
+                            index += 1;
+                        } else {
+                            v.insert(index + 1, i);
+                        }
                     }
-                    Ordering::Greater => {
-                        return child.get(index - cumulative_len);
+                    3 | 4 => {
+                        let removed = cursor.remove_current();
+                        assert_eq!(removed, (index < v.len()).then(|| v.remove(index)));
+                    }
+                    5 => {
+                        assert_eq!(cursor.move_next(), index < v.len());
+                        index = min(index + 1, v.len());
+                    }
+                    _ => {
+                        assert_eq!(cursor.move_prev(), index > 0);
+                        index = index.saturating_sub(1);
                     }
                 }
+                assert_eq!(cursor.index(), index);
+                assert_eq!(cursor.current(), v.get(index));
             }
+            drop(cursor);
+            assert_shape(&t);
+            assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
         }
-        None
+        edit::<2>();
+        edit::<3>();
+        edit::<6>();
     }
 
-    pub(crate) fn get_mut(&mut self, index: usize) -> Option<&mut T> {
-        if self.is_leaf() {
-            return self.elements.get_mut(index);
-        } else {
-            let mut cumulative_len = 0;
-            for (child_index, child) in self.children.iter_mut().enumerate() {
-                match (cumulative_len + child.len()).cmp(&index) {
-                    Ordering::Less => {
-                        cumulative_len += child.len() + 1;
-                    }
-                    Ordering::Equal => {
-                        return self.elements.get_mut(child_index);
-                    }
-                    Ordering::Greater => {
-                        return child.get_mut(index - cumulative_len);
-                    }
+    #[test]
+    fn cursor_seek() {
+        fn seek<const B: usize>() {
+            let len = 300;
+            let mut t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+            let mut rng = TestRng::new(1);
+            let targets = (0..2000)
+                .map(|_| rng.next() % (len + 1))
+                .collect::<Vec<_>>();
+            let mut cursor = t.cursor_at(len / 2).unwrap();
+            for &index in &targets {
+                assert!(cursor.seek(index));
+                assert_eq!(cursor.current(), t.get(index));
+                assert_eq!(cursor.peek_next(), t.get(index + 1));
+            }
+            assert!(!cursor.seek(len + 1));
+            let mut cursor = t.cursor_mut_at(len / 2).unwrap();
+            for &index in &targets {
+                assert!(cursor.seek(index));
+                assert_eq!(cursor.index(), index);
+                if let Some(element) = cursor.current_mut() {
+                    assert_eq!(*element % len, index);
+                    *element += len;
                 }
             }
+            drop(cursor);
+            assert_shape(&t);
+            assert!(t.iter().enumerate().all(|(i, e)| e % len == i));
         }
-        None
+        seek::<2>();
+        seek::<3>();
+        seek::<6>();
     }
-}
 
-impl<T> Default for BTreeList<T> {
-    fn default() -> Self {
-        Self::new()
+    #[test]
+    fn push_back_shape() {
+        fn push<const B: usize>() {
+            let mut t = BTreeList::<usize, B>::new();
+            for i in 0..1000 {
+                t.push_back(i);
+                assert_eq!(t.len(), i + 1);
+                assert_eq!(t.last(), Some(&i));
+            }
+            assert_shape(&t);
+            assert!(t.iter().copied().eq(0..1000));
+            t.insert(500, 0).unwrap();
+            t.push_back(1000);
+            assert_shape(&t);
+        }
+        push::<2>();
+        push::<3>();
+        push::<6>();
     }
-}
 
-impl<T> PartialEq for BTreeList<T>
-where
-    T: PartialEq,
-{
-    fn eq(&self, other: &Self) -> bool {
-        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    #[test]
+    fn push_front_shape() {
+        fn push<const B: usize>() {
+            let mut t = BTreeList::<usize, B>::new();
+            for i in 0..1000 {
+                t.push_front(i);
+                assert_eq!(t.len(), i + 1);
+                assert_eq!(t.first(), Some(&i));
+            }
+            assert_shape(&t);
+            assert!(t.iter().copied().eq((0..1000).rev()));
+            for i in 0..500 {
+                t.push_back(i);
+                t.push_front(i);
+            }
+            assert_shape(&t);
+            assert_eq!(t.len(), 2000);
+        }
+        push::<2>();
+        push::<3>();
+        push::<6>();
     }
-}
-
-impl<'a, T, const B: usize> IntoIterator for &'a BTreeList<T, B> {
-    type Item = &'a T;
-
-    type IntoIter = Iter<'a, T, B>;
 
-    fn into_iter(self) -> Self::IntoIter {
-        Iter {
-            inner: self,
-            index: 0,
-            index_back: self.len(),
+    #[test]
+    fn apply_sorted_edits() {
+        fn edit<const B: usize>() {
+            let len = 200;
+            let mut t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+            let mut v = (0..len).collect::<Vec<_>>();
+            let edits = (0..len)
+                .step_by(17)
+                .map(|start| {
+                    (
+                        start..min(start + start % 5, len),
+                        vec![1000 + start; start % 3],
+                    )
+                })
+                .collect::<Vec<_>>();
+            for (range, replacement) in edits.iter().rev() {
+                v.splice(range.clone(), replacement.iter().copied());
+            }
+            assert_eq!(t.apply_sorted_edits(edits), Ok(()));
+            assert_shape(&t);
+            assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
+
+            // overlapping and touching edits replace the union of their ranges
+            let mut t = BTreeList::<usize, B>::from_exact_iter(0..10, 10);
+            let edits = vec![
+                (2..5, vec![20]),
+                (4..7, vec![40]),
+                (7..8, vec![70]),
+                (9..9, vec![90]),
+            ];
+            assert_eq!(t.apply_sorted_edits(edits), Ok(()));
+            assert!(t.iter().copied().eq([0, 1, 20, 40, 70, 8, 90, 9]));
+
+            assert_eq!(t.apply_sorted_edits(vec![(0..9, vec![])]), Err(0..9));
+            assert_eq!(t.len(), 8);
         }
+        edit::<2>();
+        edit::<6>();
     }
-}
-
-impl<T, const B: usize> IntoIterator for BTreeList<T, B> {
-    type Item = T;
 
-    type IntoIter = OwnedIter<T, B>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        OwnedIter { inner: self }
+    #[test]
+    fn pop_ends() {
+        fn pop<const B: usize>() {
+            let len = 1000;
+            let mut t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+            let (mut front, mut back) = (0, len);
+            while !t.is_empty() {
+                if (front + back) % 3 == 1 {
+                    assert_eq!(t.pop_front(), Some(front));
+                    front += 1;
+                } else {
+                    back -= 1;
+                    assert_eq!(t.pop_back(), Some(back));
+                }
+                assert_eq!(t.len(), back - front);
+                if t.len() % 97 == 1 {
+                    assert_shape(&t);
+                }
+            }
+            assert_eq!(t.pop_back(), None);
+            assert_eq!(t.pop_front(), None);
+        }
+        pop::<2>();
+        pop::<3>();
+        pop::<6>();
     }
-}
 
-impl<T> Extend<T> for BTreeList<T> {
-    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        for item in iter {
-            self.push_back(item)
+    #[test]
+    fn into_leaf_chunks() {
+        fn chunks<const B: usize>() {
+            for len in [0, 1, 2 * B, 1000] {
+                let t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+                let chunks = t.into_leaf_chunks().collect::<Vec<_>>();
+                assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+                assert_eq!(chunks.concat(), (0..len).collect::<Vec<_>>());
+            }
         }
+        chunks::<2>();
+        chunks::<3>();
+        chunks::<6>();
     }
-}
 
-impl<T> FromIterator<T> for BTreeList<T> {
-    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut l = BTreeList::new();
-        for item in iter {
-            l.push_back(item);
+    #[test]
+    fn node_chunks() {
+        fn chunks<const B: usize>() {
+            for len in [0, 1, 2 * B, 1000] {
+                let t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+                let chunks = t.node_chunks().collect::<Vec<_>>();
+                assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+                assert_eq!(chunks.concat(), (0..len).collect::<Vec<_>>());
+                let mut visited = Vec::new();
+                let _ = t.try_for_each_chunk(|chunk| {
+                    visited.push(chunk.to_vec());
+                    Ok::<_, ()>(())
+                });
+                assert_eq!(chunks, visited);
+            }
         }
-        l
+        chunks::<2>();
+        chunks::<3>();
+        chunks::<6>();
     }
-}
 
-impl<T> Index<usize> for BTreeList<T> {
-    type Output = T;
+    #[test]
+    fn hash() {
+        use std::collections::hash_map::DefaultHasher;
 
-    fn index(&self, index: usize) -> &Self::Output {
-        self.get(index).unwrap()
-    }
-}
+        fn hash_of<T: Hash>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
 
-impl<T> IndexMut<usize> for BTreeList<T> {
-    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-        self.get_mut(index).unwrap()
+        let built = BTreeList::<usize, 2>::from_exact_iter(0..500, 500);
+        let mut pushed = BTreeList::<usize, 2>::new();
+        for i in (0..500).rev() {
+            pushed.insert(0, i).unwrap();
+        }
+        assert_eq!(hash_of(&built), hash_of(&pushed));
+        pushed.pop_back();
+        assert_ne!(hash_of(&built), hash_of(&pushed));
+
+        // the length keeps nested lists from colliding
+        let split: Vec<BTreeList<usize>> = vec![btreelist![1, 2], btreelist![3]];
+        let moved: Vec<BTreeList<usize>> = vec![btreelist![1], btreelist![2, 3]];
+        assert_ne!(hash_of(&split), hash_of(&moved));
     }
-}
-
-#[cfg(test)]
-mod tests {
-
-    use crate::btreelist;
-
-    use super::*;
 
     #[test]
-    fn push_back() {
-        let mut t = BTreeList::default();
-
-        t.push_back(());
-        t.push_back(());
-        t.push_back(());
-        t.push_back(());
-        t.push_back(());
-        t.push_back(());
-        t.push_back(());
-        t.push_back(());
+    fn ord() {
+        let vecs = [
+            vec![],
+            vec![0],
+            vec![0; 100],
+            (0..100).collect(),
+            (0..99).collect(),
+            (1..50).collect(),
+            (0..100).rev().collect::<Vec<_>>(),
+        ];
+        for a in &vecs {
+            for b in &vecs {
+                let list_a = a.iter().copied().collect::<BTreeList<usize>>();
+                let list_b = b.iter().copied().collect::<BTreeList<usize>>();
+                assert_eq!(list_a.cmp(&list_b), a.cmp(b));
+                assert_eq!(list_a.partial_cmp(&list_b), a.partial_cmp(b));
+            }
+        }
+        let with_nan: BTreeList<f64> = btreelist![1.0, f64::NAN];
+        assert_eq!(with_nan.partial_cmp(&btreelist![1.0, 2.0]), None);
+        assert!(with_nan < btreelist![2.0]);
     }
 
     #[test]
-    fn insert() {
-        let mut t = BTreeList::default();
-
-        t.insert(0, ()).unwrap();
-        t.insert(1, ()).unwrap();
-        t.insert(0, ()).unwrap();
-        t.insert(0, ()).unwrap();
-        t.insert(0, ()).unwrap();
-        t.insert(3, ()).unwrap();
-        t.insert(4, ()).unwrap();
+    fn eq_across_b() {
+        let small = BTreeList::<usize, 2>::from_exact_iter(0..500, 500);
+        let large = BTreeList::<usize, 32>::from_exact_iter(0..500, 500);
+        assert!(small == large);
+        assert!(large == small);
+        let shorter = BTreeList::<usize, 3>::from_exact_iter(0..499, 499);
+        assert!(small != shorter);
+        let changed = BTreeList::<usize, 3>::from_exact_iter((0..500).map(|i| i / 499 + i), 500);
+        assert!(small != changed);
     }
 
     #[test]
-    fn insert_book() {
-        let mut t = BTreeList::default();
-
-        for i in 0..100 {
-            t.insert(i % 2, ()).unwrap();
+    fn swap_remove_ends() {
+        let mut t = (0..200).collect::<BTreeList<usize, 3>>();
+        let mut v = (0..200).collect::<Vec<_>>();
+        let mut front = false;
+        while !v.is_empty() {
+            let index = (v.len() * 7) / 11;
+            if front {
+                assert_eq!(t.swap_remove_front(index), Some(v[index]));
+                v[index] = v[0];
+                v.remove(0);
+            } else {
+                assert_eq!(t.swap_remove(index), Some(v.swap_remove(index)));
+            }
+            front = !front;
+            assert_shape(&t);
+            assert_eq!(t, v);
         }
+        assert_eq!(t.swap_remove(0), None);
+        assert_eq!(t.swap_remove_front(0), None);
     }
 
     #[test]
-    fn insert_book_vec() {
-        let mut t = BTreeList::default();
-        let mut v = Vec::new();
-
-        for i in 0..100 {
-            t.insert(i % 3, ()).unwrap();
-            v.insert(i % 3, ());
+    fn extend_sorted() {
+        let mut t = (0..100).map(|i| i * 2).collect::<BTreeList<usize, 3>>();
+        t.extend_sorted((0..60).map(|i| i * 5));
+        assert_shape(&t);
+        let mut v = (0..100)
+            .map(|i| i * 2)
+            .chain((0..60).map(|i| i * 5))
+            .collect::<Vec<_>>();
+        v.sort();
+        assert_eq!(t, v);
+
+        t.extend_sorted(iter::empty());
+        assert_eq!(t, v);
+        let mut empty = BTreeList::<usize, 3>::new();
+        empty.extend_sorted(v.iter().copied());
+        assert_eq!(empty, v);
+    }
 
-            assert_eq!(v, t.iter().copied().collect::<Vec<_>>())
+    #[test]
+    fn rechunk() {
+        for len in [0, 1, 10, 1000] {
+            let t = BTreeList::<usize, 2>::from_exact_iter(0..len, len);
+            let large = t.clone().rechunk::<16>();
+            assert_shape(&large);
+            assert_eq!(large, t);
+            let small = large.rechunk::<3>();
+            assert_shape(&small);
+            assert_eq!(small, t);
         }
     }
 
     #[test]
-    fn iter_forth_back() {
-        let mut t = BTreeList::default();
-
-        t.push_back(1);
-        t.push_back(2);
-        t.push_back(3);
-        t.push_back(4);
-        t.push_back(5);
-
-        let mut i = t.iter();
-        assert_eq!(i.next(), Some(&1));
-        assert_eq!(i.next(), Some(&2));
-        assert_eq!(i.next(), Some(&3));
-        assert_eq!(i.next(), Some(&4));
-        assert_eq!(i.next(), Some(&5));
-        assert_eq!(i.next(), None);
-
-        let mut i = t.iter();
-        assert_eq!(i.next_back(), Some(&5));
-        assert_eq!(i.next_back(), Some(&4));
-        assert_eq!(i.next_back(), Some(&3));
-        assert_eq!(i.next_back(), Some(&2));
-        assert_eq!(i.next_back(), Some(&1));
-        assert_eq!(i.next_back(), None);
-
-        let mut i = t.iter();
-        assert_eq!(i.next(), Some(&1));
-        assert_eq!(i.next_back(), Some(&5));
-        assert_eq!(i.next_back(), Some(&4));
-        assert_eq!(i.next_back(), Some(&3));
-        assert_eq!(i.next_back(), Some(&2));
-        assert_eq!(i.next_back(), None);
-
-        let mut i = t.iter();
-        assert_eq!(i.next(), Some(&1));
-        assert_eq!(i.next(), Some(&2));
-        assert_eq!(i.next(), Some(&3));
-        assert_eq!(i.next(), Some(&4));
-        assert_eq!(i.next_back(), Some(&5));
-        assert_eq!(i.next_back(), None);
+    fn try_for_each_chunk() {
+        let t = BTreeList::<usize, 2>::from_exact_iter(0..1000, 1000);
+        let mut seen = Vec::new();
+        assert_eq!(
+            t.try_for_each_chunk(|chunk| {
+                seen.extend_from_slice(chunk);
+                Ok::<_, ()>(())
+            }),
+            Ok(())
+        );
+        assert_eq!(t, seen);
+
+        let mut calls = 0;
+        let result = t.try_for_each_chunk(|chunk| {
+            calls += 1;
+            match chunk.iter().find(|&&e| e >= 500) {
+                Some(&e) => Err(e),
+                None => Ok(()),
+            }
+        });
+        assert_eq!(result, Err(500));
+        assert!(calls < 1000);
     }
 
     #[test]
-    fn first_last() {
-        let mut t = BTreeList::default();
-        t.push_back(1);
-        t.push_back(2);
-        t.push_back(3);
-
-        assert_eq!(t.first(), Some(&1));
-        assert_eq!(t.first_mut(), Some(&mut 1));
-        assert_eq!(t.last(), Some(&3));
-        assert_eq!(t.last_mut(), Some(&mut 3));
+    fn nearest_boundary() {
+        for len in [0, 1, 10, 1000] {
+            let t = BTreeList::<usize, 3>::from_exact_iter(0..len, len);
+            let mut chunks = Vec::new();
+            let _ = t.try_for_each_chunk(|chunk| {
+                chunks.push(chunk[0]..chunk[0] + chunk.len());
+                Ok::<_, ()>(())
+            });
+            let mut chunks = chunks.into_iter();
+            let mut chunk = 0..0;
+            for i in 0..len {
+                if !chunk.contains(&i) {
+                    chunk = chunks.next().unwrap();
+                }
+                assert_eq!(t.nearest_boundary(i), Some(chunk.clone()));
+            }
+            assert_eq!(t.nearest_boundary(len), None);
+        }
     }
 
     #[test]
-    fn pop() {
-        let mut t = BTreeList::default();
-
-        t.push_back(1);
-        t.push_back(2);
-        t.push_back(3);
+    fn split() {
+        for len in [0, 1, 5, 100, 1000] {
+            let v = (0..len).map(|i| (i * 7) % 11).collect::<Vec<usize>>();
+            let t = BTreeList::<usize, 2>::from_exact_iter(v.iter().copied(), len);
+            for sep in [0, 3, 10, 11] {
+                let pieces = t
+                    .split(|&e| e == sep)
+                    .map(|piece| piece.copied().collect::<Vec<_>>())
+                    .collect::<Vec<_>>();
+                let expected = v
+                    .split(|&e| e == sep)
+                    .map(|piece| piece.to_vec())
+                    .collect::<Vec<_>>();
+                assert_eq!(pieces, expected);
+            }
+        }
+    }
 
-        assert_eq!(t.pop_back(), Some(3));
-        assert_eq!(t.pop_back(), Some(2));
-        assert_eq!(t.pop_back(), Some(1));
-        assert_eq!(t.pop_back(), None);
+    #[test]
+    fn concat() {
+        let mut lists = BTreeList::<BTreeList<usize, 2>, 3>::new();
+        let mut v = Vec::new();
+        let mut rng = TestRng::new(9);
+        for _ in 0..100 {
+            let len = rng.next() % 50;
+            lists.push_back(BTreeList::from_exact_iter(v.len()..v.len() + len, len));
+            v.extend(v.len()..v.len() + len);
+        }
+        let t = lists.concat();
+        assert_shape(&t);
+        assert_eq!(t, v);
+        assert!(BTreeList::<BTreeList<usize>>::new().concat().is_empty());
     }
 
     #[test]
-    fn set_no_panic() {
-        let mut t = BTreeList::default();
-        assert_eq!(t.set(0, 1), Err(1));
-        t.push(1);
-        assert_eq!(t.set(0, 2), Ok(1));
+    fn join() {
+        for lens in [&[][..], &[0], &[5], &[0, 0], &[3, 0, 40, 1, 0, 100]] {
+            let pieces = lens
+                .iter()
+                .map(|&len| (0..len).collect::<Vec<usize>>())
+                .collect::<Vec<_>>();
+            let lists = pieces
+                .iter()
+                .map(|piece| {
+                    BTreeList::<usize, 2>::from_exact_iter(piece.iter().copied(), piece.len())
+                })
+                .collect::<BTreeList<_>>();
+            let t = lists.clone().join(&1000);
+            assert_shape(&t);
+            assert_eq!(t, pieces.join(&1000));
+            let separator = BTreeList::from_exact_iter(1000..1010, 10);
+            let t = lists.join_list(&separator);
+            assert_shape(&t);
+            assert_eq!(t, pieces.join(&(1000..1010).collect::<Vec<_>>()[..]));
+        }
     }
 
     #[test]
-    fn remove_no_panic() {
-        let mut t = BTreeList::default();
-        assert_eq!(t.remove(0), None);
-        assert_eq!(t.remove(1), None);
-        t.push(1);
-        assert_eq!(t.remove(0), Some(1));
+    fn repeat() {
+        for len in [0, 1, 7, 100] {
+            let v = (0..len).collect::<Vec<usize>>();
+            let t = BTreeList::<usize, 2>::from_exact_iter(0..len, len);
+            for n in [0, 1, 2, 3, 8, 13, 100] {
+                let repeated = t.repeat(n);
+                assert_shape(&repeated);
+                assert_eq!(repeated, v.repeat(n));
+            }
+        }
     }
 
+    #[cfg(feature = "u32-lengths")]
     #[test]
-    fn insert_no_panic() {
-        let mut t = BTreeList::default();
-        assert_eq!(t.insert(10, 1), Err(1));
-        assert_eq!(t.insert(1, 1), Err(1));
-        assert_eq!(t.insert(0, 1), Ok(()));
-        assert_eq!(t.insert(10, 1), Err(1));
+    #[should_panic(expected = "fewer than 2^32 elements")]
+    fn u32_length_overflow() {
+        let mut t = BTreeList::<(), 2>::new();
+        t.push_back(());
+        // stand in for a list of u32::MAX elements
+        t.root_node.as_mut().unwrap().length = u32::MAX;
+        t.push_back(());
     }
 
     #[test]
-    fn generic_b() {
-        fn push_a_few<const B: usize>(mut t: BTreeList<usize, B>) {
-            t.push(1);
-            t.push(2);
-            t.push(3);
+    fn estimate_b_for() {
+        assert_eq!(super::estimate_b_for::<u8>(0).b, 2);
+        assert_eq!(super::estimate_b_for::<[u8; 100]>(256).b, 2);
+        assert_eq!(super::estimate_b_for::<()>(63).b, 32);
+        for target in [64, 100, 256, 4096] {
+            let estimate = super::estimate_b_for::<u32>(target);
+            assert!(estimate.max_element_bytes <= target);
+            let next = 2 * (estimate.b + 1) - 1;
+            assert!(next * 4 > target);
         }
+    }
 
-        let t: BTreeList<usize> = BTreeList::new();
-        let t6: BTreeList<usize, 6> = BTreeList::new();
-        let t3: BTreeList<usize, 3> = BTreeList::new();
-        let t7: BTreeList<usize, 7> = BTreeList::new();
-        let t32: BTreeList<usize, 32> = BTreeList::new();
+    #[test]
+    fn eq_iter() {
+        let t = BTreeList::<usize, 2>::from_exact_iter(0..1000, 1000);
+        assert!(t.eq_iter(0..1000));
+        assert!(t.eq_iter((0..1000).collect::<Vec<_>>().iter()));
+        assert!(t.eq_iter((0..).take_while(|&i| i < 1000)));
+        assert!(!t.eq_iter(0..999));
+        assert!(!t.eq_iter(0..1001));
+        assert!(!t.eq_iter((0..).take_while(|&i| i < 1001)));
+        assert!(!t.eq_iter((0..1000).map(|i| if i == 500 { 0 } else { i })));
+        assert!(BTreeList::<usize>::new().eq_iter(iter::empty::<usize>()));
+    }
 
-        push_a_few(t);
-        push_a_few(t6);
-        push_a_few(t3);
-        push_a_few(t7);
-        push_a_few(t32);
+    #[test]
+    fn custom_b_traits() {
+        let mut t: BTreeList<usize, 3> = (0..100).collect();
+        assert_shape(&t);
+        t.extend(100..200);
+        assert_shape(&t);
+        t[150] = 0;
+        assert_eq!(t[150], 0);
+        assert_eq!(t[199], 199);
+        assert!(BTreeList::<usize, 32>::default().is_empty());
     }
 
     #[test]
-    fn swap() {
-        let mut t = BTreeList::default();
+    fn eq_slices() {
+        let t: BTreeList<usize> = (0..100).collect();
+        let mut v = (0..100).collect::<Vec<_>>();
+        assert_eq!(t, v);
+        assert_eq!(v, t);
+        assert_eq!(t, v[..]);
+        assert_eq!(v[..], t);
+        assert_eq!(t, &v[..]);
+        assert_eq!(&v[..], t);
+        assert_eq!(t, &mut v[..]);
+        assert_eq!(&mut v[..], t);
+        v.pop();
+        assert_ne!(t, v);
+        assert_ne!(v, t);
+        v.push(0);
+        assert_ne!(t, v);
+
+        let strings: BTreeList<String> = btreelist!["a".to_owned()];
+        assert_eq!(strings, vec!["a"]);
+        assert_eq!(vec!["a"], strings);
+    }
 
-        t.push(1);
-        t.push(2);
-        t.push(3);
+    #[test]
+    fn repeat_with() {
+        let mut i = 0;
+        let t = BTreeList::<_, 3>::repeat_with(1000, || {
+            i += 1;
+            i
+        });
+        assert_shape(&t);
+        assert_eq!(
+            t.iter().copied().collect::<Vec<_>>(),
+            (1..=1000).collect::<Vec<_>>()
+        );
+        assert!(BTreeList::<(), 3>::repeat_with(0, || ()).is_empty());
+    }
 
-        t.swap(0, 2);
-        assert_eq!(t, btreelist![3, 2, 1]);
-        t.swap(2, 0);
-        assert_eq!(t, btreelist![1, 2, 3]);
-        t.swap(1, 0);
-        assert_eq!(t, btreelist![2, 1, 3]);
-        t.swap(0, 1);
-        assert_eq!(t, btreelist![1, 2, 3]);
-        assert!(!t.swap(0, 4));
-        assert!(!t.swap(5, 4));
+    #[test]
+    fn merge_sorted() {
+        let mut t: BTreeList<_> = btreelist![];
+        t.merge_sorted(btreelist![1, 2]);
+        assert_eq!(t, btreelist![1, 2]);
+        t.merge_sorted(btreelist![]);
+        assert_eq!(t, btreelist![1, 2]);
+
+        let mut t: BTreeList<_> = (0..100).map(|i| (i * 2, 0)).collect();
+        let u: BTreeList<_> = (0..50).map(|i| (i * 3, 1)).collect();
+        let mut v = t.iter().chain(u.iter()).copied().collect::<Vec<_>>();
+        v.sort_by_key(|(k, _)| *k);
+        t.merge_sorted(u);
+        assert_shape(&t);
+        assert_eq!(t.iter().copied().collect::<Vec<_>>(), v);
     }
 
     #[cfg(release)]
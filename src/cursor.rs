@@ -0,0 +1,369 @@
+use std::ops::ControlFlow;
+
+use crate::{
+    btreelist::{Path, Zipper},
+    BTreeList,
+};
+
+/// A cursor over a [`BTreeList`].
+///
+/// The cursor sits on an element of the list, or just past the last element when its index is
+/// equal to the length of the list. It keeps its path down the tree so moving to neighbouring
+/// elements takes `O(1)` amortized time.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a, T, const B: usize> {
+    list: &'a BTreeList<T, B>,
+    index: usize,
+    path: Path<'a, T, B>,
+}
+
+impl<'a, T, const B: usize> Cursor<'a, T, B> {
+    pub(crate) fn new(list: &'a BTreeList<T, B>, index: usize) -> Self {
+        Self {
+            list,
+            index,
+            path: Path::seek(list, index),
+        }
+    }
+
+    /// The index of the element the cursor is on.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the element the cursor is on, if it is not past the end of the list.
+    pub fn current(&self) -> Option<&'a T> {
+        self.path.get()
+    }
+
+    /// Get the element after the cursor without moving it.
+    pub fn peek_next(&self) -> Option<&'a T> {
+        self.path.peek_next()
+    }
+
+    /// Get the element before the cursor without moving it.
+    pub fn peek_prev(&self) -> Option<&'a T> {
+        if self.index == self.list.len() {
+            self.list.last()
+        } else {
+            self.path.peek_prev()
+        }
+    }
+
+    /// Move the cursor to the next element, returning whether it moved. The cursor can move
+    /// one place past the last element.
+    pub fn move_next(&mut self) -> bool {
+        if self.index < self.list.len() {
+            self.index += 1;
+            self.path.move_next();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move the cursor to the previous element, returning whether it moved.
+    pub fn move_prev(&mut self) -> bool {
+        if self.index == self.list.len() {
+            if self.index == 0 {
+                return false;
+            }
+            self.index -= 1;
+            self.path = Path::seek(self.list, self.index);
+            true
+        } else if self.path.move_prev() {
+            self.index -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Move the cursor to `index`, returning false without moving if it is past the end of the
+    /// list. The cursor can move one place past the last element.
+    ///
+    /// Only the part of the cursor's path that differs is walked, so seeking to nearby indices,
+    /// such as when reading sequentially with gaps, takes `O(1)` amortized time rather than a
+    /// descent from the root.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let list = (0..100).collect::<BTreeList<_>>();
+    /// let mut cursor = list.cursor_at(0).unwrap();
+    /// for i in (0..100).step_by(3) {
+    ///     assert!(cursor.seek(i));
+    ///     assert_eq!(cursor.current(), Some(&i));
+    /// }
+    /// assert!(!cursor.seek(101));
+    /// ```
+    pub fn seek(&mut self, index: usize) -> bool {
+        if index > self.list.len() {
+            return false;
+        }
+        self.path.seek_from(self.list, self.index, index);
+        self.index = index;
+        true
+    }
+}
+
+/// A cursor over a [`BTreeList`] that can edit the list at its position.
+///
+/// The cursor sits on an element of the list, or just past the last element when its index is
+/// equal to the length of the list. It holds the nodes on its path down the tree while it is on
+/// an element, so moving to neighbouring elements and inserting or removing next to it only
+/// touches the nodes around it. The tree is only rebalanced when a node fills up or runs low,
+/// which keeps runs of edits in one place `O(1)` amortized.
+#[derive(Debug)]
+pub struct CursorMut<'a, T, const B: usize> {
+    list: &'a mut BTreeList<T, B>,
+    index: usize,
+    len: usize,
+    zipper: Option<Zipper<T, B>>,
+}
+
+impl<'a, T, const B: usize> CursorMut<'a, T, B> {
+    pub(crate) fn new(list: &'a mut BTreeList<T, B>, index: usize) -> Self {
+        let len = list.len();
+        Self {
+            list,
+            index,
+            len,
+            zipper: None,
+        }
+    }
+
+    /// Open the tree at the cursor's element, if it is on one.
+    fn zipper(&mut self) -> Option<&mut Zipper<T, B>> {
+        if self.zipper.is_none() && self.index < self.len {
+            self.zipper = Some(Zipper::open(self.list, self.index));
+        }
+        self.zipper.as_mut()
+    }
+
+    /// Put the tree back together in the list.
+    fn close(&mut self) {
+        if let Some(zipper) = self.zipper.take() {
+            zipper.close(self.list);
+        }
+    }
+
+    /// The index of the element the cursor is on.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the element the cursor is on, if it is not past the end of the list.
+    pub fn current(&self) -> Option<&T> {
+        match &self.zipper {
+            Some(zipper) => Some(zipper.get()),
+            None => self.list.get(self.index),
+        }
+    }
+
+    /// Get the element the cursor is on mutably, if it is not past the end of the list.
+    pub fn current_mut(&mut self) -> Option<&mut T> {
+        self.zipper().map(Zipper::get_mut)
+    }
+
+    /// Move the cursor to the next element, returning whether it moved. The cursor can move
+    /// one place past the last element.
+    pub fn move_next(&mut self) -> bool {
+        if self.index >= self.len {
+            return false;
+        }
+        self.index += 1;
+        if let Some(zipper) = self.zipper.as_mut() {
+            if !zipper.move_next() {
+                self.close();
+            }
+        }
+        true
+    }
+
+    /// Move the cursor to the previous element, returning whether it moved.
+    pub fn move_prev(&mut self) -> bool {
+        if self.index == 0 {
+            return false;
+        }
+        self.index -= 1;
+        if let Some(zipper) = self.zipper.as_mut() {
+            if !zipper.move_prev() {
+                self.close();
+            }
+        }
+        true
+    }
+
+    /// Move the cursor to `index`, returning false without moving if it is past the end of the
+    /// list. The cursor can move one place past the last element.
+    ///
+    /// As with [`Cursor::seek`], seeking to nearby indices takes `O(1)` amortized time.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list = (0..100).collect::<BTreeList<_>>();
+    /// let mut cursor = list.cursor_mut_at(0).unwrap();
+    /// for i in (0..100).step_by(2) {
+    ///     assert!(cursor.seek(i));
+    ///     *cursor.current_mut().unwrap() *= 10;
+    /// }
+    /// drop(cursor);
+    /// assert_eq!(list.get(4), Some(&40));
+    /// assert_eq!(list.get(5), Some(&5));
+    /// ```
+    pub fn seek(&mut self, index: usize) -> bool {
+        if index > self.len {
+            return false;
+        }
+        if index == self.len {
+            self.close();
+        } else if let Some(zipper) = self.zipper.as_mut() {
+            zipper.seek_from(self.index, index);
+        }
+        self.index = index;
+        true
+    }
+
+    /// Insert `element` before the cursor, leaving the cursor on the same element. If the cursor
+    /// is past the end of the list the element is pushed onto the back.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 3];
+    /// let mut cursor = list.cursor_mut_at(1).unwrap();
+    /// cursor.insert_before(2);
+    /// assert_eq!(cursor.current(), Some(&3));
+    /// assert_eq!(cursor.index(), 2);
+    /// drop(cursor);
+    /// assert_eq!(list, btreelist![1, 2, 3]);
+    /// ```
+    pub fn insert_before(&mut self, element: T) {
+        let element = match self.zipper() {
+            Some(zipper) => zipper.insert_in_leaf(element, false).err(),
+            None => Some(element),
+        };
+        if let Some(element) = element {
+            self.close();
+            let _ = self.list.insert(self.index, element);
+        }
+        self.index += 1;
+        self.len += 1;
+    }
+
+    /// Insert `element` after the cursor, leaving the cursor on the same element. If the cursor
+    /// is past the end of the list the element is pushed onto the back.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 3];
+    /// let mut cursor = list.cursor_mut_at(0).unwrap();
+    /// cursor.insert_after(2);
+    /// assert_eq!(cursor.current(), Some(&1));
+    /// drop(cursor);
+    /// assert_eq!(list, btreelist![1, 2, 3]);
+    /// ```
+    pub fn insert_after(&mut self, element: T) {
+        if self.index >= self.len {
+            return self.insert_before(element);
+        }
+        let element = match self.zipper() {
+            Some(zipper) => zipper.insert_in_leaf(element, true).err(),
+            None => Some(element),
+        };
+        if let Some(element) = element {
+            self.close();
+            let _ = self.list.insert(self.index + 1, element);
+        }
+        self.len += 1;
+    }
+
+    /// Remove the element the cursor is on, moving the cursor on to the next element. Returns
+    /// `None` if the cursor is past the end of the list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3];
+    /// let mut cursor = list.cursor_mut_at(1).unwrap();
+    /// assert_eq!(cursor.remove_current(), Some(2));
+    /// assert_eq!(cursor.current(), Some(&3));
+    /// assert_eq!(cursor.remove_current(), Some(3));
+    /// assert_eq!(cursor.remove_current(), None);
+    /// drop(cursor);
+    /// assert_eq!(list, btreelist![1]);
+    /// ```
+    pub fn remove_current(&mut self) -> Option<T> {
+        if self.index >= self.len {
+            return None;
+        }
+        self.len -= 1;
+        match self.zipper().and_then(Zipper::remove_from_leaf) {
+            Some((element, has_next)) => {
+                if !has_next {
+                    self.close();
+                }
+                Some(element)
+            }
+            None => {
+                self.close();
+                self.list.remove(self.index)
+            }
+        }
+    }
+
+    /// Remove up to `n` elements starting at the cursor, returning them as a new list.
+    ///
+    /// The elements are cut out of the tree in one go rather than removed one at a time. The
+    /// cursor is left on the element that followed the removed ones.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4, 5];
+    /// let mut cursor = list.cursor_mut_at(1).unwrap();
+    /// assert_eq!(cursor.remove_range_forward(2), btreelist![2, 3]);
+    /// assert_eq!(cursor.current(), Some(&4));
+    /// drop(cursor);
+    /// assert_eq!(list, btreelist![1, 4, 5]);
+    /// ```
+    pub fn remove_range_forward(&mut self, n: usize) -> BTreeList<T, B> {
+        self.close();
+        let end = self.index + n.min(self.len - self.index);
+        let removed = self.list.remove_range(self.index..end).unwrap_or_default();
+        self.len -= removed.len();
+        removed
+    }
+
+    /// Remove the elements starting at the cursor for as long as `pred` returns true, returning
+    /// them as a new list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist!['a', ' ', ' ', 'b'];
+    /// let mut cursor = list.cursor_mut_at(1).unwrap();
+    /// assert_eq!(cursor.remove_forward_while(|c| c.is_whitespace()), btreelist![' ', ' ']);
+    /// drop(cursor);
+    /// assert_eq!(list, btreelist!['a', 'b']);
+    /// ```
+    pub fn remove_forward_while<P>(&mut self, mut pred: P) -> BTreeList<T, B>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.close();
+        let mut n = 0;
+        let _ = self.list.try_for_each_chunk_from(self.index, |chunk| {
+            let matched = chunk.iter().take_while(|e| pred(e)).count();
+            n += matched;
+            if matched < chunk.len() {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        self.remove_range_forward(n)
+    }
+}
+
+impl<'a, T, const B: usize> Drop for CursorMut<'a, T, B> {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
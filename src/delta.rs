@@ -0,0 +1,520 @@
+use std::{cmp::min, error::Error, fmt, vec};
+
+use crate::BTreeList;
+
+/// A single step of a [`ListDelta`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeltaOp<T> {
+    /// Keep the next `n` elements.
+    Retain(usize),
+    /// Insert these elements.
+    Insert(Vec<T>),
+    /// Delete the next elements, which are kept so that the delta can be inverted.
+    Delete(Vec<T>),
+}
+
+impl<T> DeltaOp<T> {
+    /// The number of elements this step covers.
+    pub fn len(&self) -> usize {
+        match self {
+            DeltaOp::Retain(n) => *n,
+            DeltaOp::Insert(elements) | DeltaOp::Delete(elements) => elements.len(),
+        }
+    }
+
+    /// Check if this step covers no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// A batch of edits to a list, as a sequence of steps walking over it from the front.
+///
+/// Elements after the last step are left as they are. Deleted elements are recorded in the delta
+/// so that it can be [inverted](Self::invert) to undo it, and two deltas can be
+/// [composed](Self::compose) into one with the same effect as applying them in turn.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::ListDelta;
+/// let mut list = btreelist!['a', 'b', 'c'];
+/// let mut delta = ListDelta::new();
+/// delta.retain(1).delete(vec!['b']).insert(vec!['x', 'y']);
+/// assert!(list.apply_delta(&delta));
+/// assert_eq!(list, btreelist!['a', 'x', 'y', 'c']);
+/// assert!(list.apply_delta(&delta.invert()));
+/// assert_eq!(list, btreelist!['a', 'b', 'c']);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListDelta<T> {
+    ops: Vec<DeltaOp<T>>,
+}
+
+impl<T> ListDelta<T> {
+    /// Construct a new, empty [`ListDelta`] that leaves lists unchanged.
+    pub fn new() -> Self {
+        Self { ops: Vec::new() }
+    }
+
+    /// Add a step keeping the next `n` elements.
+    pub fn retain(&mut self, n: usize) -> &mut Self {
+        self.push(DeltaOp::Retain(n))
+    }
+
+    /// Add a step inserting `elements`.
+    pub fn insert<I>(&mut self, elements: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.push(DeltaOp::Insert(elements.into_iter().collect()))
+    }
+
+    /// Add a step deleting the next elements, which should be equal to `elements`.
+    pub fn delete<I>(&mut self, elements: I) -> &mut Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        self.push(DeltaOp::Delete(elements.into_iter().collect()))
+    }
+
+    /// Add a step, merging it into the last one if they are of the same kind.
+    pub fn push(&mut self, op: DeltaOp<T>) -> &mut Self {
+        if op.is_empty() {
+            return self;
+        }
+        match (self.ops.last_mut(), op) {
+            (Some(DeltaOp::Retain(last)), DeltaOp::Retain(n)) => *last += n,
+            (Some(DeltaOp::Insert(last)), DeltaOp::Insert(elements))
+            | (Some(DeltaOp::Delete(last)), DeltaOp::Delete(elements)) => last.extend(elements),
+            (_, op) => self.ops.push(op),
+        }
+        self
+    }
+
+    /// The steps of the delta, in order.
+    pub fn ops(&self) -> &[DeltaOp<T>] {
+        &self.ops
+    }
+
+    /// The number of elements the delta walks over in the list it is applied to, which must be
+    /// at least this long.
+    pub fn base_len(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| !matches!(op, DeltaOp::Insert(_)))
+            .map(DeltaOp::len)
+            .sum()
+    }
+
+    /// The number of elements the delta leaves in place of those it walks over.
+    pub fn target_len(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| !matches!(op, DeltaOp::Delete(_)))
+            .map(DeltaOp::len)
+            .sum()
+    }
+
+    /// Move `bookmarks`, indices into a list the delta applies to, so that they point to the same
+    /// elements in the result.
+    ///
+    /// Bookmarks are plain indices stored by the caller, so unlike tracked handles they cost
+    /// nothing while the list is edited and are shifted in bulk afterwards, taking
+    /// `O(m log(m) + d)` time for `m` bookmarks and a delta of `d` steps. A bookmark on a deleted
+    /// element moves to the position after the deletion, and elements inserted at a bookmark go
+    /// before it. Bookmarks past the end of the delta keep their offset from it.
+    ///
+    /// ```
+    /// # use btreelist::ListDelta;
+    /// let mut delta = ListDelta::new();
+    /// delta.retain(1).insert(vec!['x', 'y']).retain(1).delete(vec!['c']);
+    ///
+    /// // list: ['a', 'b', 'c', 'd'] -> ['a', 'x', 'y', 'b', 'd']
+    /// let mut bookmarks = [0, 1, 2, 3, 4];
+    /// delta.rebase_bookmarks(&mut bookmarks);
+    /// assert_eq!(bookmarks, [0, 3, 4, 4, 5]);
+    /// ```
+    pub fn rebase_bookmarks(&self, bookmarks: &mut [usize]) {
+        let mut order = (0..bookmarks.len()).collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&i| bookmarks[i]);
+        let mut order = order.into_iter().peekable();
+        // the start of the current step in the list before and after the delta
+        let mut old = 0;
+        let mut new = 0;
+        for op in &self.ops {
+            match op {
+                DeltaOp::Retain(n) => {
+                    while let Some(i) = order.next_if(|&i| bookmarks[i] < old + n) {
+                        bookmarks[i] = bookmarks[i] - old + new;
+                    }
+                    old += n;
+                    new += n;
+                }
+                DeltaOp::Insert(elements) => new += elements.len(),
+                DeltaOp::Delete(elements) => {
+                    while let Some(i) = order.next_if(|&i| bookmarks[i] < old + elements.len()) {
+                        bookmarks[i] = new;
+                    }
+                    old += elements.len();
+                }
+            }
+        }
+        for i in order {
+            bookmarks[i] = bookmarks[i] - old + new;
+        }
+    }
+
+    /// Build the delta that undoes this one, turning its insertions into deletions and the other
+    /// way around.
+    pub fn invert(&self) -> Self
+    where
+        T: Clone,
+    {
+        let ops = self
+            .ops
+            .iter()
+            .map(|op| match op {
+                DeltaOp::Retain(n) => DeltaOp::Retain(*n),
+                DeltaOp::Insert(elements) => DeltaOp::Delete(elements.clone()),
+                DeltaOp::Delete(elements) => DeltaOp::Insert(elements.clone()),
+            })
+            .collect();
+        Self { ops }
+    }
+
+    /// Combine this delta with `other`, which applies to the result of this one, into a single
+    /// delta with the same effect as applying both in turn.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// # use btreelist::ListDelta;
+    /// let mut first = ListDelta::new();
+    /// first.retain(1).insert(vec![5, 6]);
+    /// let mut second = ListDelta::new();
+    /// second.retain(2).delete(vec![6, 2]);
+    ///
+    /// let mut list = btreelist![1, 2, 3];
+    /// assert!(list.apply_delta(&first.compose(&second)));
+    /// assert_eq!(list, btreelist![1, 5, 3]);
+    /// ```
+    pub fn compose(&self, other: &Self) -> Self
+    where
+        T: Clone,
+    {
+        let mut first = Steps::new(self.ops.clone());
+        let mut second = Steps::new(other.ops.clone());
+        let mut composed = Self::new();
+        loop {
+            let n = match (first.peek(), second.peek()) {
+                (None, None) => break,
+                // elements inserted by the second delta aren't seen by the first
+                (_, Some(DeltaOp::Insert(_))) | (None, Some(_)) => {
+                    composed.push(second.take(usize::MAX));
+                    continue;
+                }
+                // elements deleted by the first delta aren't seen by the second
+                (Some(DeltaOp::Delete(_)), _) | (Some(_), None) => {
+                    composed.push(first.take(usize::MAX));
+                    continue;
+                }
+                (Some(a), Some(b)) => min(a.len(), b.len()),
+            };
+            match (first.take(n), second.take(n)) {
+                (DeltaOp::Retain(_), DeltaOp::Retain(_)) => composed.retain(n),
+                (DeltaOp::Retain(_), DeltaOp::Delete(elements)) => composed.delete(elements),
+                (DeltaOp::Insert(elements), DeltaOp::Retain(_)) => composed.insert(elements),
+                // inserted by the first and deleted by the second so never seen
+                (DeltaOp::Insert(_), DeltaOp::Delete(_)) => &mut composed,
+                _ => unreachable!(
+                    "deletes from the first and inserts from the second are taken whole"
+                ),
+            };
+        }
+        composed
+    }
+}
+
+impl<T> Default for ListDelta<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The first step of a patch that didn't apply cleanly, from
+/// [`apply_patch`](BTreeList::apply_patch).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchError {
+    /// The step at index `step` of the patch runs past the end of the list from `position`.
+    OutOfBounds {
+        /// The index of the step in the patch's [`ops`](ListDelta::ops).
+        step: usize,
+        /// The position in the list the step starts at.
+        position: usize,
+    },
+    /// The elements deleted by the step at index `step` of the patch don't match those in the list
+    /// from `position`.
+    Mismatch {
+        /// The index of the step in the patch's [`ops`](ListDelta::ops).
+        step: usize,
+        /// The position in the list the step starts at.
+        position: usize,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::OutOfBounds { step, position } => write!(
+                f,
+                "step {} of the patch runs past the end of the list from position {}",
+                step, position
+            ),
+            PatchError::Mismatch { step, position } => write!(
+                f,
+                "step {} of the patch deletes elements that don't match the list at position {}",
+                step, position
+            ),
+        }
+    }
+}
+
+impl Error for PatchError {}
+
+/// The steps of a delta being consumed a piece at a time.
+struct Steps<T> {
+    current: Option<DeltaOp<T>>,
+    rest: vec::IntoIter<DeltaOp<T>>,
+}
+
+impl<T> Steps<T> {
+    fn new(ops: Vec<DeltaOp<T>>) -> Self {
+        let mut rest = ops.into_iter();
+        Self {
+            current: rest.next(),
+            rest,
+        }
+    }
+
+    fn peek(&self) -> Option<&DeltaOp<T>> {
+        self.current.as_ref()
+    }
+
+    /// Take up to `n` elements from the current step, moving on to the next step once it is used
+    /// up.
+    fn take(&mut self, n: usize) -> DeltaOp<T> {
+        let op = self.current.take().expect("no steps left to take");
+        if op.len() <= n {
+            self.current = self.rest.next();
+            return op;
+        }
+        let (taken, left) = match op {
+            DeltaOp::Retain(m) => (DeltaOp::Retain(n), DeltaOp::Retain(m - n)),
+            DeltaOp::Insert(mut elements) => {
+                let left = elements.split_off(n);
+                (DeltaOp::Insert(elements), DeltaOp::Insert(left))
+            }
+            DeltaOp::Delete(mut elements) => {
+                let left = elements.split_off(n);
+                (DeltaOp::Delete(elements), DeltaOp::Delete(left))
+            }
+        };
+        self.current = Some(left);
+        taken
+    }
+}
+
+impl<T, const B: usize> BTreeList<T, B> {
+    /// Apply `delta` to the list, returning false and leaving the list unchanged if it is shorter
+    /// than the delta's [`base_len`](ListDelta::base_len).
+    ///
+    /// Deletions remove the elements at their position without checking them against those
+    /// recorded in the delta.
+    pub fn apply_delta(&mut self, delta: &ListDelta<T>) -> bool
+    where
+        T: Clone,
+    {
+        if delta.base_len() > self.len() {
+            return false;
+        }
+        let mut position = 0;
+        let mut edits = Vec::new();
+        for op in delta.ops() {
+            match op {
+                DeltaOp::Retain(n) => position += n,
+                DeltaOp::Insert(elements) => edits.push((position..position, elements.clone())),
+                DeltaOp::Delete(elements) => {
+                    edits.push((position..position + elements.len(), Vec::new()));
+                    position += elements.len();
+                }
+            }
+        }
+        self.apply_sorted_edits(edits).is_ok()
+    }
+
+    /// Apply `patch` to the list, checking that each deletion removes the elements the patch
+    /// recorded for it.
+    ///
+    /// The patch is checked against the list before anything is changed, so if a step doesn't
+    /// apply cleanly the first such step is described in the error and the list is left as it
+    /// was. The edits are then made in a single pass over the list, taking
+    /// `O(k log(n) + m)` time for `k` steps touching `m` elements.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// # use btreelist::{ListDelta, PatchError};
+    /// let mut list = btreelist!['a', 'b', 'c'];
+    /// let mut patch = ListDelta::new();
+    /// patch.retain(1).delete(vec!['b']).insert(vec!['x']);
+    /// assert_eq!(list.apply_patch(&patch), Ok(()));
+    /// assert_eq!(list, btreelist!['a', 'x', 'c']);
+    ///
+    /// // 'b' has already been replaced
+    /// assert_eq!(
+    ///     list.apply_patch(&patch),
+    ///     Err(PatchError::Mismatch { step: 1, position: 1 })
+    /// );
+    /// assert_eq!(list, btreelist!['a', 'x', 'c']);
+    /// ```
+    pub fn apply_patch(&mut self, patch: &ListDelta<T>) -> Result<(), PatchError>
+    where
+        T: Clone + PartialEq,
+    {
+        let mut position = 0;
+        let mut edits = Vec::new();
+        for (step, op) in patch.ops().iter().enumerate() {
+            let end = match op {
+                DeltaOp::Retain(n) => position + n,
+                DeltaOp::Insert(elements) => {
+                    edits.push((position..position, elements.clone()));
+                    continue;
+                }
+                DeltaOp::Delete(elements) => position + elements.len(),
+            };
+            if end > self.len() {
+                return Err(PatchError::OutOfBounds { step, position });
+            }
+            if let DeltaOp::Delete(elements) = op {
+                if !self.matches_at(position, elements) {
+                    return Err(PatchError::Mismatch { step, position });
+                }
+                edits.push((position..end, Vec::new()));
+            }
+            position = end;
+        }
+        self.apply_sorted_edits(edits)
+            .expect("patch was checked against the list");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist;
+    use crate::btreelist::tests::{assert_shape, TestRng};
+
+    #[test]
+    fn list_delta() {
+        // a random delta over `v`, with deletions recording the elements they remove
+        fn random_delta(v: &[usize], rng: &mut TestRng) -> ListDelta<usize> {
+            let mut delta = ListDelta::new();
+            let mut position = 0;
+            while position < v.len() {
+                let n = min(rng.next() % 8, v.len() - position);
+                match rng.next() % 3 {
+                    0 => {
+                        delta.retain(n);
+                    }
+                    1 => {
+                        delta.delete(v[position..position + n].iter().copied());
+                    }
+                    _ => {
+                        delta.insert((0..n).map(|i| 1000 + position + i));
+                        continue;
+                    }
+                }
+                position += n;
+            }
+            delta
+        }
+
+        fn applied(v: &[usize], delta: &ListDelta<usize>) -> Vec<usize> {
+            let mut t = BTreeList::<usize, 2>::from_exact_iter(v.iter().copied(), v.len());
+            let mut patched = t.clone();
+            assert!(t.apply_delta(delta));
+            assert_shape(&t);
+            assert_eq!(patched.apply_patch(delta), Ok(()));
+            assert_eq!(patched, t);
+            t.iter().copied().collect()
+        }
+
+        let mut rng = TestRng::new(7);
+        for len in [0, 1, 10, 100] {
+            let v = (0..len).collect::<Vec<_>>();
+            for _ in 0..20 {
+                let first = random_delta(&v, &mut rng);
+                let middle = applied(&v, &first);
+                assert_eq!(middle.len(), len - first.base_len() + first.target_len());
+                assert_eq!(applied(&middle, &first.invert()), v);
+
+                let mut bookmarks = (0..=len).rev().collect::<Vec<_>>();
+                first.rebase_bookmarks(&mut bookmarks);
+                assert!(bookmarks.windows(2).all(|w| w[0] >= w[1]));
+                assert_eq!(bookmarks[0], middle.len());
+                for (i, &bookmark) in bookmarks.iter().rev().enumerate() {
+                    if middle.contains(&i) {
+                        assert_eq!(middle[bookmark], i);
+                    }
+                }
+
+                let second = random_delta(&middle, &mut rng);
+                let end = applied(&middle, &second);
+                let composed = first.compose(&second);
+                assert_eq!(applied(&v, &composed), end);
+                assert_eq!(applied(&end, &composed.invert()), v);
+            }
+        }
+
+        let mut delta = ListDelta::new();
+        delta.retain(2).retain(1).insert(vec![]).delete(vec![1]);
+        assert_eq!(delta.ops(), &[DeltaOp::Retain(3), DeltaOp::Delete(vec![1])]);
+        let mut t: BTreeList<usize> = btreelist![1, 2, 3];
+        assert!(!t.apply_delta(&delta));
+        assert_eq!(t, btreelist![1, 2, 3]);
+    }
+
+    #[test]
+    fn apply_patch() {
+        let mut t = BTreeList::<usize, 2>::from_exact_iter(0..100, 100);
+        let mut patch = ListDelta::new();
+        patch
+            .retain(10)
+            .delete(10..20)
+            .insert(vec![1, 2])
+            .retain(50)
+            .delete(70..75);
+        let mut mismatched = patch.clone();
+        mismatched.retain(1).delete(vec![0]);
+        assert_eq!(
+            t.apply_patch(&mismatched),
+            Err(PatchError::Mismatch {
+                step: 6,
+                position: 76
+            })
+        );
+        let mut overlong = patch.clone();
+        overlong.retain(26);
+        assert_eq!(
+            t.apply_patch(&overlong),
+            Err(PatchError::OutOfBounds {
+                step: 5,
+                position: 75
+            })
+        );
+        assert!(t.eq_iter(0..100));
+
+        assert_eq!(t.apply_patch(&patch), Ok(()));
+        assert_shape(&t);
+        assert!(t.eq_iter((0..10).chain([1, 2]).chain(20..70).chain(75..100)));
+    }
+}
@@ -0,0 +1,242 @@
+use std::{
+    io::{self, Read, Seek, SeekFrom, Write},
+    ops::{ControlFlow, Range, RangeBounds},
+};
+
+use crate::{BTreeList, CursorMut};
+
+/// A reader over a range of a [`BTreeList`] of bytes, created by
+/// [`as_read_seek_cursor`](BTreeList::as_read_seek_cursor).
+///
+/// Positions are relative to the start of the range and reads stop at its end, so the range looks
+/// like a file of its own.
+#[derive(Debug, Clone)]
+pub struct ReadSeekCursor<'a, const B: usize> {
+    list: &'a BTreeList<u8, B>,
+    range: Range<usize>,
+    position: u64,
+}
+
+impl<'a, const B: usize> ReadSeekCursor<'a, B> {
+    /// The position of the cursor from the start of its range.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<'a, const B: usize> Read for ReadSeekCursor<'a, B> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.range.len() as u64 {
+            return Ok(0);
+        }
+        let start = self.range.start + self.position as usize;
+        let n = buf.len().min(self.range.end - start);
+        let mut read = 0;
+        let _ = self.list.try_for_each_chunk_from(start, |chunk| {
+            let m = chunk.len().min(n - read);
+            buf[read..read + m].copy_from_slice(&chunk[..m]);
+            read += m;
+            if read < n {
+                ControlFlow::Continue(())
+            } else {
+                ControlFlow::Break(())
+            }
+        });
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<'a, const B: usize> Seek for ReadSeekCursor<'a, B> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(offset) => {
+                self.position = offset;
+                return Ok(offset);
+            }
+            SeekFrom::End(offset) => (self.range.len() as u64, offset),
+            SeekFrom::Current(offset) => (self.position, offset),
+        };
+        let position = if offset >= 0 {
+            base.checked_add(offset as u64)
+        } else {
+            base.checked_sub(offset.unsigned_abs())
+        };
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+impl<const B: usize> BTreeList<u8, B> {
+    /// Create a reader over the bytes in `range`, returning `None` if it is out of bounds.
+    ///
+    /// The reader copies straight out of the nodes, so a region embedded in the list can be
+    /// handed to a parser without copying it out first. Seeking past the end of the range is
+    /// allowed, as for [`std::io::Cursor`], and reads there return no bytes.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// # use std::io::{Read, Seek, SeekFrom};
+    /// let list = b"header:payload:footer".iter().copied().collect::<BTreeList<_>>();
+    /// let mut reader = list.as_read_seek_cursor(7..14).unwrap();
+    ///
+    /// let mut payload = String::new();
+    /// reader.read_to_string(&mut payload).unwrap();
+    /// assert_eq!(payload, "payload");
+    ///
+    /// reader.seek(SeekFrom::End(-4)).unwrap();
+    /// let mut tail = [0; 4];
+    /// reader.read_exact(&mut tail).unwrap();
+    /// assert_eq!(&tail, b"load");
+    /// assert!(reader.seek(SeekFrom::Current(-8)).is_err());
+    /// ```
+    pub fn as_read_seek_cursor<R>(&self, range: R) -> Option<ReadSeekCursor<'_, B>>
+    where
+        R: RangeBounds<usize>,
+    {
+        Some(ReadSeekCursor {
+            list: self,
+            range: self.resolve_range(range)?,
+            position: 0,
+        })
+    }
+}
+
+/// Overwrites the bytes from the cursor onwards, moving the cursor past them, and pushes bytes
+/// onto the end of the list once the cursor reaches it.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// # use std::io::Write;
+/// let mut list = b"hello world".iter().copied().collect::<BTreeList<_>>();
+/// let mut cursor = list.cursor_mut_at(6).unwrap();
+/// cursor.write_all(b"there!").unwrap();
+/// assert_eq!(cursor.index(), 12);
+/// drop(cursor);
+/// assert_eq!(list, b"hello there!"[..]);
+/// ```
+impl<'a, const B: usize> Write for CursorMut<'a, u8, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            match self.current_mut() {
+                Some(current) => {
+                    *current = byte;
+                    self.move_next();
+                }
+                None => self.insert_before(byte),
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Appends the bytes to the end of the list, so a list can be used as a growable output buffer.
+///
+/// Short writes are pushed onto the end a byte at a time. Longer ones are built into a tree of
+/// full leaves first and then joined onto the end, taking `O(len + log(n))` time.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// # use std::io::Write;
+/// let mut buffer = BTreeList::<u8>::new();
+/// write!(buffer, "{}:{}", "key", 42).unwrap();
+/// buffer.write_all(&[b'!'; 100]).unwrap();
+/// assert_eq!(buffer.len(), 106);
+/// assert_eq!(buffer.iter().take(6).copied().collect::<Vec<_>>(), b"key:42");
+/// ```
+impl<const B: usize> Write for BTreeList<u8, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() < 2 * B {
+            for &byte in buf {
+                self.push_back(byte);
+            }
+        } else {
+            let mut tail = Self::from_exact_iter(buf.iter().copied(), buf.len());
+            self.append(&mut tail);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::assert_shape;
+
+    #[test]
+    fn write_list() {
+        use std::io::Write;
+
+        let mut t = BTreeList::<u8, 2>::new();
+        let mut v = Vec::new();
+        for len in (0..50).chain([500, 3, 1000]) {
+            let bytes = (0..len).map(|i| (i * 7) as u8).collect::<Vec<u8>>();
+            assert_eq!(t.write(&bytes).unwrap(), len);
+            v.extend_from_slice(&bytes);
+            assert_shape(&t);
+        }
+        t.flush().unwrap();
+        assert_eq!(t, v);
+    }
+
+    #[test]
+    fn read_seek_cursor() {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let bytes = (0..1000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let t = BTreeList::<u8, 2>::from_exact_iter(bytes.iter().copied(), bytes.len());
+        let mut reader = t.as_read_seek_cursor(100..900).unwrap();
+        let mut buf = [0; 7];
+        let mut read = Vec::new();
+        loop {
+            match reader.read(&mut buf).unwrap() {
+                0 => break,
+                n => read.extend_from_slice(&buf[..n]),
+            }
+        }
+        assert_eq!(read, &bytes[100..900]);
+
+        assert_eq!(reader.seek(SeekFrom::Start(850)).unwrap(), 850);
+        assert_eq!(reader.read(&mut buf).unwrap(), 0);
+        assert_eq!(reader.seek(SeekFrom::End(-10)).unwrap(), 790);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, &bytes[890..900]);
+        assert!(t.as_read_seek_cursor(900..1001).is_none());
+    }
+
+    #[test]
+    fn write_cursor_mut() {
+        use std::io::Write;
+
+        let mut t = BTreeList::<u8, 2>::from_exact_iter(0..200, 200);
+        let mut v = (0..200).collect::<Vec<u8>>();
+        let patch = (0..100).map(|i| 255 - i).collect::<Vec<u8>>();
+        let mut cursor = t.cursor_mut_at(150).unwrap();
+        for chunk in patch.chunks(7) {
+            cursor.write_all(chunk).unwrap();
+        }
+        assert_eq!(cursor.index(), 250);
+        drop(cursor);
+        v.truncate(150);
+        v.extend_from_slice(&patch);
+        assert_shape(&t);
+        assert_eq!(t, v);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::BTreeList;
+use std::fmt;
+
+use crate::{BTreeList, Cursor};
 
 /// An iterator over items in a [`BTreeList`].
 #[derive(Debug, Clone)]
@@ -31,3 +33,53 @@ impl<'a, T, const B: usize> DoubleEndedIterator for Iter<'a, T, B> {
         }
     }
 }
+
+/// An iterator over the runs of a [`BTreeList`] separated by elements matching a predicate,
+/// created by [`split`](BTreeList::split).
+pub struct Split<'a, T, P, const B: usize> {
+    pub(crate) list: &'a BTreeList<T, B>,
+    pub(crate) cursor: Option<Cursor<'a, T, B>>,
+    pub(crate) pred: P,
+}
+
+impl<'a, T, P, const B: usize> Iterator for Split<'a, T, P, B>
+where
+    P: FnMut(&T) -> bool,
+{
+    type Item = Iter<'a, T, B>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let cursor = self.cursor.as_mut()?;
+        let list = self.list;
+        let start = cursor.index();
+        while let Some(element) = cursor.current() {
+            if (self.pred)(element) {
+                let end = cursor.index();
+                cursor.move_next();
+                return Some(Iter {
+                    inner: list,
+                    index: start,
+                    index_back: end,
+                });
+            }
+            cursor.move_next();
+        }
+        self.cursor = None;
+        Some(Iter {
+            inner: list,
+            index: start,
+            index_back: list.len(),
+        })
+    }
+}
+
+impl<'a, T, P, const B: usize> fmt::Debug for Split<'a, T, P, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Split")
+            .field("cursor", &self.cursor)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,444 @@
+use std::{fmt, iter::FromIterator};
+
+use crate::{BTreeList, Iter};
+
+/// A position in a [`LabelledList`] that sorts in the same order as the list.
+///
+/// A label is a fraction between zero and one, written as digits in base `2^32` with no trailing
+/// zeros so that comparing the digits lexicographically compares the fractions. There is always
+/// another fraction between any two, so elements keep their labels for as long as they are in the
+/// list.
+///
+/// Labels also act as handles to their elements: each carries an id unique within its list, so
+/// the label of a removed element never finds another element inserted in the same place later.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Label {
+    digits: Vec<u32>,
+    id: u64,
+}
+
+impl Label {
+    /// How far past their neighbour labels are placed when inserting at the ends of the list,
+    /// leaving room for many more before needing another digit.
+    const STEP: u64 = 1 << 16;
+
+    /// Find the digits of a new label after `lower` and before `upper`, with `None` being the ends
+    /// of the list.
+    fn between(lower: Option<&Self>, upper: Option<&Self>) -> Vec<u32> {
+        let lower_digits = lower.map_or(&[][..], |label| &label.digits[..]);
+        let mut upper_digits = upper.map(|label| &label.digits[..]);
+        let mut digits = Vec::new();
+        for i in 0.. {
+            let lo = u64::from(lower_digits.get(i).copied().unwrap_or(0));
+            // once the new label is below the upper one in an earlier digit any digit will do
+            let hi = upper_digits.map_or(1 << 32, |upper| {
+                u64::from(upper.get(i).copied().unwrap_or(0))
+            });
+            if hi - lo > 1 {
+                let digit = match (lower, upper) {
+                    // at an end of the list, so leave room for the elements likely to follow
+                    (Some(_), None) if hi - lo > 2 * Self::STEP => lo + Self::STEP,
+                    (None, Some(_)) if hi - lo > 2 * Self::STEP => hi - Self::STEP,
+                    _ => lo + (hi - lo) / 2,
+                };
+                digits.push(digit as u32);
+                break;
+            }
+            digits.push(lo as u32);
+            if hi - lo == 1 {
+                upper_digits = None;
+            }
+        }
+        digits
+    }
+}
+
+/// A list giving each element a [`Label`] that sorts in the same order as the elements.
+///
+/// Comparing the labels of two elements finds which comes first without looking up their
+/// indices, and an element keeps its label as other elements are inserted and removed around it.
+/// Inserting an element returns its label, which then serves as a handle to find the element again
+/// in `O(log(n))` time with [`index_of`](Self::index_of) or [`get_by_label`](Self::get_by_label),
+/// however the list has been edited since.
+///
+/// Labels are usually a digit or two long, but grow by a digit for every 32 or so elements
+/// inserted into the same gap between two others, making them slower to compare.
+///
+/// ```
+/// # use btreelist::LabelledList;
+/// let mut list = LabelledList::<&str>::new();
+/// let a = list.push_back("a");
+/// let c = list.push_back("c");
+///
+/// let b = list.insert(1, "b").unwrap();
+/// list.push_front("start");
+/// assert!(a < b && b < c);
+/// assert_eq!(list.index_of(&c), Some(3));
+/// assert_eq!(list.get_by_label(&b), Some(&"b"));
+/// ```
+pub struct LabelledList<T, const B: usize = 6> {
+    list: BTreeList<(Label, T), B>,
+    next_id: u64,
+}
+
+impl<T, const B: usize> LabelledList<T, B> {
+    /// Construct a new, empty [`LabelledList`].
+    ///
+    /// ```
+    /// # use btreelist::LabelledList;
+    /// let list: LabelledList<i32> = LabelledList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            list: BTreeList::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Get the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Check if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Create an iterator through the list's elements.
+    pub fn iter(&self) -> LabelledIter<'_, T, B> {
+        LabelledIter {
+            inner: self.list.iter(),
+        }
+    }
+
+    /// Get the element at `index`, if it is in the list.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.list.get(index).map(|(_, element)| element)
+    }
+
+    /// Get the element at `index` mutably, if it is in the list.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.list.get_mut(index).map(|(_, element)| element)
+    }
+
+    /// Get the first element of the list, if it isn't empty.
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Get the last element of the list, if it isn't empty.
+    pub fn last(&self) -> Option<&T> {
+        self.list.last().map(|(_, element)| element)
+    }
+
+    /// Get the label of the element at `index`, if it is in the list.
+    ///
+    /// ```
+    /// # use btreelist::LabelledList;
+    /// let list = (0..100).collect::<LabelledList<_>>();
+    /// assert!(list.label(10) < list.label(90));
+    /// assert_eq!(list.label(100), None);
+    /// ```
+    pub fn label(&self, index: usize) -> Option<&Label> {
+        self.list.get(index).map(|(label, _)| label)
+    }
+
+    /// Find the index of the element with `label`, if it is still in the list.
+    ///
+    /// ```
+    /// # use btreelist::LabelledList;
+    /// let mut list = (0..100).collect::<LabelledList<_>>();
+    /// let label = list.label(50).unwrap().clone();
+    /// list.remove(0);
+    /// assert_eq!(list.index_of(&label), Some(49));
+    /// list.remove(49);
+    /// assert_eq!(list.index_of(&label), None);
+    /// // the new element gets a new label
+    /// list.insert(49, 50).unwrap();
+    /// assert_eq!(list.index_of(&label), None);
+    /// ```
+    pub fn index_of(&self, label: &Label) -> Option<usize> {
+        self.list.binary_search_by(|(l, _)| l.cmp(label)).ok()
+    }
+
+    /// Get the element with `label`, if it is still in the list.
+    pub fn get_by_label(&self, label: &Label) -> Option<&T> {
+        self.index_of(label).and_then(|index| self.get(index))
+    }
+
+    /// Get the element with `label` mutably, if it is still in the list.
+    pub fn get_mut_by_label(&mut self, label: &Label) -> Option<&mut T> {
+        let index = self.index_of(label)?;
+        self.get_mut(index)
+    }
+
+    /// Remove the element with `label`, returning it if it was still in the list.
+    pub fn remove_by_label(&mut self, label: &Label) -> Option<T> {
+        let index = self.index_of(label)?;
+        self.remove(index)
+    }
+
+    /// Insert the `element` into the list at `index`, returning the label it was given between
+    /// those of its neighbours, or returning it back if the index is past the end of the list.
+    pub fn insert(&mut self, index: usize, element: T) -> Result<Label, T> {
+        if index > self.len() {
+            return Err(element);
+        }
+        let lower = index.checked_sub(1).and_then(|i| self.label(i));
+        let label = self.next_label(Label::between(lower, self.label(index)));
+        match self.list.insert(index, (label.clone(), element)) {
+            Ok(()) => Ok(label),
+            Err((_, element)) => Err(element),
+        }
+    }
+
+    /// Push the `element` onto the back of the list, returning its label.
+    pub fn push_back(&mut self, element: T) -> Label {
+        let label = self.next_label(Label::between(
+            self.list.last().map(|(label, _)| label),
+            None,
+        ));
+        self.list.push_back((label.clone(), element));
+        label
+    }
+
+    /// Push the `element` onto the front of the list, returning its label.
+    pub fn push_front(&mut self, element: T) -> Label {
+        let label = self.next_label(Label::between(None, self.label(0)));
+        self.list.push_front((label.clone(), element));
+        label
+    }
+
+    fn next_label(&mut self, digits: Vec<u32>) -> Label {
+        self.next_id += 1;
+        Label {
+            digits,
+            id: self.next_id,
+        }
+    }
+
+    /// Remove the element at `index`, returning it if the index was in the list.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.list.remove(index).map(|(_, element)| element)
+    }
+
+    /// Remove the last element of the list, returning it if the list wasn't empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.list.pop_back().map(|(_, element)| element)
+    }
+
+    /// Remove the first element of the list, returning it if the list wasn't empty.
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.list.pop_front().map(|(_, element)| element)
+    }
+
+    /// Replace the element at `index` with `element`, keeping its label and returning the old
+    /// element, or returning `element` back if the index is not in the list.
+    pub fn set(&mut self, index: usize, element: T) -> Result<T, T> {
+        match self.get_mut(index) {
+            Some(old) => Ok(std::mem::replace(old, element)),
+            None => Err(element),
+        }
+    }
+}
+
+impl<T, const B: usize> Clone for LabelledList<T, B>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list.clone(),
+            next_id: self.next_id,
+        }
+    }
+}
+
+impl<T, const B: usize> Default for LabelledList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const B: usize> fmt::Debug for LabelledList<T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const B: usize> PartialEq for LabelledList<T, B>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, const B: usize> Eq for LabelledList<T, B> where T: Eq {}
+
+impl<T, const B: usize> Extend<T> for LabelledList<T, B> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            self.push_back(element);
+        }
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for LabelledList<T, B> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a LabelledList<T, B> {
+    type Item = &'a T;
+
+    type IntoIter = LabelledIter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over items in a [`LabelledList`].
+#[derive(Debug, Clone)]
+pub struct LabelledIter<'a, T, const B: usize> {
+    inner: Iter<'a, (Label, T), B>,
+}
+
+impl<'a, T, const B: usize> Iterator for LabelledIter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, element)| element)
+    }
+}
+
+impl<'a, T, const B: usize> DoubleEndedIterator for LabelledIter<'a, T, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, element)| element)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::TestRng;
+
+    fn assert_labels<T, const B: usize>(list: &LabelledList<T, B>) {
+        for i in 1..list.len() {
+            assert!(list.label(i - 1) < list.label(i));
+            assert_ne!(list.label(i).unwrap().digits.last(), Some(&0));
+        }
+    }
+
+    #[test]
+    fn labels() {
+        let mut t = LabelledList::<usize, 3>::new();
+        let mut v = Vec::new();
+        let mut rng = TestRng::new(11);
+        for i in 0..2000 {
+            let r = rng.next();
+            match (v.len(), r % 8) {
+                (_, 0) => {
+                    t.push_front(i);
+                    v.insert(0, i);
+                }
+                (_, 1) => {
+                    t.push_back(i);
+                    v.push(i);
+                }
+                // keep inserting into the same gap to grow the labels
+                (len, 2) if len > 2 => {
+                    t.insert(len / 2, i).unwrap();
+                    v.insert(len / 2, i);
+                }
+                (0, _) | (_, 2..=5) => {
+                    let index = r % (v.len() + 1);
+                    t.insert(index, i).unwrap();
+                    v.insert(index, i);
+                }
+                (len, _) => {
+                    let index = r % len;
+                    let label = t.label(index).unwrap().clone();
+                    assert_eq!(t.remove(index), Some(v.remove(index)));
+                    assert_eq!(t.index_of(&label), None);
+                }
+            }
+            assert_labels(&t);
+            let index = r % v.len();
+            let label = t.label(index).unwrap().clone();
+            assert_eq!(t.index_of(&label), Some(index));
+            assert_eq!(t.get_by_label(&label), Some(&v[index]));
+        }
+        assert!(t.iter().eq(v.iter()));
+        assert_eq!(t.insert(v.len() + 1, 0), Err(0));
+    }
+
+    #[test]
+    fn label_length() {
+        // appending and prepending many elements keeps the labels short
+        let mut t = (0..10_000).collect::<LabelledList<usize>>();
+        for i in 0..10_000 {
+            t.push_front(i);
+        }
+        assert_labels(&t);
+        assert!(t.list.iter().all(|(label, _)| label.digits.len() <= 2));
+
+        // each digit has room for 32 insertions into the same gap
+        let mut t = (0..2).collect::<LabelledList<usize>>();
+        for i in 0..320 {
+            t.insert(1, i).unwrap();
+        }
+        assert_labels(&t);
+        assert!(t.list.iter().all(|(label, _)| label.digits.len() <= 12));
+    }
+
+    #[test]
+    fn handles() {
+        let mut t = LabelledList::<usize, 2>::new();
+        let mut v = Vec::new();
+        let mut handles = Vec::new();
+        let mut rng = TestRng::new(5);
+        for i in 0..1000 {
+            let r = rng.next();
+            match (v.len(), r % 4) {
+                (0, _) | (_, 0..=1) => {
+                    let index = r % (v.len() + 1);
+                    handles.push((t.insert(index, i).unwrap(), i));
+                    v.insert(index, i);
+                }
+                (len, 2) => {
+                    // remove and put back an element in the same place
+                    let index = r % len;
+                    assert_eq!(t.remove(index), Some(v[index]));
+                    handles.push((t.insert(index, i).unwrap(), i));
+                    v[index] = i;
+                }
+                (_, _) => {
+                    let (label, element) = &handles[r % handles.len()];
+                    let removed = t.remove_by_label(label);
+                    let position = v.iter().position(|e| e == element);
+                    assert_eq!(removed, position.map(|p| v.remove(p)));
+                }
+            }
+        }
+        assert!(t.iter().eq(v.iter()));
+        for (label, element) in &handles {
+            assert_eq!(t.index_of(label), v.iter().position(|e| e == element));
+        }
+        let (label, element) = handles.last().unwrap();
+        if let Some(e) = t.get_mut_by_label(label) {
+            *e += 1;
+            assert_eq!(t.get_by_label(label), Some(&(element + 1)));
+        }
+    }
+}
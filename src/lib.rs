@@ -1,4 +1,4 @@
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 #![warn(
     missing_debug_implementations,
     missing_docs, // TODO: add documentation!
@@ -12,7 +12,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unused,
     unused_allocation,
@@ -26,13 +25,93 @@
 //! A BTreeList offers `O(log(n))` indexing, `O(log(n))` insertion (anywhere in the list) and
 //! `O(log(n))` removal (also anywhere in the list).
 //!
-//! See [`BTreeList`] for more details.
+//! See [`BTreeList`] for more details, [`PersistentBTreeList`] for a variant sharing its nodes
+//! between clones, [`AugmentedList`] for one caching a user defined [`Measure`] of its elements,
+//! and [`BTreeString`] for a rope of text built on it.
+//!
+//! # Features
+//!
+//! No features are enabled by default. Each of these can be enabled on its own:
+//!
+//! - `rayon`: parallel operations over the list. This needs a newer compiler than the rest of the
+//!   crate.
+//! - `serde`: `Serialize` and `Deserialize` for the list, as a plain sequence of elements.
+//! - `rkyv`: zero-copy archiving with [rkyv](https://docs.rs/rkyv), with the list archived as an
+//!   `ArchivedVec` of its elements. This needs a newer compiler than the rest of the crate.
+//! - `arbitrary`: an [`Arbitrary`](https://docs.rs/arbitrary) implementation for fuzzing, building
+//!   trees of varied shapes. This needs a newer compiler than the rest of the crate.
+//! - `quickcheck`: a [quickcheck](https://docs.rs/quickcheck) `Arbitrary` implementation, shrinking
+//!   towards shorter lists and simpler elements. This needs a newer compiler than the rest of the
+//!   crate.
+//! - `unsafe`: skip bounds checks on the elements and children of nodes when looking up or
+//!   inserting at an index, and move elements between nodes without bounds checks when splitting
+//!   them. Without it the crate has no unsafe code.
+//! - `u32-lengths`: store the length of each subtree as a `u32` rather than a `usize`, making nodes
+//!   smaller. Lists must then hold fewer than 2^32 elements, and building a longer one panics.
+//! - `merkle`: cache a hash of each subtree in the nodes of a [`PersistentBTreeList`], for
+//!   comparing and diffing lists by `content_hash` in `O(log(n))` time.
+//! - `proptest`: the `proptest` module of strategies for generating lists and the indices to edit
+//!   them with. This needs a newer compiler than the rest of the crate.
+//! - `debug-tree`: `BTreeList::dump_tree` and `BTreeList::to_dot` to render the nodes of a list as
+//!   text or a Graphviz graph, for debugging.
+//! - `validate`: `BTreeList::validate` to check the invariants of a list, returning a
+//!   `ValidationError` rather than panicking, for tests and fuzzers.
+//! - `strict-checks`: check the lengths stored through the tree on every insert and remove,
+//!   panicking if they are wrong. This makes each edit take `O(n)` time, so is only meant for
+//!   debugging the crate itself.
 
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod augmented;
 mod btreelist;
+mod cursor;
+mod delta;
+mod io;
 mod iter;
+mod labelled;
 mod r#macro;
+#[cfg(feature = "merkle")]
+mod merkle;
+mod node;
+mod observe;
 mod owned_iter;
+#[cfg(feature = "rayon")]
+mod par;
+mod persistent;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
+#[cfg(feature = "rkyv")]
+mod rkyv;
+mod rle;
+#[cfg(feature = "serde")]
+mod serde;
+mod slice;
+mod sort;
+mod sorted;
+mod string;
 
-pub use crate::btreelist::BTreeList;
-pub use crate::iter::Iter;
+pub use crate::augmented::{AugmentedIter, AugmentedList, Measure, Sum};
+#[cfg(feature = "validate")]
+pub use crate::btreelist::ValidationError;
+pub use crate::btreelist::{
+    estimate_b_for, BTreeList, LeafChunks, NodeChunks, NodeEstimate, TreeStats,
+};
+pub use crate::cursor::{Cursor, CursorMut};
+pub use crate::delta::{DeltaOp, ListDelta, PatchError};
+pub use crate::io::ReadSeekCursor;
+pub use crate::iter::{Iter, Split};
+pub use crate::labelled::{Label, LabelledIter, LabelledList};
+pub use crate::observe::{
+    Anchor, Anchors, AnnotationId, Annotations, Bias, DirtyRanges, ListEvent, ListObserver,
+    ObservedList, WatchRange,
+};
 pub use crate::owned_iter::OwnedIter;
+#[cfg(feature = "rayon")]
+pub use crate::par::{IntoParIter, ParIter, ParIterMut};
+pub use crate::persistent::{PersistentBTreeList, PersistentIter, Snapshot, SnapshotIter};
+pub use crate::rle::{RleIter, RleList, Runs};
+pub use crate::slice::{BTreeSlice, BTreeSliceMut, RangeDescriptor, SliceIterMut};
+pub use crate::sorted::SortedList;
+pub use crate::string::{BTreeString, Chars, StrChunks};
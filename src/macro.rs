@@ -1,21 +1,47 @@
 /// Create a [`BTreeList`](crate::BTreeList).
 ///
+/// A custom `B` parameter can be given before the elements.
+///
 /// ```
 /// # use btreelist::btreelist;
+/// # use btreelist::BTreeList;
 /// # fn main() {
 /// btreelist![1];
 /// btreelist![1, 2, 3];
 /// btreelist![1; 3];
+///
+/// let list: BTreeList<_, 16> = btreelist![B = 16; 1, 2, 3];
+/// let list: BTreeList<_, 16> = btreelist![B = 16; 1; 3];
+/// let list: BTreeList<i32, 16> = btreelist![B = 16];
 /// # }
 /// ```
 #[macro_export]
 macro_rules! btreelist {
+    (B = $b:expr $(;)?) => {
+        $crate::BTreeList::<_, { $b }>::new()
+    };
+    (B = $b:expr; $elem:expr; $n:expr) => {
+        {
+            let mut t = $crate::BTreeList::<_, { $b }>::new();
+            for _ in 0..$n {
+                t.push($elem)
+            }
+            t
+        }
+    };
+    (B = $b:expr; $($x:expr),+ $(,)?) => {
+        {
+            let mut t = $crate::BTreeList::<_, { $b }>::new();
+            $(t.push($x);)+
+            t
+        }
+    };
     () => {
-        $crate::BTreeList::default()
+        $crate::BTreeList::<_>::default()
     };
     ($elem:expr; $n:expr) => {
         {
-            let mut t = $crate::BTreeList::default();
+            let mut t = $crate::BTreeList::<_>::default();
             for _ in 0..$n {
                 t.push($elem)
             }
@@ -24,7 +50,7 @@ macro_rules! btreelist {
     };
     ($($x:expr),+ $(,)?) => {
         {
-            let mut t = $crate::BTreeList::default();
+            let mut t = $crate::BTreeList::<_>::default();
             $(t.push($x);)+
             t
         }
@@ -42,4 +68,14 @@ mod tests {
         btreelist![1, 2, 3];
         btreelist![1; 3];
     }
+
+    #[test]
+    fn mc_custom_b() {
+        let _: BTreeList<(), 3> = btreelist![B = 3];
+        let list: BTreeList<_, 3> = btreelist![B = 3; 1, 2, 3,];
+        assert_eq!(list, [1, 2, 3][..]);
+        const B: usize = 4;
+        let list: BTreeList<_, B> = btreelist![B = B; 7; 20];
+        assert_eq!(list, [7; 20][..]);
+    }
 }
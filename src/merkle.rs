@@ -0,0 +1,108 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The modulus of the polynomial hashes, the Mersenne prime `2^61 - 1`.
+const MODULUS: u64 = (1 << 61) - 1;
+
+/// The point the polynomial hashes are evaluated at.
+const BASE: u64 = 0x1d8e_4e27_c47d_124f % MODULUS;
+
+/// The hash of a sequence of elements, as a polynomial in [`BASE`] with a coefficient per element.
+///
+/// The hash of two sequences joined together is found from their hashes and the length of the
+/// second, so a node's hash can be built from its children's whatever the shape of the tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// The hash of the empty sequence.
+    pub(crate) const EMPTY: Self = Self(0);
+
+    /// The hash of `self` followed by the single `element`.
+    pub(crate) fn push<T: Hash>(self, element: &T) -> Self {
+        let mut hasher = DefaultHasher::new();
+        element.hash(&mut hasher);
+        Self(add(mul(self.0, BASE), hasher.finish() % MODULUS))
+    }
+
+    /// The hash of `self` followed by a sequence of `len` elements hashing to `other`.
+    pub(crate) fn append(self, other: Self, len: usize) -> Self {
+        Self(add(mul(self.0, pow(BASE, len)), other.0))
+    }
+
+    pub(crate) fn value(self) -> u64 {
+        self.0
+    }
+}
+
+fn add(a: u64, b: u64) -> u64 {
+    (a + b) % MODULUS
+}
+
+fn mul(a: u64, b: u64) -> u64 {
+    ((u128::from(a) * u128::from(b)) % u128::from(MODULUS)) as u64
+}
+
+fn pow(mut base: u64, mut exp: usize) -> u64 {
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// A node's [`Fingerprint`] once it has been worked out, cleared whenever the node is edited.
+///
+/// This is atomic so that nodes shared between threads can fill it in.
+pub(crate) struct HashCache(AtomicU64);
+
+impl HashCache {
+    /// Fingerprints are below the modulus so this can't be one.
+    const UNKNOWN: u64 = u64::MAX;
+
+    pub(crate) fn new() -> Self {
+        Self(AtomicU64::new(Self::UNKNOWN))
+    }
+
+    /// Get the cached fingerprint, working it out with `compute` if it isn't known.
+    pub(crate) fn get_or_compute<F>(&self, compute: F) -> Fingerprint
+    where
+        F: FnOnce() -> Fingerprint,
+    {
+        match self.0.load(Ordering::Relaxed) {
+            Self::UNKNOWN => {
+                let fingerprint = compute();
+                self.0.store(fingerprint.0, Ordering::Relaxed);
+                fingerprint
+            }
+            hash => Fingerprint(hash),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self.0.get_mut() = Self::UNKNOWN;
+    }
+}
+
+impl Clone for HashCache {
+    fn clone(&self) -> Self {
+        Self(AtomicU64::new(self.0.load(Ordering::Relaxed)))
+    }
+}
+
+impl fmt::Debug for HashCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.load(Ordering::Relaxed) {
+            Self::UNKNOWN => f.write_str("HashCache(unknown)"),
+            hash => write!(f, "HashCache({:#x})", hash),
+        }
+    }
+}
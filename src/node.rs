@@ -0,0 +1,371 @@
+use std::{cmp::Ordering, mem};
+
+/// A node of the B-trees behind [`AugmentedList`](crate::AugmentedList) and
+/// [`PersistentBTreeList`](crate::PersistentBTreeList), which keep the shape of a
+/// [`BTreeList`](crate::BTreeList)'s tree but hold their children differently and cache more
+/// about each subtree.
+///
+/// This gives the parts of the node to the lookups shared by both trees.
+pub(crate) trait TreeNode<T, const B: usize>: Sized {
+    /// How the node holds each of its children.
+    type Child;
+
+    /// The elements of this node, between its children.
+    fn elements(&self) -> &[T];
+
+    /// The children of this node, one more than the elements unless it is a leaf.
+    fn children(&self) -> &[Self::Child];
+
+    /// Get the node held by `child`.
+    fn child(child: &Self::Child) -> &Self;
+
+    /// The number of elements in this subtree.
+    fn len(&self) -> usize;
+
+    fn is_leaf(&self) -> bool {
+        self.children().is_empty()
+    }
+
+    /// The number of levels below this node.
+    fn height(&self) -> usize {
+        let mut height = 0;
+        let mut node = self;
+        while let Some(child) = node.children().first() {
+            height += 1;
+            node = Self::child(child);
+        }
+        height
+    }
+
+    /// Find the child that `index` falls in, or just after, returning the child's index and the
+    /// index within it. An index equal to the child's length is the element after it.
+    fn find_child(&self, mut index: usize) -> (usize, usize) {
+        let children = self.children();
+        let mut child_index = 0;
+        while index > Self::child(&children[child_index]).len() {
+            index -= Self::child(&children[child_index]).len() + 1;
+            child_index += 1;
+        }
+        (child_index, index)
+    }
+
+    fn get(&self, index: usize) -> Option<&T> {
+        if self.is_leaf() {
+            return self.elements().get(index);
+        }
+        if index >= self.len() {
+            return None;
+        }
+        let (child_index, index) = self.find_child(index);
+        let child = Self::child(&self.children()[child_index]);
+        if index < child.len() {
+            child.get(index)
+        } else {
+            self.elements().get(child_index)
+        }
+    }
+}
+
+/// The edits shared by the trees of [`TreeNode`]s, rebalancing nodes as they fill up and empty.
+///
+/// Nodes work out what they cache about their subtree again in [`refresh`](Self::refresh), which
+/// is called on each node whose parts are changed here.
+pub(crate) trait TreeNodeMut<T, const B: usize>: TreeNode<T, B> {
+    /// Build a node from its elements and children.
+    fn from_parts(elements: Vec<T>, children: Vec<Self::Child>) -> Self;
+
+    /// Take the node apart into its elements and children.
+    fn into_parts(self) -> (Vec<T>, Vec<Self::Child>);
+
+    /// Get the elements and children of this node to change them.
+    fn parts_mut(&mut self) -> (&mut Vec<T>, &mut Vec<Self::Child>);
+
+    /// Get the node held by `child` to edit.
+    fn edit(child: &mut Self::Child) -> &mut Self;
+
+    /// Take the node held by `child`.
+    fn unwrap(child: Self::Child) -> Self;
+
+    /// Hold `node` as a child.
+    fn wrap(node: Self) -> Self::Child;
+
+    /// Work out the length and anything else cached about this subtree again from its parts.
+    fn refresh(&mut self);
+
+    /// Split this node if it has gone over the maximum size, returning the median and the new
+    /// successor sibling.
+    fn split_overfull(&mut self) -> Option<(T, Self)> {
+        if self.elements().len() < 2 * B {
+            return None;
+        }
+        let (elements, children) = self.parts_mut();
+        let sibling_elements = elements.split_off(B + 1);
+        let sibling_children = if children.is_empty() {
+            Vec::new()
+        } else {
+            children.split_off(B + 1)
+        };
+        let median = elements.pop().expect("overfull nodes have a median");
+        self.refresh();
+        Some((median, Self::from_parts(sibling_elements, sibling_children)))
+    }
+
+    /// Bring child `child_index` back up to the minimum size, by merging with or borrowing from a
+    /// sibling.
+    fn fix_underfull_child(&mut self, child_index: usize) {
+        let (elements, children) = self.parts_mut();
+        if Self::child(&children[child_index]).elements().len() >= B - 1 {
+            return;
+        }
+        let left_index = child_index.saturating_sub(1);
+        if Self::child(&children[left_index]).elements().len()
+            + Self::child(&children[left_index + 1]).elements().len()
+            < 2 * B - 1
+        {
+            let separator = elements.remove(left_index);
+            let (right_elements, right_children) =
+                Self::unwrap(children.remove(left_index + 1)).into_parts();
+            let left = Self::edit(&mut children[left_index]);
+            let (left_elements, left_children) = left.parts_mut();
+            left_elements.push(separator);
+            left_elements.extend(right_elements);
+            left_children.extend(right_children);
+            left.refresh();
+        } else if left_index == child_index {
+            // borrow the first elements of the right sibling through the separator, more than one
+            // when the child is the small root of a joined tree
+            while Self::child(&children[child_index]).elements().len() < B - 1 {
+                let right = Self::edit(&mut children[child_index + 1]);
+                let (right_elements, right_children) = right.parts_mut();
+                let first = right_elements.remove(0);
+                let first_child = (!right_children.is_empty()).then(|| right_children.remove(0));
+                right.refresh();
+                let separator = mem::replace(&mut elements[child_index], first);
+                let child = Self::edit(&mut children[child_index]);
+                let (child_elements, child_children) = child.parts_mut();
+                child_elements.push(separator);
+                child_children.extend(first_child);
+                child.refresh();
+            }
+        } else {
+            // borrow the last elements of the left sibling through the separator
+            while Self::child(&children[child_index]).elements().len() < B - 1 {
+                let left = Self::edit(&mut children[left_index]);
+                let (left_elements, left_children) = left.parts_mut();
+                let last = left_elements
+                    .pop()
+                    .expect("siblings to borrow from aren't empty");
+                let last_child = left_children.pop();
+                left.refresh();
+                let separator = mem::replace(&mut elements[left_index], last);
+                let child = Self::edit(&mut children[child_index]);
+                let (child_elements, child_children) = child.parts_mut();
+                child_elements.insert(0, separator);
+                if let Some(last_child) = last_child {
+                    child_children.insert(0, last_child);
+                }
+                child.refresh();
+            }
+        }
+    }
+
+    /// Make a tree from a node that may have been left without elements, or with only a single
+    /// child in their place.
+    fn collapse(mut node: Self) -> Option<Self> {
+        while node.elements().is_empty() {
+            let (_, mut children) = node.into_parts();
+            node = Self::unwrap(children.pop()?);
+        }
+        Some(node)
+    }
+
+    /// Make this root node the first child of a new root, with `median` and `sibling` after it.
+    fn grow(self, median: T, sibling: Self) -> Self {
+        Self::from_parts(vec![median], vec![Self::wrap(self), Self::wrap(sibling)])
+    }
+
+    /// Push `element` onto the right edge of this subtree, returning the median and new sibling
+    /// if this node had to be split.
+    fn push_back(&mut self, element: T) -> Option<(T, Self)> {
+        let (elements, children) = self.parts_mut();
+        match children.last_mut() {
+            Some(last) => {
+                if let Some((median, sibling)) = Self::edit(last).push_back(element) {
+                    elements.push(median);
+                    children.push(Self::wrap(sibling));
+                }
+            }
+            None => elements.push(element),
+        }
+        let split = self.split_overfull();
+        self.refresh();
+        split
+    }
+
+    /// Push `element` onto the left edge of this subtree, returning the median and new sibling
+    /// if this node had to be split.
+    fn push_front(&mut self, element: T) -> Option<(T, Self)> {
+        let (elements, children) = self.parts_mut();
+        match children.first_mut() {
+            Some(first) => {
+                if let Some((median, sibling)) = Self::edit(first).push_front(element) {
+                    elements.insert(0, median);
+                    children.insert(1, Self::wrap(sibling));
+                }
+            }
+            None => elements.insert(0, element),
+        }
+        let split = self.split_overfull();
+        self.refresh();
+        split
+    }
+
+    /// Join the trees `left` and `right` with `separator` between them, refreshing the nodes
+    /// along the edge they meet at.
+    fn join(left: Option<Self>, separator: T, right: Option<Self>) -> Self {
+        let (root, overflow) = match (left, right) {
+            (Some(left), Some(right)) => {
+                let (left_height, right_height) = (left.height(), right.height());
+                match left_height.cmp(&right_height) {
+                    Ordering::Equal => {
+                        let mut root = Self::from_parts(
+                            vec![separator],
+                            vec![Self::wrap(left), Self::wrap(right)],
+                        );
+                        root.fix_underfull_child(0);
+                        if root.children().len() > 1 {
+                            root.fix_underfull_child(1);
+                        }
+                        root.refresh();
+                        return Self::collapse(root).expect("joined trees aren't empty");
+                    }
+                    Ordering::Greater => {
+                        let mut root = left;
+                        let overflow = root.join_right(left_height, separator, right, right_height);
+                        (root, overflow)
+                    }
+                    Ordering::Less => {
+                        let mut root = right;
+                        let overflow = root.join_left(right_height, left, separator, left_height);
+                        (root, overflow)
+                    }
+                }
+            }
+            (Some(mut left), None) => {
+                let overflow = left.push_back(separator);
+                (left, overflow)
+            }
+            (None, Some(mut right)) => {
+                let overflow = right.push_front(separator);
+                (right, overflow)
+            }
+            (None, None) => return Self::from_parts(vec![separator], Vec::new()),
+        };
+        match overflow {
+            Some((median, sibling)) => root.grow(median, sibling),
+            None => root,
+        }
+    }
+
+    /// Join the shorter tree `right` onto the right edge of this subtree of `height`, returning
+    /// the median and new sibling if this node had to be split.
+    fn join_right(
+        &mut self,
+        height: usize,
+        separator: T,
+        right: Self,
+        right_height: usize,
+    ) -> Option<(T, Self)> {
+        let (elements, children) = self.parts_mut();
+        if height == right_height + 1 {
+            elements.push(separator);
+            children.push(Self::wrap(right));
+            self.fix_underfull_child(self.children().len() - 1);
+        } else {
+            let last = children.len() - 1;
+            if let Some((median, sibling)) = Self::edit(&mut children[last]).join_right(
+                height - 1,
+                separator,
+                right,
+                right_height,
+            ) {
+                elements.push(median);
+                children.push(Self::wrap(sibling));
+            }
+        }
+        let split = self.split_overfull();
+        self.refresh();
+        split
+    }
+
+    /// Join the shorter tree `left` onto the left edge of this subtree of `height`, returning the
+    /// median and new sibling if this node had to be split.
+    fn join_left(
+        &mut self,
+        height: usize,
+        left: Self,
+        separator: T,
+        left_height: usize,
+    ) -> Option<(T, Self)> {
+        let (elements, children) = self.parts_mut();
+        if height == left_height + 1 {
+            elements.insert(0, separator);
+            children.insert(0, Self::wrap(left));
+            self.fix_underfull_child(0);
+        } else if let Some((median, sibling)) =
+            Self::edit(&mut children[0]).join_left(height - 1, left, separator, left_height)
+        {
+            elements.insert(0, median);
+            children.insert(1, Self::wrap(sibling));
+        }
+        let split = self.split_overfull();
+        self.refresh();
+        split
+    }
+
+    /// Split this tree into the elements before `at` and those from `at` onwards, cutting along
+    /// the path to `at` and joining the pieces either side of it back up.
+    fn split_at(self, at: usize) -> (Option<Self>, Option<Self>) {
+        let (mut elements, mut children) = self.into_parts();
+        if children.is_empty() {
+            let right = elements.split_off(at);
+            return (
+                Self::collapse(Self::from_parts(elements, Vec::new())),
+                Self::collapse(Self::from_parts(right, Vec::new())),
+            );
+        }
+
+        // find the child that `at` falls in, or just after
+        let mut child_index = 0;
+        let mut child_start = 0;
+        while at > child_start + Self::child(&children[child_index]).len() {
+            child_start += Self::child(&children[child_index]).len() + 1;
+            child_index += 1;
+        }
+
+        let suffix_children = children.split_off(child_index + 1);
+        let mut suffix_elements = elements.split_off(child_index);
+        let child = Self::unwrap(children.pop().expect("the child `at` falls in"));
+        let (child_left, child_right) = child.split_at(at - child_start);
+
+        let left = match elements.pop() {
+            Some(separator) => Some(Self::join(
+                Self::collapse(Self::from_parts(elements, children)),
+                separator,
+                child_left,
+            )),
+            None => child_left,
+        };
+        let right = if suffix_elements.is_empty() {
+            child_right
+        } else {
+            let separator = suffix_elements.remove(0);
+            Some(Self::join(
+                child_right,
+                separator,
+                Self::collapse(Self::from_parts(suffix_elements, suffix_children)),
+            ))
+        };
+        (left, right)
+    }
+}
@@ -0,0 +1,1043 @@
+use std::{
+    fmt, iter, mem,
+    ops::{Deref, Range, RangeBounds},
+};
+
+use crate::BTreeList;
+
+/// A change made to an [`ObservedList`], reported to its observer as it happens.
+///
+/// Indices refer to the list as it is just after the change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListEvent {
+    /// `len` elements were inserted starting at `index`.
+    Inserted {
+        /// The index of the first inserted element.
+        index: usize,
+        /// The number of elements inserted.
+        len: usize,
+    },
+    /// `len` elements were removed from `index` onwards.
+    Removed {
+        /// The index the first removed element was at.
+        index: usize,
+        /// The number of elements removed.
+        len: usize,
+    },
+    /// The element at `index` was replaced or handed out to be changed.
+    Updated {
+        /// The index of the element.
+        index: usize,
+    },
+}
+
+/// Something notified of the changes made to an [`ObservedList`].
+///
+/// This is implemented for closures taking a [`ListEvent`].
+pub trait ListObserver {
+    /// Handle a change that was just made to the list.
+    fn on_event(&mut self, event: ListEvent);
+}
+
+impl<F> ListObserver for F
+where
+    F: FnMut(ListEvent),
+{
+    fn on_event(&mut self, event: ListEvent) {
+        self(event)
+    }
+}
+
+/// Passes each change on to both observers, so that several can watch the same list.
+impl<A, B> ListObserver for (A, B)
+where
+    A: ListObserver,
+    B: ListObserver,
+{
+    fn on_event(&mut self, event: ListEvent) {
+        self.0.on_event(event);
+        self.1.on_event(event);
+    }
+}
+
+/// A [`ListObserver`] gathering the ranges of the list changed since they were last taken.
+///
+/// The ranges are kept in terms of the list as it is now, moving along as elements are inserted
+/// and removed before them, and ranges that overlap or touch are coalesced. Inserted and updated
+/// elements are marked as dirty, and removals leave an empty range where the elements were unless
+/// it touches another range. Each change takes `O(m)` time for `m` ranges.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// # use btreelist::{DirtyRanges, ObservedList};
+/// let mut list = ObservedList::new((0..10).collect::<BTreeList<_>>(), DirtyRanges::new());
+/// list.set(5, 50).unwrap();
+/// list.insert(6, 60).unwrap();
+/// list.remove(0);
+/// assert_eq!(list.take_dirty(), [0..0, 4..6]);
+/// assert_eq!(list.take_dirty(), []);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirtyRanges {
+    ranges: Vec<Range<usize>>,
+}
+
+impl DirtyRanges {
+    /// Construct a new [`DirtyRanges`] with nothing marked.
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// The ranges changed since they were last taken, in order.
+    pub fn dirty(&self) -> &[Range<usize>] {
+        &self.ranges
+    }
+
+    /// Take the ranges changed since they were last taken, in order, leaving nothing marked.
+    pub fn take_dirty(&mut self) -> Vec<Range<usize>> {
+        mem::take(&mut self.ranges)
+    }
+
+    /// Mark `range` as dirty, coalescing it with the ranges it overlaps or touches.
+    fn mark(&mut self, range: Range<usize>) {
+        let start = self.ranges.partition_point(|r| r.end < range.start);
+        let end = self.ranges.partition_point(|r| r.start <= range.end);
+        let touching = &self.ranges[start..end];
+        let merged = match (touching.first(), touching.last()) {
+            (Some(first), Some(last)) => first.start.min(range.start)..last.end.max(range.end),
+            _ => range,
+        };
+        self.ranges.splice(start..end, iter::once(merged));
+    }
+}
+
+impl ListObserver for DirtyRanges {
+    fn on_event(&mut self, event: ListEvent) {
+        match event {
+            ListEvent::Inserted { index, len } => {
+                for range in &mut self.ranges {
+                    if range.start >= index {
+                        range.start += len;
+                        range.end += len;
+                    } else if range.end > index {
+                        range.end += len;
+                    }
+                }
+                self.mark(index..index + len);
+            }
+            ListEvent::Removed { index, len } => {
+                let shift = |i: usize| {
+                    if i > index {
+                        i.saturating_sub(len).max(index)
+                    } else {
+                        i
+                    }
+                };
+                let mut ranges = mem::take(&mut self.ranges);
+                for range in &mut ranges {
+                    *range = shift(range.start)..shift(range.end);
+                }
+                // ranges either side of the removal can now touch
+                for range in ranges {
+                    self.mark(range);
+                }
+                self.mark(index..index);
+            }
+            ListEvent::Updated { index } => self.mark(index..index + 1),
+        }
+    }
+}
+
+/// Which way an [`Anchor`] moves when elements are inserted right where it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Bias {
+    /// Stay after the element before the anchor, so insertions at the anchor go after it.
+    Left,
+    /// Stay before the element after the anchor, so insertions at the anchor go before it.
+    Right,
+}
+
+/// A position between elements of an [`ObservedList`], tracked by [`Anchors`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Anchor(usize);
+
+/// A [`ListObserver`] keeping [`Anchor`]s at the same place in the list as it is edited, such as
+/// for the cursors and selections of an editor.
+///
+/// An anchor sits between two elements, or at either end of the list, and moves along as
+/// elements are inserted and removed before it. Elements inserted right at the anchor go after
+/// it or before it depending on its [`Bias`], and if the elements around it are removed it ends
+/// up where they were. Each change takes `O(m)` time for `m` anchors.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::{Anchors, Bias, ObservedList};
+/// let mut list = ObservedList::new(btreelist!['a', 'b', 'c'], Anchors::new());
+/// let left = list.anchor(1, Bias::Left).unwrap();
+/// let right = list.anchor(1, Bias::Right).unwrap();
+///
+/// list.insert(1, 'x').unwrap();
+/// assert_eq!(list.resolve(left), Some(1));
+/// assert_eq!(list.resolve(right), Some(2));
+///
+/// list.remove(0);
+/// assert_eq!(list.resolve(left), Some(0));
+/// assert_eq!(list.resolve(right), Some(1));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Anchors {
+    /// The position and bias of each anchor, or `None` once it has been released.
+    anchors: Vec<Option<(usize, Bias)>>,
+}
+
+impl Anchors {
+    /// Construct a new [`Anchors`] with no anchors.
+    pub fn new() -> Self {
+        Self {
+            anchors: Vec::new(),
+        }
+    }
+
+    /// Place a new anchor at `position`, just before the element at that index.
+    ///
+    /// This doesn't know how long the list is, so prefer [`ObservedList::anchor`] which checks
+    /// the position is in the list.
+    pub fn create(&mut self, position: usize, bias: Bias) -> Anchor {
+        self.anchors.push(Some((position, bias)));
+        Anchor(self.anchors.len() - 1)
+    }
+
+    /// Get the current position of `anchor`, if it hasn't been released.
+    pub fn resolve(&self, anchor: Anchor) -> Option<usize> {
+        self.anchors
+            .get(anchor.0)
+            .copied()
+            .flatten()
+            .map(|(position, _)| position)
+    }
+
+    /// Stop tracking `anchor`, returning its position if it hadn't already been released.
+    pub fn release(&mut self, anchor: Anchor) -> Option<usize> {
+        self.anchors
+            .get_mut(anchor.0)
+            .and_then(Option::take)
+            .map(|(position, _)| position)
+    }
+}
+
+impl ListObserver for Anchors {
+    fn on_event(&mut self, event: ListEvent) {
+        for (position, bias) in self.anchors.iter_mut().flatten() {
+            match event {
+                ListEvent::Inserted { index, len } => {
+                    if *position > index || (*position == index && *bias == Bias::Right) {
+                        *position += len;
+                    }
+                }
+                ListEvent::Removed { index, len } => {
+                    if *position > index {
+                        *position = position.saturating_sub(len).max(index);
+                    }
+                }
+                ListEvent::Updated { .. } => {}
+            }
+        }
+    }
+}
+
+/// A range of an [`ObservedList`] with a value attached, tracked by [`Annotations`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnnotationId(usize);
+
+/// A [`ListObserver`] keeping values attached to ranges of the list, such as highlighting or
+/// comments, which stretch and shrink with the elements in them as the list is edited.
+///
+/// Each range is held between two [`Anchor`]s. By default elements inserted inside a range join
+/// it while those inserted at either end stay outside, and removing elements from a range
+/// shrinks it, down to being empty where they were. Queries look through every annotation,
+/// taking `O(m)` time for `m` annotations.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::{Annotations, ObservedList};
+/// let mut list = ObservedList::new(btreelist!['a', 'b', 'c', 'd'], Annotations::new());
+/// let bold = list.annotate(1..3, "bold").unwrap();
+///
+/// list.insert(2, 'x').unwrap();
+/// list.insert(0, 'y').unwrap();
+/// assert_eq!(list.observer().range(bold), Some(2..5));
+///
+/// list.remove(2);
+/// let covering = list.observer().covering(3).collect::<Vec<_>>();
+/// assert_eq!(covering, [(bold, 2..4, &"bold")]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Annotations<A> {
+    anchors: Anchors,
+    /// The anchors around each annotation and its value, or `None` once it has been removed.
+    annotations: Vec<Option<(Anchor, Anchor, A)>>,
+}
+
+impl<A> Annotations<A> {
+    /// Construct a new [`Annotations`] with no annotations.
+    pub fn new() -> Self {
+        Self {
+            anchors: Anchors::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Attach `value` to `range`, with elements inserted inside the range joining it and those
+    /// inserted at either end staying outside.
+    ///
+    /// This doesn't know how long the list is, so prefer [`ObservedList::annotate`] which checks
+    /// the range is in the list.
+    pub fn add(&mut self, range: Range<usize>, value: A) -> AnnotationId {
+        self.add_biased(range, Bias::Right, Bias::Left, value)
+    }
+
+    /// Attach `value` to `range`, with the [`Bias`]es of the anchors at either end deciding
+    /// whether elements inserted there join the range.
+    ///
+    /// An end with [`Bias::Left`] and a start with [`Bias::Right`] leave insertions there outside
+    /// the range, while the opposite biases take them in.
+    pub fn add_biased(
+        &mut self,
+        range: Range<usize>,
+        start: Bias,
+        end: Bias,
+        value: A,
+    ) -> AnnotationId {
+        let start = self.anchors.create(range.start, start);
+        let end = self.anchors.create(range.end.max(range.start), end);
+        self.annotations.push(Some((start, end, value)));
+        AnnotationId(self.annotations.len() - 1)
+    }
+
+    /// Get the current range of `annotation`, if it hasn't been removed.
+    pub fn range(&self, annotation: AnnotationId) -> Option<Range<usize>> {
+        let (start, end, _) = self.annotations.get(annotation.0)?.as_ref()?;
+        self.resolve(*start, *end)
+    }
+
+    /// Get the value attached by `annotation`, if it hasn't been removed.
+    pub fn get(&self, annotation: AnnotationId) -> Option<&A> {
+        let (_, _, value) = self.annotations.get(annotation.0)?.as_ref()?;
+        Some(value)
+    }
+
+    /// Remove `annotation`, returning its range and value if it hadn't already been removed.
+    pub fn remove(&mut self, annotation: AnnotationId) -> Option<(Range<usize>, A)> {
+        let (start, end, value) = self.annotations.get_mut(annotation.0)?.take()?;
+        let range = self.resolve(start, end)?;
+        self.anchors.release(start);
+        self.anchors.release(end);
+        Some((range, value))
+    }
+
+    /// Iterate over the annotations with the element at `index` in their range.
+    pub fn covering(
+        &self,
+        index: usize,
+    ) -> impl Iterator<Item = (AnnotationId, Range<usize>, &A)> + '_ {
+        self.overlapping(index..index + 1)
+    }
+
+    /// Iterate over the annotations whose ranges share at least one element with `range`.
+    pub fn overlapping(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (AnnotationId, Range<usize>, &A)> + '_ {
+        self.iter()
+            .filter(move |(_, r, _)| r.start < range.end && range.start < r.end)
+    }
+
+    /// Iterate over all of the annotations, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (AnnotationId, Range<usize>, &A)> + '_ {
+        self.annotations
+            .iter()
+            .enumerate()
+            .filter_map(move |(i, annotation)| {
+                let (start, end, value) = annotation.as_ref()?;
+                Some((AnnotationId(i), self.resolve(*start, *end)?, value))
+            })
+    }
+
+    fn resolve(&self, start: Anchor, end: Anchor) -> Option<Range<usize>> {
+        let start = self.anchors.resolve(start)?;
+        let end = self.anchors.resolve(end)?;
+        // an end biased to the left can be overtaken by a start biased to the right
+        Some(start..end.max(start))
+    }
+}
+
+impl<A> Default for Annotations<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> ListObserver for Annotations<A> {
+    fn on_event(&mut self, event: ListEvent) {
+        self.anchors.on_event(event);
+    }
+}
+
+/// A [`ListObserver`] passing on only the changes to a window of the list, with their indices
+/// counted from the start of the window, such as for a widget showing part of a large list.
+///
+/// The window moves along as elements are inserted and removed before it, without reporting
+/// anything. Elements inserted inside the window join it while those inserted at either end stay
+/// outside, as with [`Annotations::add`], and removals are cut down to the part inside the
+/// window, which shrinks to match. Each change takes `O(1)` time.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// # use btreelist::ListEvent;
+/// let mut events = Vec::new();
+/// let list = (0..100).collect::<BTreeList<_>>();
+/// let mut list = list.watch_range(40..50, |event| events.push(event)).unwrap();
+/// list.insert(0, 0).unwrap();
+/// list.set(45, 0).unwrap();
+/// list.remove(30);
+/// list.remove(40);
+/// assert_eq!(list.observer().range(), 40..49);
+/// drop(list);
+///
+/// assert_eq!(
+///     events,
+///     [
+///         ListEvent::Updated { index: 4 },
+///         ListEvent::Removed { index: 0, len: 1 },
+///     ]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct WatchRange<O> {
+    start: usize,
+    end: usize,
+    observer: O,
+}
+
+impl<O> WatchRange<O> {
+    /// Watch `range` of the list, passing its changes on to `observer`.
+    ///
+    /// This doesn't know how long the list is, so prefer [`BTreeList::watch_range`] which checks
+    /// the range is in the list.
+    pub fn new(range: Range<usize>, observer: O) -> Self {
+        Self {
+            start: range.start,
+            end: range.end.max(range.start),
+            observer,
+        }
+    }
+
+    /// The current range of the window in the list.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Get the observer the window's changes are passed on to.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Get the observer the window's changes are passed on to mutably.
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Unwrap the observer the window's changes are passed on to.
+    pub fn into_inner(self) -> O {
+        self.observer
+    }
+}
+
+impl<O> ListObserver for WatchRange<O>
+where
+    O: ListObserver,
+{
+    fn on_event(&mut self, event: ListEvent) {
+        match event {
+            ListEvent::Inserted { index, len } => {
+                if index <= self.start {
+                    self.start += len;
+                    self.end += len;
+                } else if index < self.end {
+                    self.end += len;
+                    self.observer.on_event(ListEvent::Inserted {
+                        index: index - self.start,
+                        len,
+                    });
+                }
+            }
+            ListEvent::Removed { index, len } => {
+                let overlap_start = index.max(self.start);
+                let overlap_end = (index + len).min(self.end);
+                if overlap_start < overlap_end {
+                    self.observer.on_event(ListEvent::Removed {
+                        index: overlap_start - self.start,
+                        len: overlap_end - overlap_start,
+                    });
+                }
+                let shift = |i: usize| {
+                    if i > index {
+                        i.saturating_sub(len).max(index)
+                    } else {
+                        i
+                    }
+                };
+                self.start = shift(self.start);
+                self.end = shift(self.end);
+            }
+            ListEvent::Updated { index } => {
+                if (self.start..self.end).contains(&index) {
+                    self.observer.on_event(ListEvent::Updated {
+                        index: index - self.start,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// A [`BTreeList`] that reports the changes made to it to an observer, so that views bound to
+/// the list can be updated incrementally.
+///
+/// The list can be read through [`Deref`], while edits go through the methods here which notify
+/// the observer after each change.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// # use btreelist::{ListEvent, ObservedList};
+/// let mut events = Vec::new();
+/// let mut list = ObservedList::new(btreelist![1, 2, 3], |event| events.push(event));
+/// list.insert(1, 7).unwrap();
+/// list.remove(3);
+/// assert_eq!(list.len(), 3);
+/// drop(list);
+///
+/// assert_eq!(
+///     events,
+///     [
+///         ListEvent::Inserted { index: 1, len: 1 },
+///         ListEvent::Removed { index: 3, len: 1 },
+///     ]
+/// );
+/// ```
+pub struct ObservedList<T, O, const B: usize = 6> {
+    list: BTreeList<T, B>,
+    observer: O,
+}
+
+impl<T, O, const B: usize> ObservedList<T, O, B>
+where
+    O: ListObserver,
+{
+    /// Wrap `list` so that changes to it are reported to `observer`.
+    pub fn new(list: BTreeList<T, B>, observer: O) -> Self {
+        Self { list, observer }
+    }
+
+    /// Get the observer.
+    pub fn observer(&self) -> &O {
+        &self.observer
+    }
+
+    /// Get the observer mutably, such as to take the changes it has gathered.
+    pub fn observer_mut(&mut self) -> &mut O {
+        &mut self.observer
+    }
+
+    /// Unwrap the list and its observer.
+    pub fn into_parts(self) -> (BTreeList<T, B>, O) {
+        (self.list, self.observer)
+    }
+
+    /// Insert the `element` into the list at `index`, as with [`BTreeList::insert`].
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        self.list.insert(index, element)?;
+        self.observer
+            .on_event(ListEvent::Inserted { index, len: 1 });
+        Ok(())
+    }
+
+    /// Push the `element` onto the back of the list.
+    pub fn push_back(&mut self, element: T) {
+        self.list.push_back(element);
+        let index = self.list.len() - 1;
+        self.observer
+            .on_event(ListEvent::Inserted { index, len: 1 });
+    }
+
+    /// Push the `element` onto the front of the list.
+    pub fn push_front(&mut self, element: T) {
+        self.list.push_front(element);
+        self.observer
+            .on_event(ListEvent::Inserted { index: 0, len: 1 });
+    }
+
+    /// Remove the element at `index`, as with [`BTreeList::remove`].
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let element = self.list.remove(index)?;
+        self.observer.on_event(ListEvent::Removed { index, len: 1 });
+        Some(element)
+    }
+
+    /// Remove the last element of the list.
+    pub fn pop_back(&mut self) -> Option<T> {
+        let element = self.list.pop_back()?;
+        let index = self.list.len();
+        self.observer.on_event(ListEvent::Removed { index, len: 1 });
+        Some(element)
+    }
+
+    /// Remove the first element of the list.
+    pub fn pop_front(&mut self) -> Option<T> {
+        let element = self.list.pop_front()?;
+        self.observer
+            .on_event(ListEvent::Removed { index: 0, len: 1 });
+        Some(element)
+    }
+
+    /// Replace the element at `index`, as with [`BTreeList::set`].
+    pub fn set(&mut self, index: usize, element: T) -> Result<T, T> {
+        let old = self.list.set(index, element)?;
+        self.observer.on_event(ListEvent::Updated { index });
+        Ok(old)
+    }
+
+    /// Get the element at `index` to change it, reporting it as updated straight away.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let element = self.list.get_mut(index)?;
+        self.observer.on_event(ListEvent::Updated { index });
+        Some(element)
+    }
+
+    /// Swap the elements at `a` and `b`, as with [`BTreeList::swap`].
+    pub fn swap(&mut self, a: usize, b: usize) -> bool {
+        if !self.list.swap(a, b) {
+            return false;
+        }
+        self.observer.on_event(ListEvent::Updated { index: a });
+        if a != b {
+            self.observer.on_event(ListEvent::Updated { index: b });
+        }
+        true
+    }
+
+    /// Move the elements of `other` onto the back of the list, as with [`BTreeList::append`].
+    pub fn append(&mut self, other: &mut BTreeList<T, B>) {
+        let index = self.list.len();
+        let len = other.len();
+        self.list.append(other);
+        if len > 0 {
+            self.observer.on_event(ListEvent::Inserted { index, len });
+        }
+    }
+
+    /// Split the list at `at`, as with [`BTreeList::split_off`].
+    pub fn split_off(&mut self, at: usize) -> Option<BTreeList<T, B>> {
+        let tail = self.list.split_off(at)?;
+        if !tail.is_empty() {
+            self.observer.on_event(ListEvent::Removed {
+                index: at,
+                len: tail.len(),
+            });
+        }
+        Some(tail)
+    }
+}
+
+impl<T, const B: usize> ObservedList<T, DirtyRanges, B> {
+    /// Take the ranges of the list changed since they were last taken, as with
+    /// [`DirtyRanges::take_dirty`].
+    pub fn take_dirty(&mut self) -> Vec<Range<usize>> {
+        self.observer.take_dirty()
+    }
+}
+
+impl<T, const B: usize> ObservedList<T, Anchors, B> {
+    /// Place a new anchor at `position`, just before the element at that index, returning `None`
+    /// if it is past the end of the list.
+    pub fn anchor(&mut self, position: usize, bias: Bias) -> Option<Anchor> {
+        if position > self.list.len() {
+            return None;
+        }
+        Some(self.observer.create(position, bias))
+    }
+
+    /// Get the current position of `anchor`, as with [`Anchors::resolve`].
+    pub fn resolve(&self, anchor: Anchor) -> Option<usize> {
+        self.observer.resolve(anchor)
+    }
+}
+
+impl<T, A, const B: usize> ObservedList<T, Annotations<A>, B> {
+    /// Attach `value` to `range` as with [`Annotations::add`], returning `None` if the range
+    /// isn't in the list.
+    pub fn annotate(&mut self, range: Range<usize>, value: A) -> Option<AnnotationId> {
+        if range.start > range.end || range.end > self.list.len() {
+            return None;
+        }
+        Some(self.observer.add(range, value))
+    }
+}
+
+impl<T, const B: usize> BTreeList<T, B> {
+    /// Wrap the list so that only the changes to the elements in `range` are reported to
+    /// `observer`, with their indices counted from the start of the range, returning `None` if it
+    /// is out of bounds.
+    ///
+    /// The range moves and stretches with the list as described for [`WatchRange`]. Several
+    /// ranges can be watched at once by passing [`WatchRange`]s in a tuple to
+    /// [`ObservedList::new`] instead.
+    pub fn watch_range<R, O>(
+        self,
+        range: R,
+        observer: O,
+    ) -> Option<ObservedList<T, WatchRange<O>, B>>
+    where
+        R: RangeBounds<usize>,
+        O: ListObserver,
+    {
+        let range = self.resolve_range(range)?;
+        Some(ObservedList::new(self, WatchRange::new(range, observer)))
+    }
+}
+
+impl<T, O, const B: usize> Deref for ObservedList<T, O, B> {
+    type Target = BTreeList<T, B>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.list
+    }
+}
+
+/// Reports the elements pushed onto the back as a single insertion.
+impl<T, O, const B: usize> Extend<T> for ObservedList<T, O, B>
+where
+    O: ListObserver,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let index = self.list.len();
+        self.list.extend(iter);
+        let len = self.list.len() - index;
+        if len > 0 {
+            self.observer.on_event(ListEvent::Inserted { index, len });
+        }
+    }
+}
+
+impl<T, O, const B: usize> fmt::Debug for ObservedList<T, O, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObservedList")
+            .field("list", &self.list)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::min;
+
+    use super::*;
+    use crate::btreelist::tests::{assert_shape, TestRng};
+
+    /// Records the events it is passed, so they can be checked while the list is still in use.
+    #[derive(Debug, Default)]
+    struct Recorder(Vec<ListEvent>);
+
+    impl ListObserver for Recorder {
+        fn on_event(&mut self, event: ListEvent) {
+            self.0.push(event);
+        }
+    }
+
+    #[test]
+    fn observed_list() {
+        use std::{cell::RefCell, rc::Rc};
+
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&events);
+        let mut t = ObservedList::new(
+            BTreeList::<usize, 2>::from_exact_iter(0..10, 10),
+            move |event| recorded.borrow_mut().push(event),
+        );
+        // a view of the list kept up to date from the events alone
+        let mut view = t.iter().copied().collect::<Vec<_>>();
+        let mut rng = TestRng::new(7);
+        for i in 0..500 {
+            let r = rng.next();
+            match r % 10 {
+                0 => t.push_back(i),
+                1 => t.push_front(i),
+                2 => {
+                    t.pop_back();
+                }
+                3 => {
+                    t.pop_front();
+                }
+                4 => {
+                    if !t.is_empty() {
+                        let _ = t.set(r % t.len(), i);
+                    }
+                }
+                5 => {
+                    if let Some(e) = t.get_mut(r % (t.len() + 1)) {
+                        *e = i;
+                    }
+                }
+                6 => {
+                    t.swap(r % (t.len() + 1), (r / 7) % (t.len() + 1));
+                }
+                7 => t.extend(i..i + r % 5),
+                8 => {
+                    let mut tail = t.split_off((r / 3) % (t.len() + 2)).unwrap_or_default();
+                    tail.push_back(i);
+                    t.append(&mut tail);
+                }
+                _ => {
+                    if r % 2 == 1 {
+                        let _ = t.remove(r % (t.len() + 1));
+                    } else {
+                        let _ = t.insert(r % (t.len() + 2), i);
+                    }
+                }
+            }
+            for event in events.borrow_mut().drain(..) {
+                match event {
+                    ListEvent::Inserted { index, len } => {
+                        let inserted = (index..index + len).map(|j| t[j]);
+                        view.splice(index..index, inserted);
+                    }
+                    ListEvent::Removed { index, len } => {
+                        view.drain(index..index + len);
+                    }
+                    ListEvent::Updated { index } => view[index] = t[index],
+                }
+            }
+            assert!(t.eq_iter(view.iter()));
+        }
+        let (list, _) = t.into_parts();
+        assert_shape(&list);
+    }
+
+    #[test]
+    fn dirty_ranges() {
+        let mut t = ObservedList::new(
+            BTreeList::<usize, 2>::from_exact_iter(0..50, 50),
+            DirtyRanges::new(),
+        );
+        // whether each element has changed since the ranges were last taken
+        let mut dirty = vec![false; 50];
+        let mut rng = TestRng::new(7);
+        for i in 0..2000 {
+            let r = rng.next();
+            let index = r % (t.len() + 1);
+            match r % 4 {
+                0 => {
+                    if index < t.len() {
+                        t.set(index, i).unwrap();
+                        dirty[index] = true;
+                    }
+                }
+                1 => {
+                    t.insert(index, i).unwrap();
+                    dirty.insert(index, true);
+                }
+                2 => {
+                    let mut tail = t.split_off(index).unwrap();
+                    let n = min(tail.len(), r % 4);
+                    let mut rest = tail.split_off(n).unwrap();
+                    t.append(&mut rest);
+                    // the rest is reported as removed and inserted again
+                    dirty.truncate(index);
+                    dirty.resize(t.len(), true);
+                }
+                _ => {
+                    if t.remove(index).is_some() {
+                        dirty.remove(index);
+                    }
+                }
+            }
+            if i % 20 == 19 {
+                let mut runs: Vec<Range<usize>> = Vec::new();
+                for (j, &d) in dirty.iter().enumerate() {
+                    match runs.last_mut() {
+                        Some(run) if d && run.end == j => run.end += 1,
+                        _ if d => runs.push(j..j + 1),
+                        _ => {}
+                    }
+                }
+                let taken = t.take_dirty();
+                assert!(taken.windows(2).all(|w| w[0].end < w[1].start));
+                assert!(taken.iter().all(|r| r.end <= t.len()));
+                assert_eq!(
+                    taken
+                        .into_iter()
+                        .filter(|r| !r.is_empty())
+                        .collect::<Vec<_>>(),
+                    runs
+                );
+                dirty.iter_mut().for_each(|d| *d = false);
+            }
+        }
+    }
+
+    #[test]
+    fn anchors() {
+        let mut t = ObservedList::new(
+            BTreeList::<usize, 2>::from_exact_iter(0..100, 100),
+            (DirtyRanges::new(), Anchors::new()),
+        );
+        // each anchor with the element it should stay next to
+        let mut anchored = Vec::new();
+        for p in (0..=100).step_by(7) {
+            if p > 0 {
+                let anchor = t.observer_mut().1.create(p, Bias::Left);
+                anchored.push((anchor, Bias::Left, t[p - 1]));
+            }
+            if p < 100 {
+                let anchor = t.observer_mut().1.create(p, Bias::Right);
+                anchored.push((anchor, Bias::Right, t[p]));
+            }
+        }
+        let mut rng = TestRng::new(7);
+        for i in 100..1000 {
+            let r = rng.next();
+            let index = r % (t.len() + 1);
+            if r % 3 == 1 {
+                t.remove(index);
+            } else {
+                t.insert(index, i).unwrap();
+            }
+            for &(anchor, bias, element) in &anchored {
+                let position = t.observer().1.resolve(anchor).unwrap();
+                assert!(position <= t.len());
+                if let Some(index) = t.index_of(&element) {
+                    match bias {
+                        Bias::Left => assert_eq!(position, index + 1),
+                        Bias::Right => assert_eq!(position, index),
+                    }
+                }
+            }
+        }
+        let (anchor, _, _) = anchored[0];
+        assert!(t.observer_mut().1.release(anchor).is_some());
+        assert_eq!(t.observer().1.resolve(anchor), None);
+        assert_eq!(t.observer_mut().1.release(anchor), None);
+    }
+
+    #[test]
+    fn annotations() {
+        let mut t = ObservedList::new(
+            BTreeList::<usize, 2>::from_exact_iter(0..100, 100),
+            Annotations::new(),
+        );
+        let ranges = [0..10, 5..50, 20..21, 30..30, 90..100, 0..100];
+        let ids = ranges
+            .iter()
+            .map(|range| t.annotate(range.clone(), range.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(t.annotate(50..101, 0..0), None);
+        let mut rng = TestRng::new(7);
+        for i in 100..1000 {
+            let r = rng.next();
+            let index = r % (t.len() + 1);
+            if r % 3 == 1 {
+                t.remove(index);
+            } else {
+                t.insert(index, i).unwrap();
+            }
+            for &id in &ids {
+                let original = t.observer().get(id).unwrap().clone();
+                let range = t.observer().range(id).unwrap();
+                assert!(range.end <= t.len());
+                // the original elements are still inside or outside the range as they were
+                for (j, e) in t.iter().enumerate() {
+                    if *e < 100 {
+                        assert_eq!(range.contains(&j), original.contains(e));
+                    }
+                }
+            }
+            let index = r % t.len();
+            let covering = t.observer().covering(index).map(|(id, _, _)| id);
+            assert!(covering.eq(ids.iter().copied().filter(|&id| t
+                .observer()
+                .range(id)
+                .unwrap()
+                .contains(&index))));
+        }
+        let removed = t.observer_mut().remove(ids[0]).unwrap();
+        assert_eq!(removed.1, 0..10);
+        assert_eq!(t.observer().range(ids[0]), None);
+        assert_eq!(t.observer().iter().count(), ids.len() - 1);
+    }
+
+    #[test]
+    fn watch_range() {
+        // check the events passed on against the window cut out of the list after each edit
+        let mut rng = TestRng::new(3);
+        let mut next = |n: usize| rng.next() % n;
+        let mut list = (0..200)
+            .collect::<BTreeList<usize, 3>>()
+            .watch_range(50..100, Recorder::default())
+            .unwrap();
+        let mut window = (50..100).collect::<Vec<_>>();
+        for value in 1000..3000 {
+            let index = next(list.len() + 1);
+            match next(3) {
+                0 => list.insert(index, value).unwrap(),
+                1 => {
+                    list.remove(index);
+                }
+                _ => {
+                    if index < list.len() {
+                        list.set(index, value).unwrap();
+                    }
+                }
+            }
+            for event in list.observer_mut().observer_mut().0.drain(..) {
+                match event {
+                    ListEvent::Inserted { index, len } => {
+                        assert_eq!(len, 1);
+                        window.insert(index, value);
+                    }
+                    ListEvent::Removed { index, len } => {
+                        assert_eq!(len, 1);
+                        window.remove(index);
+                    }
+                    ListEvent::Updated { index } => window[index] = value,
+                }
+            }
+            let range = list.observer().range();
+            assert!(list
+                .iter()
+                .skip(range.start)
+                .take(range.len())
+                .eq(window.iter()));
+        }
+    }
+
+    #[test]
+    fn watch_range_split_off() {
+        let mut list = (0..10)
+            .collect::<BTreeList<usize>>()
+            .watch_range(3..7, Recorder::default())
+            .unwrap();
+        list.extend(10..12);
+        list.split_off(5).unwrap();
+        assert_eq!(list.observer().range(), 3..5);
+        assert_eq!(
+            list.observer().observer().0,
+            [ListEvent::Removed { index: 2, len: 2 }]
+        );
+        assert!(BTreeList::<usize>::new()
+            .watch_range(0..1, Recorder::default())
+            .is_none());
+    }
+}
@@ -0,0 +1,356 @@
+use std::mem;
+
+use rayon::{
+    iter::{plumbing::UnindexedConsumer, FromParallelIterator, ParallelExtend},
+    prelude::*,
+};
+
+use crate::BTreeList;
+
+impl<T, const B: usize> BTreeList<T, B>
+where
+    T: Sync,
+{
+    /// Keep only the elements for which `f` returns true, evaluating `f` in parallel.
+    ///
+    /// The predicate is run over the node chunks of the list in parallel and the kept elements
+    /// are then built into a new tree in one pass, which pays off for expensive predicates over
+    /// large lists.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list = (0..100).collect::<BTreeList<_>>();
+    /// list.par_retain(|x| x % 3 == 0);
+    /// assert_eq!(list.len(), 34);
+    /// assert!(list.iter().copied().eq((0..100).step_by(3)));
+    /// ```
+    pub fn par_retain<F>(&mut self, f: F)
+    where
+        F: Fn(&T) -> bool + Sync,
+    {
+        let keep = self.par_flags(f);
+        let len = keep.iter().filter(|k| **k).count();
+        let list = mem::take(self);
+        let kept = list
+            .into_drain()
+            .zip(keep)
+            .filter_map(|(element, keep)| if keep { Some(element) } else { None });
+        *self = Self::from_exact_iter(kept, len);
+    }
+
+    /// Remove the elements for which `f` returns true, evaluating `f` in parallel, and return
+    /// them as a new list.
+    ///
+    /// As with [`par_retain`](Self::par_retain), both lists are built in one pass once the
+    /// predicate has been evaluated.
+    ///
+    /// ```
+    /// # use btreelist::BTreeList;
+    /// let mut list = (0..10).collect::<BTreeList<_>>();
+    /// let odd = list.par_extract_if(|x| x % 2 == 1);
+    /// assert!(list.iter().copied().eq([0, 2, 4, 6, 8]));
+    /// assert!(odd.iter().copied().eq([1, 3, 5, 7, 9]));
+    /// ```
+    pub fn par_extract_if<F>(&mut self, f: F) -> Self
+    where
+        F: Fn(&T) -> bool + Sync,
+    {
+        let extract = self.par_flags(f);
+        let len = extract.iter().filter(|e| **e).count();
+        let list = mem::take(self);
+        let kept_len = list.len() - len;
+        let mut extracted = Vec::with_capacity(len);
+        {
+            let mut kept = list
+                .into_drain()
+                .zip(extract)
+                .filter_map(|(element, extract)| {
+                    if extract {
+                        extracted.push(element);
+                        None
+                    } else {
+                        Some(element)
+                    }
+                });
+            *self = Self::from_exact_iter(kept.by_ref(), kept_len);
+            // only extracted elements remain, so this runs the rest of the way through them
+            assert!(kept.next().is_none());
+        }
+        Self::from_exact_iter(extracted.into_iter(), len)
+    }
+
+    /// Evaluate `f` on each element in parallel, a node chunk at a time, returning the results
+    /// in order.
+    fn par_flags<F>(&self, f: F) -> Vec<bool>
+    where
+        F: Fn(&T) -> bool + Sync,
+    {
+        self.node_chunks()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .flat_map_iter(|chunk| chunk.iter().map(&f))
+            .collect()
+    }
+}
+
+/// A parallel iterator over references to the elements of a [`BTreeList`].
+///
+/// The list is split into its node chunks up front and the work is divided between threads a
+/// chunk at a time, so neighbouring elements stay on the same thread.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// use rayon::prelude::*;
+///
+/// let list = (0..10_000_u64).collect::<BTreeList<_>>();
+/// assert_eq!(list.par_iter().map(|x| x * 2).sum::<u64>(), 99_990_000);
+/// ```
+#[derive(Debug)]
+pub struct ParIter<'a, T> {
+    chunks: Vec<&'a [T]>,
+}
+
+impl<'a, T> ParallelIterator for ParIter<'a, T>
+where
+    T: Sync,
+{
+    type Item = &'a T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.chunks
+            .into_par_iter()
+            .flat_map_iter(|chunk| chunk.iter())
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a, T, const B: usize> IntoParallelIterator for &'a BTreeList<T, B>
+where
+    T: Sync,
+{
+    type Iter = ParIter<'a, T>;
+    type Item = &'a T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter {
+            chunks: self.node_chunks().collect(),
+        }
+    }
+}
+
+/// A parallel iterator over mutable references to the elements of a [`BTreeList`].
+///
+/// As with [`ParIter`] the work is divided between threads a node chunk at a time.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// use rayon::prelude::*;
+///
+/// let mut list = (0..1000).collect::<BTreeList<_>>();
+/// list.par_iter_mut().for_each(|x| *x *= 2);
+/// assert!(list.iter().copied().eq((0..2000).step_by(2)));
+/// ```
+#[derive(Debug)]
+pub struct ParIterMut<'a, T> {
+    chunks: Vec<&'a mut [T]>,
+}
+
+impl<'a, T> ParallelIterator for ParIterMut<'a, T>
+where
+    T: Send,
+{
+    type Item = &'a mut T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.chunks
+            .into_par_iter()
+            .flat_map_iter(|chunk: &'a mut [T]| chunk.iter_mut())
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<'a, T, const B: usize> IntoParallelIterator for &'a mut BTreeList<T, B>
+where
+    T: Send,
+{
+    type Iter = ParIterMut<'a, T>;
+    type Item = &'a mut T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIterMut {
+            chunks: self.chunks_mut(),
+        }
+    }
+}
+
+/// A parallel iterator moving the elements out of a [`BTreeList`].
+///
+/// The list is broken up into its leaves, with the elements between them, and the work is
+/// divided between threads a leaf at a time.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// use rayon::prelude::*;
+///
+/// let list = (0..1000).map(|i| i.to_string()).collect::<BTreeList<_>>();
+/// let lengths: usize = list.into_par_iter().map(|s| s.len()).sum();
+/// assert_eq!(lengths, 2890);
+/// ```
+#[derive(Debug)]
+pub struct IntoParIter<T> {
+    chunks: Vec<Vec<T>>,
+}
+
+impl<T> ParallelIterator for IntoParIter<T>
+where
+    T: Send,
+{
+    type Item = T;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.chunks
+            .into_par_iter()
+            .flat_map_iter(Vec::into_iter)
+            .drive_unindexed(consumer)
+    }
+}
+
+impl<T, const B: usize> IntoParallelIterator for BTreeList<T, B>
+where
+    T: Send,
+{
+    type Iter = IntoParIter<T>;
+    type Item = T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        IntoParIter {
+            chunks: self.into_leaf_chunks().collect(),
+        }
+    }
+}
+
+/// Builds a list for each piece of work, filling its nodes bottom-up, and joins the lists with
+/// [`append`](BTreeList::append) in `O(log(n))` time each.
+///
+/// ```
+/// # use btreelist::BTreeList;
+/// use rayon::prelude::*;
+///
+/// let list = (0..1000).into_par_iter().map(|x| x * 2).collect::<BTreeList<_>>();
+/// assert!(list.iter().copied().eq((0..2000).step_by(2)));
+/// ```
+impl<T, const B: usize> FromParallelIterator<T> for BTreeList<T, B>
+where
+    T: Send,
+{
+    fn from_par_iter<I>(par_iter: I) -> Self
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        par_iter
+            .into_par_iter()
+            .fold(Vec::new, |mut elements, element| {
+                elements.push(element);
+                elements
+            })
+            .map(|elements| {
+                let len = elements.len();
+                Self::from_exact_iter(elements.into_iter(), len)
+            })
+            .reduce(Self::new, |mut left, mut right| {
+                left.append(&mut right);
+                left
+            })
+    }
+}
+
+/// Collects the elements into a list in parallel, as for
+/// [`from_par_iter`](FromParallelIterator::from_par_iter), and appends it.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// use rayon::prelude::*;
+///
+/// let mut list = btreelist![0, 1];
+/// list.par_extend((2..100).into_par_iter());
+/// assert!(list.iter().copied().eq(0..100));
+/// ```
+impl<T, const B: usize> ParallelExtend<T> for BTreeList<T, B>
+where
+    T: Send,
+{
+    fn par_extend<I>(&mut self, par_iter: I)
+    where
+        I: IntoParallelIterator<Item = T>,
+    {
+        let mut other = Self::from_par_iter(par_iter);
+        self.append(&mut other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::assert_shape;
+
+    #[test]
+    fn par_retain() {
+        fn retain<const B: usize>() {
+            for len in [0, 1, 10, 1000] {
+                let mut t = BTreeList::<usize, B>::from_exact_iter(0..len, len);
+                t.par_retain(|x| x % 7 != 3);
+                assert_shape(&t);
+                assert!(t.iter().copied().eq((0..len).filter(|x| x % 7 != 3)));
+                let extracted = t.par_extract_if(|x| x % 2 == 0);
+                assert_shape(&t);
+                assert_shape(&extracted);
+                assert!(t.iter().all(|x| x % 2 == 1));
+                assert_eq!(
+                    t.len() + extracted.len(),
+                    (0..len).filter(|x| x % 7 != 3).count()
+                );
+            }
+        }
+        retain::<2>();
+        retain::<6>();
+    }
+
+    #[test]
+    fn par_iter() {
+        for len in [0, 1, 10, 1000] {
+            let mut t = BTreeList::<usize, 2>::from_exact_iter(0..len, len);
+            let v = t.par_iter().copied().collect::<Vec<_>>();
+            assert_eq!(t, v);
+            t.par_iter_mut().for_each(|x| *x += 1);
+            assert!(t.eq_iter(1..len + 1));
+            assert_shape(&t);
+            let v = t.into_par_iter().collect::<Vec<_>>();
+            assert!(v.into_iter().eq(1..len + 1));
+        }
+    }
+
+    #[test]
+    fn par_extend() {
+        fn extend<const B: usize>() {
+            for len in [0, 1, 10, 1000] {
+                let mut t = (0..len).into_par_iter().collect::<BTreeList<_, B>>();
+                assert_shape(&t);
+                assert!(t.eq_iter(0..len));
+                t.par_extend((len..2 * len).into_par_iter());
+                assert_shape(&t);
+                assert!(t.eq_iter(0..2 * len));
+            }
+        }
+        extend::<2>();
+        extend::<6>();
+    }
+}
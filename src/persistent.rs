@@ -0,0 +1,926 @@
+#[cfg(feature = "merkle")]
+use std::hash::Hash;
+use std::{fmt, iter::FromIterator, mem, sync::Arc};
+
+#[cfg(feature = "merkle")]
+use crate::merkle::{Fingerprint, HashCache};
+use crate::{
+    node::{TreeNode, TreeNodeMut},
+    BTreeList,
+};
+
+/// A persistent list, sharing its nodes between clones.
+///
+/// The tree has the same shape as a [`BTreeList`] but each child is held in an [`Arc`], so
+/// [`clone`](Clone::clone) takes `O(1)` time and clones share all of their nodes. Editing a list
+/// copies just the nodes on the path from the root to the edit that are still shared with another
+/// list, taking `O(B log(n))` time, and leaves every other version of the list as it was. This
+/// suits keeping the history of a document or the versions of a CRDT around cheaply.
+///
+/// Editing needs the elements to be [`Clone`] so that shared nodes can be copied.
+///
+/// ```
+/// # use btreelist::PersistentBTreeList;
+/// let mut list = (0..100).collect::<PersistentBTreeList<_>>();
+/// let before = list.clone();
+///
+/// assert_eq!(list.remove(0), Some(0));
+/// list.push_back(100);
+///
+/// assert_eq!(before.len(), 100);
+/// assert_eq!(before.first(), Some(&0));
+/// assert_eq!(list.first(), Some(&1));
+/// assert_eq!(list.last(), Some(&100));
+/// ```
+pub struct PersistentBTreeList<T, const B: usize = 6> {
+    root_node: Option<Arc<PersistentNode<T, B>>>,
+}
+
+#[derive(Clone, Debug)]
+struct PersistentNode<T, const B: usize> {
+    elements: Vec<T>,
+    children: Vec<Arc<PersistentNode<T, B>>>,
+    length: usize,
+    #[cfg(feature = "merkle")]
+    hash: HashCache,
+}
+
+impl<T, const B: usize> PersistentBTreeList<T, B> {
+    /// Construct a new, empty [`PersistentBTreeList`].
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list: PersistentBTreeList<i32> = PersistentBTreeList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        let () = BTreeList::<T, B>::VALID_B;
+        Self { root_node: None }
+    }
+
+    /// Get the number of elements in the list.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.root_node.as_ref().map_or(0, |root| root.length)
+    }
+
+    /// Check if the list is empty.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = PersistentBTreeList::<i32>::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Create an iterator through the list's elements.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// assert!(list.iter().copied().eq(0..3));
+    /// ```
+    pub fn iter(&self) -> PersistentIter<'_, T, B> {
+        PersistentIter {
+            inner: self,
+            index: 0,
+            index_back: self.len(),
+        }
+    }
+
+    /// Get the element at `index`, if it is in the list.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.get(1), Some(&1));
+    /// assert_eq!(list.get(3), None);
+    /// ```
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.root_node.as_ref().and_then(|root| root.get(index))
+    }
+
+    /// Get the first element of the list, if it isn't empty.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (1..4).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.first(), Some(&1));
+    /// ```
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    /// Get the last element of the list, if it isn't empty.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (1..4).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.last(), Some(&3));
+    /// ```
+    pub fn last(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Take an immutable view of the list as it is now, sharing its nodes, in `O(1)` time.
+    ///
+    /// The snapshot stays the same while the list carries on being edited, and can be sent to
+    /// other threads when the elements can be.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..100).collect::<PersistentBTreeList<_>>();
+    /// let snapshot = list.snapshot();
+    /// let render = std::thread::spawn(move || snapshot.iter().sum::<i32>());
+    /// list.push_back(100);
+    /// assert_eq!(render.join().unwrap(), 4950);
+    /// ```
+    pub fn snapshot(&self) -> Snapshot<T, B> {
+        Snapshot { list: self.clone() }
+    }
+
+    /// Iterate over the list as it is now, cloning the elements out of a [`snapshot`] taken in
+    /// `O(1)` time.
+    ///
+    /// The iterator owns its snapshot rather than borrowing the list, so the list can carry on
+    /// being edited while it runs and none of those edits are seen by it. To borrow the elements
+    /// instead, take a [`snapshot`] and iterate over that.
+    ///
+    /// [`snapshot`]: Self::snapshot
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..5).collect::<PersistentBTreeList<_>>();
+    /// for element in list.iter_snapshot() {
+    ///     list.push_back(element * 10);
+    /// }
+    /// assert!(list.iter().copied().eq((0..5).chain((0..5).map(|e| e * 10))));
+    /// ```
+    pub fn iter_snapshot(&self) -> SnapshotIter<T, B> {
+        SnapshotIter {
+            index: 0,
+            index_back: self.len(),
+            snapshot: self.snapshot(),
+        }
+    }
+
+    /// Check whether the two lists share their whole tree, in which case they are equal without
+    /// comparing any elements.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// let mut copy = list.clone();
+    /// assert!(list.ptr_eq(&copy));
+    /// copy.push_back(3);
+    /// assert!(!list.ptr_eq(&copy));
+    /// ```
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        match (&self.root_node, &other.root_node) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<T, const B: usize> PersistentBTreeList<T, B>
+where
+    T: Clone,
+{
+    /// Insert the `element` into the list at `index`, returning it back if the index is past the
+    /// end of the list.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.insert(1, 7), Ok(()));
+    /// assert_eq!(list.insert(5, 7), Err(7));
+    /// assert!(list.iter().copied().eq([0, 7, 1, 2]));
+    /// ```
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        if index > self.len() {
+            return Err(element);
+        }
+        match self.root_node.as_mut() {
+            Some(root) => {
+                if let Some((median, sibling)) = PersistentNode::edit(root).insert(index, element) {
+                    let old_root = self.root_node.take().expect("root was just edited");
+                    self.root_node = Some(Arc::new(PersistentNode::from_parts(
+                        vec![median],
+                        vec![old_root, Arc::new(sibling)],
+                    )));
+                }
+            }
+            None => {
+                self.root_node = Some(Arc::new(PersistentNode::from_parts(
+                    vec![element],
+                    Vec::new(),
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// Push the `element` onto the back of the list.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = PersistentBTreeList::<_>::new();
+    /// list.push_back(1);
+    /// list.push_back(2);
+    /// assert!(list.iter().copied().eq([1, 2]));
+    /// ```
+    pub fn push_back(&mut self, element: T) {
+        let _ = self.insert(self.len(), element);
+    }
+
+    /// Remove the element at `index` from the list, returning it if the index was in the list.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.remove(1), Some(1));
+    /// assert_eq!(list.remove(2), None);
+    /// assert!(list.iter().copied().eq([0, 2]));
+    /// ```
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        let root = PersistentNode::edit(self.root_node.as_mut()?);
+        let element = root.remove(index);
+        if root.elements.is_empty() {
+            // the root has run out of elements, leaving at most a single child to take its place
+            self.root_node = root.children.pop();
+        }
+        Some(element)
+    }
+
+    /// Remove the last element of the list, returning it if the list wasn't empty.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// assert_eq!(list.pop_back(), Some(2));
+    /// assert_eq!(list.len(), 2);
+    /// ```
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.len().checked_sub(1)?)
+    }
+
+    /// Replace the element at `index` with `element`, returning the old element, or returning
+    /// `element` back if the index is not in the list.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..3).collect::<PersistentBTreeList<_>>();
+    /// let before = list.clone();
+    /// assert_eq!(list.set(1, 7), Ok(1));
+    /// assert_eq!(list.set(3, 7), Err(7));
+    /// assert_eq!(list.get(1), Some(&7));
+    /// assert_eq!(before.get(1), Some(&1));
+    /// ```
+    pub fn set(&mut self, index: usize, element: T) -> Result<T, T> {
+        match self.root_node.as_mut() {
+            Some(root) if index < root.length => Ok(PersistentNode::edit(root).set(index, element)),
+            _ => Err(element),
+        }
+    }
+}
+
+#[cfg(feature = "merkle")]
+impl<T, const B: usize> PersistentBTreeList<T, B>
+where
+    T: Hash,
+{
+    /// A hash of the list's elements in order, which is the same for lists with equal elements
+    /// whatever the shape of their trees.
+    ///
+    /// Each node caches the hash of its subtree, built from its children's, so after an edit only
+    /// the nodes on the path to it are hashed again and clones share the hashes of the nodes they
+    /// share. The hash is a polynomial over the elements' [`Hash`]es modulo a 61 bit prime, so
+    /// lists with different elements collide with probability around `n / 2^61`. It is only
+    /// stable within a build of the program.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let mut list = (0..1000).collect::<PersistentBTreeList<_>>();
+    /// let mut other = list.clone();
+    /// other.set(500, 0).unwrap();
+    /// assert_ne!(list.content_hash(), other.content_hash());
+    /// list.set(500, 0).unwrap();
+    /// assert_eq!(list.content_hash(), other.content_hash());
+    /// ```
+    pub fn content_hash(&self) -> u64 {
+        self.root_node
+            .as_ref()
+            .map_or(Fingerprint::EMPTY, |root| root.fingerprint())
+            .value()
+    }
+
+    /// Check whether the lists are equal by comparing their lengths and
+    /// [`content_hash`](Self::content_hash)es, rather than each of their elements.
+    ///
+    /// This takes `O(1)` time once the hashes are cached, but like the hashes it can be wrong
+    /// for different lists with a very small probability.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (0..1000).collect::<PersistentBTreeList<_>>();
+    /// let mut other = list.clone();
+    /// other.push_back(1000);
+    /// assert!(!list.hash_eq(&other));
+    /// other.pop_back();
+    /// assert!(list.hash_eq(&other));
+    /// ```
+    pub fn hash_eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+            || (self.len() == other.len() && self.content_hash() == other.content_hash())
+    }
+
+    /// Find the first index at which the lists differ, or the length of the shorter one if it is
+    /// a prefix of the other, returning `None` if they are equal.
+    ///
+    /// Rather than comparing elements this binary searches for the longest common prefix by
+    /// hash, making `O(log(n))` comparisons that each combine the cached hashes down a path of
+    /// the trees. As with [`hash_eq`](Self::hash_eq) it can miss differences with a very small
+    /// probability.
+    ///
+    /// ```
+    /// # use btreelist::PersistentBTreeList;
+    /// let list = (0..1000).collect::<PersistentBTreeList<_>>();
+    /// let mut other = list.clone();
+    /// other.set(700, 0).unwrap();
+    /// assert_eq!(list.first_difference(&other), Some(700));
+    /// assert_eq!(list.first_difference(&list.clone()), None);
+    /// ```
+    pub fn first_difference(&self, other: &Self) -> Option<usize> {
+        if self.hash_eq(other) {
+            return None;
+        }
+        // the prefixes of length `low` are equal and those of length `high` aren't, unless
+        // `high` is past the end of one of the lists
+        let (mut low, mut high) = (0, self.len().min(other.len()) + 1);
+        while high - low > 1 {
+            let mid = low + (high - low) / 2;
+            if self.prefix_fingerprint(mid) == other.prefix_fingerprint(mid) {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        Some(low)
+    }
+
+    fn prefix_fingerprint(&self, len: usize) -> Fingerprint {
+        self.root_node.as_ref().map_or(Fingerprint::EMPTY, |root| {
+            root.prefix_fingerprint(Fingerprint::EMPTY, len)
+        })
+    }
+}
+
+impl<T, const B: usize> TreeNode<T, B> for PersistentNode<T, B> {
+    type Child = Arc<Self>;
+
+    fn elements(&self) -> &[T] {
+        &self.elements
+    }
+
+    fn children(&self) -> &[Arc<Self>] {
+        &self.children
+    }
+
+    fn child(child: &Arc<Self>) -> &Self {
+        child
+    }
+
+    fn len(&self) -> usize {
+        self.length
+    }
+}
+
+impl<T, const B: usize> TreeNodeMut<T, B> for PersistentNode<T, B>
+where
+    T: Clone,
+{
+    fn from_parts(elements: Vec<T>, children: Vec<Arc<Self>>) -> Self {
+        let length = elements.len() + children.iter().map(|c| c.length).sum::<usize>();
+        Self {
+            elements,
+            children,
+            length,
+            #[cfg(feature = "merkle")]
+            hash: HashCache::new(),
+        }
+    }
+
+    fn into_parts(self) -> (Vec<T>, Vec<Arc<Self>>) {
+        (self.elements, self.children)
+    }
+
+    fn parts_mut(&mut self) -> (&mut Vec<T>, &mut Vec<Arc<Self>>) {
+        (&mut self.elements, &mut self.children)
+    }
+
+    /// Get the node in `node` to edit, copying it first if it is shared.
+    fn edit(node: &mut Arc<Self>) -> &mut Self {
+        let node = Arc::make_mut(node);
+        #[cfg(feature = "merkle")]
+        node.hash.clear();
+        node
+    }
+
+    /// Take the node out of `child`, copying it if it is shared.
+    fn unwrap(child: Arc<Self>) -> Self {
+        Arc::try_unwrap(child).unwrap_or_else(|shared| (*shared).clone())
+    }
+
+    fn wrap(node: Self) -> Arc<Self> {
+        Arc::new(node)
+    }
+
+    fn refresh(&mut self) {
+        self.length = self.elements.len() + self.children.iter().map(|c| c.length).sum::<usize>();
+    }
+}
+
+impl<T, const B: usize> PersistentNode<T, B> {
+    #[cfg(feature = "merkle")]
+    fn fingerprint(&self) -> Fingerprint
+    where
+        T: Hash,
+    {
+        self.hash
+            .get_or_compute(|| self.prefix_fingerprint(Fingerprint::EMPTY, self.length))
+    }
+
+    /// Extend `fingerprint` with the first `len` elements of this subtree, using the cached
+    /// fingerprints of the children it covers entirely.
+    #[cfg(feature = "merkle")]
+    fn prefix_fingerprint(&self, mut fingerprint: Fingerprint, mut len: usize) -> Fingerprint
+    where
+        T: Hash,
+    {
+        if self.is_leaf() {
+            return self.elements[..len]
+                .iter()
+                .fold(fingerprint, Fingerprint::push);
+        }
+        for (child, element) in self
+            .children
+            .iter()
+            .zip(self.elements.iter().map(Some).chain(std::iter::once(None)))
+        {
+            if len < child.length {
+                return child.prefix_fingerprint(fingerprint, len);
+            }
+            fingerprint = fingerprint.append(child.fingerprint(), child.length);
+            len -= child.length;
+            match element {
+                Some(element) if len > 0 => {
+                    fingerprint = fingerprint.push(element);
+                    len -= 1;
+                }
+                _ => return fingerprint,
+            }
+        }
+        fingerprint
+    }
+
+    #[cfg(test)]
+    fn check(&self, root: bool) -> usize {
+        assert!(self.elements.len() < 2 * B);
+        assert!(root || self.elements.len() >= B - 1);
+        assert!(!self.elements.is_empty());
+        let mut depth = 0;
+        if !self.is_leaf() {
+            assert_eq!(self.children.len(), self.elements.len() + 1);
+            let depths = self
+                .children
+                .iter()
+                .map(|c| c.check(false))
+                .collect::<Vec<_>>();
+            assert!(depths.iter().all(|d| *d == depths[0]));
+            depth = depths[0] + 1;
+        }
+        assert_eq!(
+            self.length,
+            self.elements.len() + self.children.iter().map(|c| c.length).sum::<usize>()
+        );
+        depth
+    }
+}
+
+impl<T, const B: usize> PersistentNode<T, B>
+where
+    T: Clone,
+{
+    /// Insert `element` at `index` in this subtree, returning the median and new sibling if this
+    /// node had to be split.
+    fn insert(&mut self, index: usize, element: T) -> Option<(T, Self)> {
+        self.length += 1;
+        if self.is_leaf() {
+            self.elements.insert(index, element);
+        } else {
+            let (child_index, index) = self.find_child(index);
+            let child = Self::edit(&mut self.children[child_index]);
+            if let Some((median, sibling)) = child.insert(index, element) {
+                self.elements.insert(child_index, median);
+                self.children.insert(child_index + 1, Arc::new(sibling));
+            }
+        }
+        self.split_overfull()
+    }
+
+    fn set(&mut self, index: usize, element: T) -> T {
+        if self.is_leaf() {
+            return mem::replace(&mut self.elements[index], element);
+        }
+        let (child_index, index) = self.find_child(index);
+        if index < self.children[child_index].length {
+            Self::edit(&mut self.children[child_index]).set(index, element)
+        } else {
+            mem::replace(&mut self.elements[child_index], element)
+        }
+    }
+
+    /// Remove the element at `index`, which must be in this subtree, topping the child it was
+    /// removed from back up if it runs low.
+    fn remove(&mut self, index: usize) -> T {
+        self.length -= 1;
+        if self.is_leaf() {
+            return self.elements.remove(index);
+        }
+        let (child_index, index) = self.find_child(index);
+        let child = Self::edit(&mut self.children[child_index]);
+        let element = if index < child.length {
+            child.remove(index)
+        } else {
+            // replace the separator with its predecessor from the end of the child before it
+            let predecessor = child.pop_back();
+            mem::replace(&mut self.elements[child_index], predecessor)
+        };
+        self.fix_underfull_child(child_index);
+        element
+    }
+
+    fn pop_back(&mut self) -> T {
+        self.length -= 1;
+        if self.is_leaf() {
+            return self
+                .elements
+                .pop()
+                .expect("non-root nodes shouldn't be empty");
+        }
+        let last = self.children.len() - 1;
+        let element = Self::edit(&mut self.children[last]).pop_back();
+        self.fix_underfull_child(last);
+        element
+    }
+}
+
+/// Clones share the whole tree with the original, taking `O(1)` time.
+impl<T, const B: usize> Clone for PersistentBTreeList<T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            root_node: self.root_node.clone(),
+        }
+    }
+}
+
+impl<T, const B: usize> Default for PersistentBTreeList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const B: usize> fmt::Debug for PersistentBTreeList<T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const B: usize> PartialEq for PersistentBTreeList<T, B>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.len() == other.len() && self.iter().eq(other.iter()))
+    }
+}
+
+impl<T, const B: usize> Eq for PersistentBTreeList<T, B> where T: Eq {}
+
+impl<T, const B: usize> Extend<T> for PersistentBTreeList<T, B>
+where
+    T: Clone,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            self.push_back(element);
+        }
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for PersistentBTreeList<T, B>
+where
+    T: Clone,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a PersistentBTreeList<T, B> {
+    type Item = &'a T;
+
+    type IntoIter = PersistentIter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An immutable view of a [`PersistentBTreeList`] at a point in time.
+///
+/// Created by [`PersistentBTreeList::snapshot`]. The snapshot shares its nodes with the list so
+/// taking and cloning one takes `O(1)` time, and it can be turned back into a list to carry on
+/// editing from that point.
+///
+/// ```
+/// # use btreelist::PersistentBTreeList;
+/// let mut list = (0..3).collect::<PersistentBTreeList<_>>();
+/// let snapshot = list.snapshot();
+/// list.remove(0);
+/// assert_eq!(snapshot.get(0), Some(&0));
+/// assert_eq!(list.get(0), Some(&1));
+///
+/// let restored = PersistentBTreeList::from(snapshot);
+/// assert!(restored.iter().copied().eq(0..3));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot<T, const B: usize = 6> {
+    list: PersistentBTreeList<T, B>,
+}
+
+impl<T, const B: usize> Snapshot<T, B> {
+    /// Get the number of elements in the snapshot.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Check if the snapshot is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Create an iterator through the snapshot's elements.
+    pub fn iter(&self) -> PersistentIter<'_, T, B> {
+        self.list.iter()
+    }
+
+    /// Get the element at `index`, if it is in the snapshot.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Get the first element of the snapshot, if it isn't empty.
+    pub fn first(&self) -> Option<&T> {
+        self.list.first()
+    }
+
+    /// Get the last element of the snapshot, if it isn't empty.
+    pub fn last(&self) -> Option<&T> {
+        self.list.last()
+    }
+}
+
+impl<T, const B: usize> From<Snapshot<T, B>> for PersistentBTreeList<T, B> {
+    fn from(snapshot: Snapshot<T, B>) -> Self {
+        snapshot.list
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a Snapshot<T, B> {
+    type Item = &'a T;
+
+    type IntoIter = PersistentIter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over items in a [`PersistentBTreeList`].
+#[derive(Debug, Clone)]
+pub struct PersistentIter<'a, T, const B: usize> {
+    inner: &'a PersistentBTreeList<T, B>,
+    index: usize,
+    index_back: usize,
+}
+
+impl<'a, T, const B: usize> Iterator for PersistentIter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.index_back {
+            self.index += 1;
+            self.inner.get(self.index - 1)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, T, const B: usize> DoubleEndedIterator for PersistentIter<'a, T, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.index_back {
+            self.index_back -= 1;
+            self.inner.get(self.index_back)
+        } else {
+            None
+        }
+    }
+}
+
+/// An iterator over the elements of a [`Snapshot`] that owns it, cloning each element, created
+/// by [`PersistentBTreeList::iter_snapshot`].
+#[derive(Debug, Clone)]
+pub struct SnapshotIter<T, const B: usize> {
+    snapshot: Snapshot<T, B>,
+    index: usize,
+    index_back: usize,
+}
+
+impl<T, const B: usize> Iterator for SnapshotIter<T, B>
+where
+    T: Clone,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index < self.index_back {
+            self.index += 1;
+            self.snapshot.get(self.index - 1).cloned()
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.index_back - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<T, const B: usize> DoubleEndedIterator for SnapshotIter<T, B>
+where
+    T: Clone,
+{
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.index < self.index_back {
+            self.index_back -= 1;
+            self.snapshot.get(self.index_back).cloned()
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, const B: usize> ExactSizeIterator for SnapshotIter<T, B> where T: Clone {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::TestRng;
+
+    fn assert_shape<T, const B: usize>(list: &PersistentBTreeList<T, B>) {
+        if let Some(root) = list.root_node.as_ref() {
+            root.check(true);
+        }
+    }
+
+    #[test]
+    fn versions() {
+        fn versions<const B: usize>() {
+            let mut t = PersistentBTreeList::<usize, B>::new();
+            let mut v = Vec::new();
+            let mut versions = Vec::new();
+            let mut snapshots = Vec::new();
+            let mut rng = TestRng::new(7);
+            for i in 0..2000 {
+                let r = rng.next();
+                match (v.len(), r % 6) {
+                    (0, _) | (_, 0..=3) => {
+                        let index = r % (v.len() + 1);
+                        t.insert(index, i).unwrap();
+                        v.insert(index, i);
+                    }
+                    (len, 4) => {
+                        let index = r % len;
+                        assert_eq!(t.set(index, i), Ok(v[index]));
+                        v[index] = i;
+                    }
+                    (len, _) => {
+                        let index = r % len;
+                        assert_eq!(t.remove(index), Some(v.remove(index)));
+                    }
+                }
+                assert_shape(&t);
+                if i % 50 == 1 {
+                    versions.push((t.clone(), v.clone()));
+                    snapshots.push((t.snapshot(), v.clone()));
+                }
+            }
+            while let Some(element) = t.pop_back() {
+                assert_eq!(Some(element), v.pop());
+            }
+            assert_shape(&t);
+            assert!(t.is_empty());
+            for (t, v) in versions {
+                assert_shape(&t);
+                assert!(t.iter().eq(v.iter()));
+            }
+            for (snapshot, v) in snapshots {
+                assert_eq!(snapshot.len(), v.len());
+                assert!(snapshot.iter().eq(v.iter()));
+            }
+        }
+        versions::<2>();
+        versions::<3>();
+        versions::<6>();
+    }
+
+    #[cfg(feature = "merkle")]
+    #[test]
+    fn content_hash() {
+        fn hashes<const B: usize>() {
+            // the same elements in different shapes of tree
+            let pushed = (0..500).collect::<PersistentBTreeList<usize, B>>();
+            let mut inserted = PersistentBTreeList::<usize, B>::new();
+            for i in (0..500).rev() {
+                inserted.insert(0, i).unwrap();
+            }
+            assert_eq!(pushed.content_hash(), inserted.content_hash());
+            assert!(pushed.hash_eq(&inserted));
+            assert_eq!(pushed.first_difference(&inserted), None);
+
+            let mut edited = pushed.clone();
+            for (i, index) in [0, 499, 250, 17, 333].iter().copied().enumerate() {
+                edited.set(index, 1000 + i).unwrap();
+                assert_shape(&edited);
+                assert_ne!(edited.content_hash(), pushed.content_hash());
+                assert_eq!(edited.first_difference(&pushed), Some(0));
+            }
+            let mut removed = pushed.clone();
+            assert_eq!(removed.remove(400), Some(400));
+            assert_eq!(removed.first_difference(&pushed), Some(400));
+            removed.insert(400, 400).unwrap();
+            assert!(removed.hash_eq(&pushed));
+
+            let mut longer = pushed.clone();
+            longer.push_back(500);
+            assert_eq!(longer.first_difference(&pushed), Some(500));
+            assert_eq!(pushed.first_difference(&longer), Some(500));
+            assert_eq!(
+                PersistentBTreeList::<usize, B>::new().first_difference(&pushed),
+                Some(0)
+            );
+        }
+        hashes::<2>();
+        hashes::<3>();
+        hashes::<6>();
+    }
+
+    #[test]
+    fn iter_snapshot() {
+        // edits made while iterating, from both ends, aren't seen by the iterator
+        let mut list = (0..300).collect::<PersistentBTreeList<usize, 2>>();
+        let mut iter = list.iter_snapshot();
+        assert_eq!(iter.len(), 300);
+        for i in 0..150 {
+            assert_eq!(iter.next(), Some(i));
+            assert_eq!(iter.next_back(), Some(299 - i));
+            list.set(i, 1000).unwrap();
+            list.remove(list.len() / 2);
+            list.insert(0, 2000).unwrap();
+            assert_shape(&list);
+        }
+        assert_eq!(iter.next(), None);
+        assert_eq!(list.len(), 300);
+        assert!(list.iter_snapshot().eq(list.iter().cloned()));
+    }
+}
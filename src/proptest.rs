@@ -0,0 +1,107 @@
+//! [Proptest](https://docs.rs/proptest) strategies for generating lists and the indices to edit
+//! them with.
+//!
+//! ```
+//! use btreelist::proptest::{insert_indices, list};
+//! use proptest::{prelude::*, test_runner::TestRunner};
+//!
+//! let strategy = (list::<_, _, 6>(any::<u8>(), 0..100), insert_indices(0..50));
+//! TestRunner::default()
+//!     .run(&strategy, |(mut l, indices)| {
+//!         let len = l.len();
+//!         for &i in &indices {
+//!             // each index is at most the number inserted before it, so it is in bounds
+//!             prop_assert!(l.insert(i, 0).is_ok());
+//!         }
+//!         prop_assert_eq!(l.len(), len + indices.len());
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+
+use ::proptest::{
+    collection::{vec, SizeRange},
+    prelude::*,
+};
+
+use crate::BTreeList;
+
+/// Generate lists of elements from `element`, with a length in `size`.
+///
+/// The lists are built in one of several ways, so that the same elements can come out as trees
+/// of different fills and depths: filled bottom-up, pushed onto either end, or inserted at
+/// positions spread over the list. They shrink towards shorter lists of simpler elements built
+/// bottom-up.
+pub fn list<T, S, const B: usize>(
+    element: S,
+    size: impl Into<SizeRange>,
+) -> impl Strategy<Value = BTreeList<T, B>>
+where
+    T: std::fmt::Debug,
+    S: Strategy<Value = T>,
+{
+    (0..4_u8, vec(element, size), any::<usize>()).prop_map(|(shape, elements, seed)| {
+        let len = elements.len();
+        let mut list = BTreeList::new();
+        match shape {
+            0 => list = BTreeList::from_exact_iter(elements.into_iter(), len),
+            1 => elements.into_iter().for_each(|e| list.push_back(e)),
+            2 => elements.into_iter().rev().for_each(|e| list.push_front(e)),
+            _ => {
+                for (n, element) in elements.into_iter().enumerate() {
+                    let index = seed.wrapping_mul(n + 1).rotate_left(n as u32) % (n + 1);
+                    let _ = list.insert(index, element);
+                }
+            }
+        }
+        list
+    })
+}
+
+/// Generate sequences of indices, with a length in `size`, that can each be inserted at in turn
+/// starting from an empty list: the `n`th index is at most `n`.
+pub fn insert_indices(size: impl Into<SizeRange>) -> impl Strategy<Value = Vec<usize>> {
+    vec(any::<usize>(), size).prop_map(|indices| {
+        indices
+            .into_iter()
+            .enumerate()
+            .map(|(n, i)| i % (n + 1))
+            .collect()
+    })
+}
+
+/// Generate sequences of up to `len` indices that can each be removed in turn from a list of
+/// length `len`: the `n`th index is less than `len - n`.
+pub fn remove_indices(len: usize) -> impl Strategy<Value = Vec<usize>> {
+    vec(any::<usize>(), 0..=len).prop_map(move |indices| {
+        indices
+            .into_iter()
+            .enumerate()
+            .map(|(n, i)| i % (len - n))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::assert_shape;
+
+    proptest! {
+        #[test]
+        fn proptest_strategies(
+            t in list::<_, _, 2>(any::<u8>(), 0..200),
+            inserts in insert_indices(0..100),
+        ) {
+            assert_shape(&t);
+            let mut t = t;
+            let mut v = t.iter().copied().collect::<Vec<_>>();
+            for i in inserts {
+                assert!(t.insert(i, 0).is_ok());
+                v.insert(i, 0);
+            }
+            assert_shape(&t);
+            assert_eq!(t, v);
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use quickcheck::{Arbitrary, Gen};
+
+use crate::BTreeList;
+
+/// Generates lists as for [`Vec`], and shrinks them as a `Vec` would be, towards shorter lists
+/// and simpler elements.
+impl<T, const B: usize> Arbitrary for BTreeList<T, B>
+where
+    T: Arbitrary,
+{
+    fn arbitrary(g: &mut Gen) -> Self {
+        Vec::<T>::arbitrary(g).into_iter().collect()
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let elements = self.iter().cloned().collect::<Vec<_>>();
+        Box::new(elements.shrink().map(|shrunk| shrunk.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::assert_shape;
+
+    #[test]
+    fn quickcheck_shrink() {
+        let mut g = Gen::new(100);
+        for _ in 0..20 {
+            let t = BTreeList::<u8, 2>::arbitrary(&mut g);
+            assert_shape(&t);
+            for shrunk in t.shrink().take(20) {
+                assert_shape(&shrunk);
+                assert!(shrunk.len() <= t.len());
+            }
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use rkyv::{
+    rancor::Fallible,
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Serialize,
+};
+
+use crate::BTreeList;
+
+/// Archives the elements as an [`ArchivedVec`], which can be read in place without deserializing
+/// the list.
+impl<T, const B: usize> Archive for BTreeList<T, B>
+where
+    T: Archive,
+{
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_len(self.len(), resolver, out);
+    }
+}
+
+impl<T, S, const B: usize> Serialize<S> for BTreeList<T, B>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_iter::<T, _, _>(self.elements(), serializer)
+    }
+}
+
+/// Deserializes the archived elements and builds the tree bottom-up from them.
+impl<T, D, const B: usize> Deserialize<BTreeList<T, B>, D> for ArchivedVec<T::Archived>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<BTreeList<T, B>, D::Error> {
+        let elements = self
+            .iter()
+            .map(|element| element.deserialize(deserializer))
+            .collect::<Result<Vec<_>, _>>()?;
+        let len = elements.len();
+        Ok(BTreeList::from_exact_iter(elements.into_iter(), len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::assert_shape;
+
+    #[test]
+    fn rkyv_round_trip() {
+        for len in [0, 1, 10, 1000] {
+            let t = BTreeList::<u32, 3>::from_exact_iter(0..len, len as usize);
+            let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&t).unwrap();
+            let archived =
+                rkyv::access::<ArchivedVec<rkyv::Archived<u32>>, rkyv::rancor::Error>(&bytes)
+                    .unwrap();
+            assert!(archived.iter().map(|e| e.to_native()).eq(0..len));
+            let back =
+                rkyv::deserialize::<BTreeList<u32, 3>, rkyv::rancor::Error>(archived).unwrap();
+            assert_shape(&back);
+            assert_eq!(back, t);
+        }
+    }
+}
@@ -0,0 +1,409 @@
+use std::{fmt, iter::FromIterator};
+
+use crate::{augmented::AugmentedIter, AugmentedList, Measure};
+
+/// A run of `count` copies of `value`.
+#[derive(Debug, Clone)]
+struct Run<T> {
+    value: T,
+    count: usize,
+}
+
+/// The [`Measure`] of the runs of an [`RleList`], summing their lengths.
+struct RunLength;
+
+impl<T> Measure<Run<T>> for RunLength {
+    type Summary = usize;
+
+    fn empty() -> usize {
+        0
+    }
+
+    fn measure(run: &Run<T>) -> usize {
+        run.count
+    }
+
+    fn combine(left: &usize, right: &usize) -> usize {
+        left + right
+    }
+}
+
+/// A list storing runs of equal elements once each, with a count.
+///
+/// The runs are held in an [`AugmentedList`] caching the number of elements in each subtree, so
+/// elements are still indexed, inserted and removed individually in `O(log(r))` time for `r`
+/// runs. Lists made up of long runs, like formatting flags over a document, take memory for the
+/// runs rather than the elements.
+///
+/// Neighbouring runs never hold equal values, as they are joined up when edits bring them
+/// together. Editing needs the elements to be [`Clone`] to split runs and [`PartialEq`] to find
+/// the runs to join.
+///
+/// ```
+/// # use btreelist::RleList;
+/// let mut flags = RleList::<bool>::new();
+/// flags.insert_run(0, false, 1000).unwrap();
+/// flags.insert_run(100, true, 50).unwrap();
+/// assert_eq!(flags.len(), 1050);
+/// assert_eq!(flags.run_count(), 3);
+/// assert_eq!(flags.get(120), Some(&true));
+///
+/// // filling the gap joins the runs back together
+/// flags.set(100, false).unwrap();
+/// for _ in 0..49 {
+///     flags.remove(101);
+/// }
+/// assert_eq!(flags.run_count(), 1);
+/// ```
+pub struct RleList<T, const B: usize = 6> {
+    runs: AugmentedList<Run<T>, RunLength, B>,
+}
+
+impl<T, const B: usize> RleList<T, B> {
+    /// Construct a new, empty [`RleList`].
+    ///
+    /// ```
+    /// # use btreelist::RleList;
+    /// let list: RleList<i32> = RleList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            runs: AugmentedList::new(),
+        }
+    }
+
+    /// Get the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.runs.summary()
+    }
+
+    /// Check if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Get the number of runs the elements are stored in.
+    pub fn run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    /// Create an iterator through the list's elements, repeating each run's value.
+    pub fn iter(&self) -> RleIter<'_, T, B> {
+        RleIter {
+            runs: self.runs(),
+            current: None,
+        }
+    }
+
+    /// Create an iterator through the runs of the list, as each value and the number of times it
+    /// repeats.
+    ///
+    /// ```
+    /// # use btreelist::RleList;
+    /// let list = "aaabccc".chars().collect::<RleList<_>>();
+    /// assert_eq!(
+    ///     list.runs().collect::<Vec<_>>(),
+    ///     [(&'a', 3), (&'b', 1), (&'c', 3)]
+    /// );
+    /// ```
+    pub fn runs(&self) -> Runs<'_, T, B> {
+        Runs {
+            runs: self.runs.iter(),
+        }
+    }
+
+    /// Get the element at `index`, if it is in the list.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        let (run_index, _) = self.locate(index)?;
+        self.runs.get(run_index).map(|run| &run.value)
+    }
+
+    /// Get the first element of the list, if it isn't empty.
+    pub fn first(&self) -> Option<&T> {
+        self.runs.first().map(|run| &run.value)
+    }
+
+    /// Get the last element of the list, if it isn't empty.
+    pub fn last(&self) -> Option<&T> {
+        self.runs.last().map(|run| &run.value)
+    }
+
+    /// Find the run that `index` is in and the offset into it.
+    fn locate(&self, index: usize) -> Option<(usize, usize)> {
+        let run_index = self.runs.find_index(|len| *len > index)?;
+        Some((run_index, index - self.runs.prefix_summary(run_index)?))
+    }
+}
+
+impl<T, const B: usize> RleList<T, B>
+where
+    T: Clone + PartialEq,
+{
+    /// Insert the `element` into the list at `index`, returning it back if the index is past the
+    /// end of the list.
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), T> {
+        self.insert_run(index, element, 1)
+    }
+
+    /// Insert `count` copies of `element` into the list at `index`, returning it back if the
+    /// index is past the end of the list.
+    pub fn insert_run(&mut self, index: usize, element: T, count: usize) -> Result<(), T> {
+        if index > self.len() {
+            return Err(element);
+        }
+        if count == 0 {
+            return Ok(());
+        }
+        let (run_index, offset) = self.locate(index).unwrap_or_else(|| (self.runs.len(), 0));
+        let before = run_index.checked_sub(1).and_then(|i| self.runs.get(i));
+        if matches!(self.runs.get(run_index), Some(run) if run.value == element) {
+            self.runs.update(run_index, |run| run.count += count);
+        } else if offset > 0 {
+            // split the run around the new one
+            let run = self.runs.get(run_index).expect("index is in a run");
+            let rest = Run {
+                value: run.value.clone(),
+                count: run.count - offset,
+            };
+            self.runs.update(run_index, |run| run.count = offset);
+            let _ = self.runs.insert(run_index + 1, rest);
+            let _ = self.runs.insert(
+                run_index + 1,
+                Run {
+                    value: element,
+                    count,
+                },
+            );
+        } else if matches!(before, Some(run) if run.value == element) {
+            self.runs.update(run_index - 1, |run| run.count += count);
+        } else {
+            let _ = self.runs.insert(
+                run_index,
+                Run {
+                    value: element,
+                    count,
+                },
+            );
+        }
+        Ok(())
+    }
+
+    /// Push the `element` onto the back of the list.
+    pub fn push_back(&mut self, element: T) {
+        let _ = self.insert(self.len(), element);
+    }
+
+    /// Remove the element at `index` from the list, returning it if the index was in the list.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let (run_index, _) = self.locate(index)?;
+        let run = self.runs.get(run_index)?;
+        if run.count > 1 {
+            let element = run.value.clone();
+            self.runs.update(run_index, |run| run.count -= 1);
+            return Some(element);
+        }
+        let run = self.runs.remove(run_index)?;
+        // the runs either side may now be next to each other
+        if run_index > 0 {
+            let joins = match (self.runs.get(run_index - 1), self.runs.get(run_index)) {
+                (Some(before), Some(after)) => before.value == after.value,
+                _ => false,
+            };
+            if joins {
+                let after = self.runs.remove(run_index)?;
+                self.runs
+                    .update(run_index - 1, |run| run.count += after.count);
+            }
+        }
+        Some(run.value)
+    }
+
+    /// Remove the last element of the list, returning it if the list wasn't empty.
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.remove(self.len().checked_sub(1)?)
+    }
+
+    /// Replace the element at `index` with `element`, returning the old element, or returning
+    /// `element` back if the index is not in the list.
+    pub fn set(&mut self, index: usize, element: T) -> Result<T, T> {
+        match self.remove(index) {
+            Some(old) => {
+                self.insert(index, element)?;
+                Ok(old)
+            }
+            None => Err(element),
+        }
+    }
+}
+
+impl<T, const B: usize> Clone for RleList<T, B>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            runs: self.runs.clone(),
+        }
+    }
+}
+
+impl<T, const B: usize> Default for RleList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const B: usize> fmt::Debug for RleList<T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const B: usize> PartialEq for RleList<T, B>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, const B: usize> Eq for RleList<T, B> where T: Eq {}
+
+impl<T, const B: usize> Extend<T> for RleList<T, B>
+where
+    T: Clone + PartialEq,
+{
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for element in iter {
+            self.push_back(element);
+        }
+    }
+}
+
+impl<T, const B: usize> FromIterator<T> for RleList<T, B>
+where
+    T: Clone + PartialEq,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a RleList<T, B> {
+    type Item = &'a T;
+
+    type IntoIter = RleIter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the runs in an [`RleList`].
+#[derive(Debug, Clone)]
+pub struct Runs<'a, T, const B: usize> {
+    runs: AugmentedIter<'a, Run<T>, RunLength, B>,
+}
+
+impl<'a, T, const B: usize> Iterator for Runs<'a, T, B> {
+    type Item = (&'a T, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runs.next().map(|run| (&run.value, run.count))
+    }
+}
+
+impl<'a, T, const B: usize> DoubleEndedIterator for Runs<'a, T, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.runs.next_back().map(|run| (&run.value, run.count))
+    }
+}
+
+/// An iterator over items in an [`RleList`].
+#[derive(Debug, Clone)]
+pub struct RleIter<'a, T, const B: usize> {
+    runs: Runs<'a, T, B>,
+    current: Option<(&'a T, usize)>,
+}
+
+impl<'a, T, const B: usize> Iterator for RleIter<'a, T, B> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.current.as_mut() {
+                Some((value, count)) if *count > 0 => {
+                    *count -= 1;
+                    return Some(*value);
+                }
+                _ => self.current = Some(self.runs.next()?),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::TestRng;
+
+    fn assert_runs<T, const B: usize>(list: &RleList<T, B>)
+    where
+        T: PartialEq,
+    {
+        let runs = list.runs().collect::<Vec<_>>();
+        assert!(runs.iter().all(|(_, count)| *count > 0));
+        assert!(runs.windows(2).all(|w| w[0].0 != w[1].0));
+    }
+
+    #[test]
+    fn runs() {
+        let mut t = RleList::<u8, 2>::new();
+        let mut v = Vec::new();
+        let mut rng = TestRng::new(17);
+        for _ in 0..3000 {
+            let r = rng.next();
+            let value = (r % 3) as u8;
+            match (v.len(), (r >> 4) % 6) {
+                (0, _) | (_, 0..=1) => {
+                    let index = (r >> 8) % (v.len() + 1);
+                    t.insert(index, value).unwrap();
+                    v.insert(index, value);
+                }
+                (_, 2) => {
+                    let index = (r >> 8) % (v.len() + 1);
+                    let count = (r >> 16) % 20;
+                    t.insert_run(index, value, count).unwrap();
+                    v.splice(index..index, vec![value; count]);
+                }
+                (len, 3) => {
+                    let index = (r >> 8) % len;
+                    assert_eq!(t.set(index, value), Ok(v[index]));
+                    v[index] = value;
+                }
+                (len, _) => {
+                    let index = (r >> 8) % len;
+                    assert_eq!(t.remove(index), Some(v.remove(index)));
+                }
+            }
+            assert_runs(&t);
+            assert_eq!(t.len(), v.len());
+            let index = r % (v.len() + 1);
+            assert_eq!(t.get(index), v.get(index));
+        }
+        assert!(t.iter().eq(v.iter()));
+        assert_eq!(t.insert(v.len() + 1, 0), Err(0));
+        assert_eq!(t.set(v.len(), 0), Err(0));
+        while let Some(element) = t.pop_back() {
+            assert_eq!(Some(element), v.pop());
+        }
+        assert!(t.is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{SeqAccess, Visitor},
+    ser::SerializeSeq,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::BTreeList;
+
+/// Serializes the elements as a sequence, as for [`Vec`].
+impl<T, const B: usize> Serialize for BTreeList<T, B>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        self.try_for_each_chunk(|chunk| {
+            chunk
+                .iter()
+                .try_for_each(|element| seq.serialize_element(element))
+        })?;
+        seq.end()
+    }
+}
+
+/// Deserializes a sequence of elements, building the tree bottom-up once they are all read
+/// rather than inserting them one at a time.
+impl<'de, T, const B: usize> Deserialize<'de> for BTreeList<T, B>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ListVisitor(PhantomData))
+    }
+}
+
+struct ListVisitor<T, const B: usize>(PhantomData<BTreeList<T, B>>);
+
+impl<'de, T, const B: usize> Visitor<'de> for ListVisitor<T, B>
+where
+    T: Deserialize<'de>,
+{
+    type Value = BTreeList<T, B>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a sequence")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        // don't trust the hint too far, it comes from the input
+        let mut elements = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+        let len = elements.len();
+        Ok(BTreeList::from_exact_iter(elements.into_iter(), len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::assert_shape;
+
+    #[test]
+    fn serde_round_trip() {
+        for len in [0, 1, 10, 1000] {
+            let t = BTreeList::<usize, 3>::from_exact_iter(0..len, len);
+            let json = serde_json::to_string(&t).unwrap();
+            assert_eq!(
+                json,
+                serde_json::to_string(&(0..len).collect::<Vec<_>>()).unwrap()
+            );
+            let back: BTreeList<usize, 3> = serde_json::from_str(&json).unwrap();
+            assert_shape(&back);
+            assert_eq!(back, t);
+        }
+        assert!(serde_json::from_str::<BTreeList<usize>>("[1, \"a\"]").is_err());
+    }
+}
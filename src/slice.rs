@@ -0,0 +1,781 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    iter, mem,
+    ops::{Bound, Range, RangeBounds},
+    slice, vec,
+};
+
+use crate::{BTreeList, Iter, ListDelta};
+
+/// A borrowed view of a range of a [`BTreeList`], created by [`slice`](BTreeList::slice).
+///
+/// The view is just the list and the range, so creating and cloning it takes `O(1)` time.
+/// Indices into the view are relative to the start of its range. Views compare, order and hash
+/// by their elements like lists do, so a view can stand in for a list where a part of one is
+/// needed.
+///
+/// ```
+/// # use btreelist::btreelist;
+/// let list = btreelist![1, 2, 3, 4, 5];
+/// let middle = list.slice(1..4).unwrap();
+/// assert_eq!(middle.len(), 3);
+/// assert_eq!(middle.get(0), Some(&2));
+/// assert_eq!(middle.last(), Some(&4));
+/// assert_eq!(middle, [2, 3, 4][..]);
+/// assert!(middle.iter().copied().eq(2..5));
+/// ```
+pub struct BTreeSlice<'a, T, const B: usize = 6> {
+    list: &'a BTreeList<T, B>,
+    range: Range<usize>,
+}
+
+impl<T, const B: usize> BTreeList<T, B> {
+    /// Create a borrowed view of the elements in `range`, returning `None` if it is out of
+    /// bounds.
+    pub fn slice<R>(&self, range: R) -> Option<BTreeSlice<'_, T, B>>
+    where
+        R: RangeBounds<usize>,
+    {
+        Some(BTreeSlice {
+            list: self,
+            range: self.resolve_range(range)?,
+        })
+    }
+
+    /// Create a mutable view of the elements in `range`, returning `None` if it is out of bounds.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4, 5];
+    /// let mut view = list.slice_mut(1..4).unwrap();
+    /// for element in view.iter_mut() {
+    ///     *element *= 10;
+    /// }
+    /// assert_eq!(list, btreelist![1, 20, 30, 40, 5]);
+    /// ```
+    pub fn slice_mut<R>(&mut self, range: R) -> Option<BTreeSliceMut<'_, T, B>>
+    where
+        R: RangeBounds<usize>,
+    {
+        let range = self.resolve_range(range)?;
+        Some(BTreeSliceMut::new(self.range_chunks_mut(range)))
+    }
+
+    /// Split the list into two disjoint mutable views at `mid`, returning `None` if it is past
+    /// the end of the list.
+    ///
+    /// The views can be edited at the same time, such as by handing them to different threads.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4];
+    /// let (mut left, mut right) = list.split_at_mut(2).unwrap();
+    /// for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+    ///     std::mem::swap(l, r);
+    /// }
+    /// assert_eq!(list, btreelist![3, 4, 1, 2]);
+    /// ```
+    pub fn split_at_mut(
+        &mut self,
+        mid: usize,
+    ) -> Option<(BTreeSliceMut<'_, T, B>, BTreeSliceMut<'_, T, B>)> {
+        self.slice_mut(..)?.split_at_mut(mid)
+    }
+
+    /// Create mutable views of several ranges at once, returning `None` if any range is out of
+    /// bounds or two of them overlap.
+    ///
+    /// The views are cut from the runs of elements covering all of the ranges, gathered in one
+    /// walk over the list.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4, 5];
+    /// let [mut front, mut back] = list.try_get_disjoint_ranges_mut([0..2, 3..5]).unwrap();
+    /// for (a, b) in front.iter_mut().zip(back.iter_mut()) {
+    ///     std::mem::swap(a, b);
+    /// }
+    /// assert_eq!(list, btreelist![4, 5, 3, 1, 2]);
+    /// assert!(list.try_get_disjoint_ranges_mut([0..2, 1..3]).is_none());
+    /// ```
+    pub fn try_get_disjoint_ranges_mut<R, const N: usize>(
+        &mut self,
+        ranges: [R; N],
+    ) -> Option<[BTreeSliceMut<'_, T, B>; N]>
+    where
+        R: RangeBounds<usize>,
+    {
+        let mut resolved = Vec::with_capacity(N);
+        for range in ranges {
+            resolved.push(self.resolve_range(range)?);
+        }
+        // empty ranges hold no elements so can't overlap anything
+        let mut order = (0..N)
+            .filter(|&i| !resolved[i].is_empty())
+            .collect::<Vec<_>>();
+        order.sort_unstable_by_key(|&i| resolved[i].start);
+        if order
+            .windows(2)
+            .any(|pair| resolved[pair[0]].end > resolved[pair[1]].start)
+        {
+            return None;
+        }
+
+        let mut out = [(); N].map(|_| BTreeSliceMut::new(Vec::new()));
+        if let (Some(&first), Some(&last)) = (order.first(), order.last()) {
+            let span = resolved[first].start..resolved[last].end;
+            let mut position = span.start;
+            let mut rest = BTreeSliceMut::new(self.range_chunks_mut(span));
+            for i in order {
+                let range = &resolved[i];
+                let (_, after) = rest
+                    .split_at_mut(range.start - position)
+                    .expect("the ranges are in order within the span");
+                let (view, after) = after
+                    .split_at_mut(range.len())
+                    .expect("the ranges are in order within the span");
+                out[i] = view;
+                rest = after;
+                position = range.end;
+            }
+        }
+        Some(out)
+    }
+}
+
+impl<'a, T, const B: usize> BTreeSlice<'a, T, B> {
+    /// Get the number of elements in the view.
+    pub fn len(&self) -> usize {
+        self.range.len()
+    }
+
+    /// Check if the view is empty.
+    pub fn is_empty(&self) -> bool {
+        self.range.is_empty()
+    }
+
+    /// The range of the list the view covers.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Create an iterator through the view's elements.
+    pub fn iter(&self) -> Iter<'a, T, B> {
+        Iter {
+            inner: self.list,
+            index: self.range.start,
+            index_back: self.range.end,
+        }
+    }
+
+    /// Get the element at `index` in the view, if it is in the view.
+    pub fn get(&self, index: usize) -> Option<&'a T> {
+        if index < self.len() {
+            self.list.get(self.range.start + index)
+        } else {
+            None
+        }
+    }
+
+    /// Get the first element of the view, if it isn't empty.
+    pub fn first(&self) -> Option<&'a T> {
+        self.get(0)
+    }
+
+    /// Get the last element of the view, if it isn't empty.
+    pub fn last(&self) -> Option<&'a T> {
+        self.get(self.len().checked_sub(1)?)
+    }
+
+    /// Create a view of the elements in `range` within this view, returning `None` if it is out
+    /// of bounds.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 4, 5];
+    /// let view = list.slice(1..).unwrap();
+    /// assert_eq!(view.slice(..2).unwrap(), [2, 3][..]);
+    /// assert!(view.slice(..5).is_none());
+    /// ```
+    pub fn slice<R>(&self, range: R) -> Option<Self>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || end > self.len() {
+            return None;
+        }
+        let range = start..end;
+        Some(Self {
+            list: self.list,
+            range: self.range.start + range.start..self.range.start + range.end,
+        })
+    }
+
+    /// Split the view in two at `mid`, returning `None` if it is past the end of the view.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let list = btreelist![1, 2, 3, 4, 5];
+    /// let (left, right) = list.slice(..).unwrap().split_at(2).unwrap();
+    /// assert_eq!(left, [1, 2][..]);
+    /// assert_eq!(right, [3, 4, 5][..]);
+    /// ```
+    pub fn split_at(&self, mid: usize) -> Option<(Self, Self)> {
+        Some((self.slice(..mid)?, self.slice(mid..)?))
+    }
+
+    /// Record the range of the view in an owned [`RangeDescriptor`], which can be held across
+    /// edits to the list and resolved back into a view afterwards.
+    pub fn descriptor(&self) -> RangeDescriptor {
+        RangeDescriptor {
+            range: self.range.clone(),
+            list_len: self.list.len(),
+        }
+    }
+
+    /// Copy the elements of the view into a new list.
+    pub fn to_list(&self) -> BTreeList<T, B>
+    where
+        T: Clone,
+    {
+        BTreeList::from_exact_iter(self.iter().cloned(), self.len())
+    }
+}
+
+impl<'a, T, const B: usize> Clone for BTreeSlice<'a, T, B> {
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list,
+            range: self.range.clone(),
+        }
+    }
+}
+
+impl<'a, T, const B: usize> From<&'a BTreeList<T, B>> for BTreeSlice<'a, T, B> {
+    fn from(list: &'a BTreeList<T, B>) -> Self {
+        Self {
+            list,
+            range: 0..list.len(),
+        }
+    }
+}
+
+impl<'a, T, const B: usize> fmt::Debug for BTreeSlice<'a, T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, 'b, T, U, const B: usize, const C: usize> PartialEq<BTreeSlice<'b, U, C>>
+    for BTreeSlice<'a, T, B>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &BTreeSlice<'b, U, C>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<'a, T, U, const B: usize, const C: usize> PartialEq<BTreeList<U, C>> for BTreeSlice<'a, T, B>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &BTreeList<U, C>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<'a, T, U, const B: usize, const C: usize> PartialEq<BTreeSlice<'a, U, C>> for BTreeList<T, B>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &BTreeSlice<'a, U, C>) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<'a, T, U, const B: usize> PartialEq<[U]> for BTreeSlice<'a, T, B>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &[U]) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<'a, T, const B: usize> Eq for BTreeSlice<'a, T, B> where T: Eq {}
+
+/// Compares the views lexicographically, as for slices.
+impl<'a, T, const B: usize> PartialOrd for BTreeSlice<'a, T, B>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.iter().partial_cmp(other.iter())
+    }
+}
+
+impl<'a, T, const B: usize> Ord for BTreeSlice<'a, T, B>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.iter().cmp(other.iter())
+    }
+}
+
+/// Hashes like a [`BTreeList`] of the same elements.
+impl<'a, T, const B: usize> Hash for BTreeSlice<'a, T, B>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_usize(self.len());
+        for element in self.iter() {
+            element.hash(state);
+        }
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for BTreeSlice<'a, T, B> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &BTreeSlice<'a, T, B> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An owned record of the range of a [`BTreeSlice`], which can be carried across edits to the
+/// list and resolved back into a view afterwards, created by
+/// [`descriptor`](BTreeSlice::descriptor).
+///
+/// The descriptor is stamped with the length of the list it describes. Edits are brought in by
+/// [`rebase`](Self::rebase)ing the descriptor over the [`ListDelta`] they made, which moves the
+/// range as for [`ListDelta::rebase_bookmarks`]: elements inserted inside the range or at its end
+/// join it, those inserted at its start stay outside, and deleting elements in the range shrinks
+/// it. [`resolve`](Self::resolve) refuses lists whose length doesn't match the stamp, catching
+/// edits that weren't rebased over unless they happened to leave the length the same.
+///
+/// ```
+/// # use btreelist::{btreelist, ListDelta};
+/// let mut list = btreelist!['a', 'b', 'c', 'd'];
+/// let mut region = list.slice(1..3).unwrap().descriptor();
+///
+/// let mut delta = ListDelta::new();
+/// delta.insert(vec!['x', 'y']).retain(2).delete(vec!['c']);
+/// assert!(list.apply_delta(&delta));
+/// assert!(region.resolve(&list).is_none());
+///
+/// assert!(region.rebase(&delta));
+/// assert_eq!(region.range(), 3..4);
+/// assert_eq!(region.resolve(&list).unwrap(), ['b'][..]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeDescriptor {
+    range: Range<usize>,
+    list_len: usize,
+}
+
+impl RangeDescriptor {
+    /// The range of the list described.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The length of the list the range is in, which it has to match to be resolved.
+    pub fn list_len(&self) -> usize {
+        self.list_len
+    }
+
+    /// Move the range over the edits made by `delta`, returning false and leaving the descriptor
+    /// unchanged if the delta doesn't apply to a list of [`list_len`](Self::list_len) elements.
+    ///
+    /// This takes `O(d)` time for a delta of `d` steps.
+    pub fn rebase<T>(&mut self, delta: &ListDelta<T>) -> bool {
+        if delta.base_len() > self.list_len {
+            return false;
+        }
+        let mut bounds = [self.range.start, self.range.end];
+        delta.rebase_bookmarks(&mut bounds);
+        let [start, end] = bounds;
+        self.range = start..end;
+        self.list_len = self.list_len - delta.base_len() + delta.target_len();
+        true
+    }
+
+    /// Resolve the range against `list` to view it again, returning `None` if the list's length
+    /// doesn't match the one the descriptor was stamped with.
+    pub fn resolve<'a, T, const B: usize>(
+        &self,
+        list: &'a BTreeList<T, B>,
+    ) -> Option<BTreeSlice<'a, T, B>> {
+        if list.len() != self.list_len {
+            return None;
+        }
+        list.slice(self.range.clone())
+    }
+}
+
+/// A mutable view of a range of a [`BTreeList`], created by [`slice_mut`](BTreeList::slice_mut),
+/// [`split_at_mut`](BTreeList::split_at_mut) or
+/// [`try_get_disjoint_ranges_mut`](BTreeList::try_get_disjoint_ranges_mut).
+///
+/// The view holds the contiguous runs of elements in the nodes covering its range, gathered in
+/// one walk over the range when it is created, so creating a view of `k` elements takes
+/// `O(k / B + log(n))` time and space. Indexing into it takes `O(log(k / B))` time, and it can be
+/// split into disjoint views without any unsafe code. Indices into the view are relative to the
+/// start of its range.
+pub struct BTreeSliceMut<'a, T, const B: usize = 6> {
+    chunks: Vec<&'a mut [T]>,
+    /// The index of the first element of each chunk.
+    starts: Vec<usize>,
+    len: usize,
+}
+
+impl<'a, T, const B: usize> BTreeSliceMut<'a, T, B> {
+    /// View the non-empty `chunks` of elements as one range, in order.
+    fn new(chunks: Vec<&'a mut [T]>) -> Self {
+        let mut starts = Vec::with_capacity(chunks.len());
+        let mut len = 0;
+        for chunk in &chunks {
+            starts.push(len);
+            len += chunk.len();
+        }
+        Self {
+            chunks,
+            starts,
+            len,
+        }
+    }
+
+    /// Find the chunk holding `index`, which must be in the view, and the offset into it.
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let chunk = self.starts.partition_point(|&start| start <= index) - 1;
+        (chunk, index - self.starts[chunk])
+    }
+
+    /// Get the number of elements in the view.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the view is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Create an iterator through the view's elements.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+
+    /// Create an iterator through the view's elements, allowing them to be changed.
+    pub fn iter_mut(&mut self) -> SliceIterMut<'_, 'a, T> {
+        SliceIterMut {
+            inner: self
+                .chunks
+                .iter_mut()
+                .flat_map(chunk_iter_mut as fn(_) -> _),
+        }
+    }
+
+    /// Get the element at `index` in the view, if it is in the view.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if index >= self.len {
+            return None;
+        }
+        let (chunk, offset) = self.locate(index);
+        self.chunks[chunk].get(offset)
+    }
+
+    /// Get the element at `index` in the view mutably, if it is in the view.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        if index >= self.len {
+            return None;
+        }
+        let (chunk, offset) = self.locate(index);
+        self.chunks[chunk].get_mut(offset)
+    }
+
+    /// Swap the elements at `a` and `b` in the view, returning whether both were in the view.
+    pub fn swap(&mut self, a: usize, b: usize) -> bool {
+        if a >= self.len || b >= self.len {
+            return false;
+        }
+        let (low, high) = (self.locate(a.min(b)), self.locate(a.max(b)));
+        if low.0 == high.0 {
+            self.chunks[low.0].swap(low.1, high.1);
+        } else {
+            let (left, right) = self.chunks.split_at_mut(high.0);
+            mem::swap(&mut left[low.0][low.1], &mut right[0][high.1]);
+        }
+        true
+    }
+
+    /// Split the view in two at `mid`, returning `None` if it is past the end of the view.
+    ///
+    /// This takes `O(k / B)` time for the `k` elements in the view.
+    ///
+    /// ```
+    /// # use btreelist::btreelist;
+    /// let mut list = btreelist![1, 2, 3, 4, 5];
+    /// let view = list.slice_mut(1..).unwrap();
+    /// let (mut left, mut right) = view.split_at_mut(1).unwrap();
+    /// std::mem::swap(left.get_mut(0).unwrap(), right.get_mut(2).unwrap());
+    /// assert_eq!(list, btreelist![1, 5, 3, 4, 2]);
+    /// ```
+    pub fn split_at_mut(mut self, mid: usize) -> Option<(Self, Self)> {
+        if mid > self.len {
+            return None;
+        }
+        if mid == self.len {
+            return Some((self, Self::new(Vec::new())));
+        }
+        let (chunk, offset) = self.locate(mid);
+        let mut right = self.chunks.split_off(chunk);
+        let (head, tail) = mem::take(&mut right[0]).split_at_mut(offset);
+        right[0] = tail;
+        let mut left = self.chunks;
+        if !head.is_empty() {
+            left.push(head);
+        }
+        Some((Self::new(left), Self::new(right)))
+    }
+}
+
+impl<'a, T, const B: usize> fmt::Debug for BTreeSliceMut<'a, T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, U, const B: usize> PartialEq<[U]> for BTreeSliceMut<'a, T, B>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &[U]) -> bool {
+        self.len() == other.len() && self.iter().zip(other.iter()).all(|(a, b)| a == b)
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for BTreeSliceMut<'a, T, B> {
+    type Item = &'a mut T;
+
+    type IntoIter = iter::Flatten<vec::IntoIter<&'a mut [T]>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.chunks.into_iter().flatten()
+    }
+}
+
+/// The function [`SliceIterMut`] iterates over each chunk of a [`BTreeSliceMut`] with.
+type ChunkIterMut<'b, 'a, T> = fn(&'b mut &'a mut [T]) -> slice::IterMut<'b, T>;
+
+/// Iterate over the elements of a chunk of a [`BTreeSliceMut`].
+fn chunk_iter_mut<'b, T>(chunk: &'b mut &mut [T]) -> slice::IterMut<'b, T> {
+    chunk.iter_mut()
+}
+
+/// An iterator over mutable references to the items in a [`BTreeSliceMut`].
+#[derive(Debug)]
+pub struct SliceIterMut<'b, 'a, T> {
+    inner: iter::FlatMap<
+        slice::IterMut<'b, &'a mut [T]>,
+        slice::IterMut<'b, T>,
+        ChunkIterMut<'b, 'a, T>,
+    >,
+}
+
+impl<'b, 'a, T> Iterator for SliceIterMut<'b, 'a, T> {
+    type Item = &'b mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+impl<'b, 'a, T> DoubleEndedIterator for SliceIterMut<'b, 'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist;
+
+    #[test]
+    fn slice() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash_of<H: Hash>(value: &H) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let v = (0..300).map(|i| (i * 7) % 13).collect::<Vec<usize>>();
+        let t = BTreeList::<usize, 2>::from_exact_iter(v.iter().copied(), v.len());
+        assert!(t.slice(..=300).is_none());
+        assert_eq!(t.slice(..).unwrap(), t);
+        for start in (0..=300).step_by(37) {
+            for end in (start..=300).step_by(23) {
+                let view = t.slice(start..end).unwrap();
+                assert_eq!(view, v[start..end]);
+                assert_eq!(view.first(), v[start..end].first());
+                assert_eq!(view.last(), v[start..end].last());
+                assert_eq!(view.get(end - start), None);
+                assert_eq!(hash_of(&view), hash_of(&view.to_list()));
+                assert_eq!(view.to_list(), v[start..end]);
+                let mid = (end - start) / 3;
+                let (left, right) = view.split_at(mid).unwrap();
+                assert_eq!(left, v[start..start + mid]);
+                assert_eq!(right, v[start + mid..end]);
+                assert!(view.split_at(end - start + 1).is_none());
+                let other = t.slice(end..).unwrap();
+                assert_eq!(view.cmp(&other), v[start..end].cmp(&v[end..]));
+            }
+        }
+    }
+
+    #[test]
+    fn split_at_mut() {
+        let mut t = BTreeList::<usize, 2>::from_exact_iter(0..200, 200);
+        let mut v = (0..200).collect::<Vec<usize>>();
+        for mid in (0..=200).step_by(13) {
+            let (mut left, mut right) = t.split_at_mut(mid).unwrap();
+            let (l, r) = v.split_at_mut(mid);
+            assert_eq!(left, *l);
+            assert_eq!(right, *r);
+            for (a, b) in left.iter_mut().rev().zip(right.iter_mut()) {
+                std::mem::swap(a, b);
+            }
+            for (a, b) in l.iter_mut().rev().zip(r.iter_mut()) {
+                std::mem::swap(a, b);
+            }
+            assert!(right.swap(0, right.len().saturating_sub(1)) || r.is_empty());
+            if let Some(last) = r.len().checked_sub(1) {
+                r.swap(0, last);
+            }
+        }
+        assert_eq!(t, v);
+        assert!(t.split_at_mut(201).is_none());
+        let view = t.slice_mut(50..150).unwrap();
+        assert!(view.split_at_mut(101).is_none());
+
+        // views split from views, which start and end partway through nodes
+        for (start, mid) in [(0, 0), (3, 1), (17, 40), (50, 100), (199, 1), (200, 0)] {
+            let view = t.slice_mut(start..).unwrap();
+            let (mut left, right) = view.split_at_mut(mid).unwrap();
+            let (l, r) = v[start..].split_at_mut(mid);
+            for i in 0..=l.len() {
+                assert_eq!(left.get_mut(i), l.get_mut(i));
+            }
+            for i in 0..=r.len() {
+                assert_eq!(right.get(i), r.get(i));
+            }
+            assert!(left.into_iter().eq(l.iter_mut()));
+            assert!(right.into_iter().rev().eq(r.iter_mut().rev()));
+        }
+    }
+
+    #[test]
+    fn range_descriptor() {
+        let mut list = (0..10).collect::<BTreeList<usize, 2>>();
+        let mut region = list.slice(3..6).unwrap().descriptor();
+        assert_eq!(region.list_len(), 10);
+
+        // a delta for a longer list doesn't apply
+        let mut too_long = ListDelta::<usize>::new();
+        too_long.retain(11);
+        assert!(!region.rebase(&too_long));
+        assert_eq!(region.range(), 3..6);
+
+        // inserting at the end joins the range, and deleting its start shrinks it
+        let mut delta = ListDelta::new();
+        delta
+            .retain(2)
+            .delete(vec![2, 3])
+            .retain(2)
+            .insert(vec![20]);
+        assert!(list.apply_delta(&delta));
+        assert!(region.rebase(&delta));
+        assert_eq!(region.list_len(), 9);
+        assert_eq!(region.resolve(&list).unwrap(), [4, 5, 20][..]);
+
+        // deleting the whole range leaves it empty where it was
+        let mut delta = ListDelta::new();
+        delta.retain(1).delete(vec![1, 4, 5, 20, 6]);
+        assert!(list.apply_delta(&delta));
+        assert!(region.rebase(&delta));
+        assert_eq!(region.range(), 1..1);
+        assert_eq!(list, btreelist![0, 7, 8, 9]);
+        assert!(region.resolve(&list).unwrap().is_empty());
+    }
+
+    #[test]
+    fn try_get_disjoint_ranges_mut() {
+        let mut t = BTreeList::<usize, 2>::from_exact_iter(0..100, 100);
+        let [a, mut b, c] = t
+            .try_get_disjoint_ranges_mut([50..60, 10..20, 30..30])
+            .unwrap();
+        assert!(a.iter().copied().eq(50..60));
+        assert!(b.iter().copied().eq(10..20));
+        assert!(c.is_empty());
+        assert!(b.swap(0, 9));
+        *b.get_mut(5).unwrap() = 1000;
+        let (_, mut tail) = b.split_at_mut(8).unwrap();
+        *tail.get_mut(0).unwrap() = 2000;
+        assert_eq!(
+            t.iter().skip(10).take(10).copied().collect::<Vec<_>>(),
+            [19, 11, 12, 13, 14, 1000, 16, 17, 2000, 10]
+        );
+
+        let [a, b] = t.try_get_disjoint_ranges_mut([50..60, 10..20]).unwrap();
+        for (x, y) in a.into_iter().zip(b) {
+            *y = *x;
+        }
+        assert!(t.iter().skip(10).take(10).copied().eq(50..60));
+
+        assert!(t.try_get_disjoint_ranges_mut([0..10, 9..11]).is_none());
+        assert!(t.try_get_disjoint_ranges_mut([0..10, 95..101]).is_none());
+        assert!(t.try_get_disjoint_ranges_mut([0..10, 10..20]).is_some());
+        assert!(t.try_get_disjoint_ranges_mut([5..5, 0..10]).is_some());
+        let [all] = t.try_get_disjoint_ranges_mut([..]).unwrap();
+        assert_eq!(all.len(), 100);
+        let mut empty = BTreeList::<usize>::new();
+        let [view] = empty.try_get_disjoint_ranges_mut([..]).unwrap();
+        assert!(view.is_empty());
+    }
+}
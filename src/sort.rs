@@ -0,0 +1,266 @@
+use std::{cmp::Ordering, mem};
+
+/// The elements of a list seen as one sequence through the contiguous runs of elements in its
+/// nodes, for reordering them in place.
+///
+/// Finding the position of an index takes `O(log(r))` time for `r` runs, while stepping to a
+/// neighbouring position takes `O(1)`, so the partitions scan from their ends.
+pub(crate) struct NodeRuns<'a, T> {
+    runs: Vec<&'a mut [T]>,
+    /// The index of the first element of each run.
+    starts: Vec<usize>,
+    len: usize,
+}
+
+/// The position of an element in [`NodeRuns`], as the run it is in and its offset into that run.
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    run: usize,
+    offset: usize,
+}
+
+impl<'a, T> NodeRuns<'a, T> {
+    /// View the non-empty `runs` of elements as one sequence, in order.
+    pub(crate) fn new(runs: Vec<&'a mut [T]>) -> Self {
+        let mut starts = Vec::with_capacity(runs.len());
+        let mut len = 0;
+        for run in &runs {
+            starts.push(len);
+            len += run.len();
+        }
+        Self { runs, starts, len }
+    }
+
+    /// Sort the elements with heapsort's fallback on poor pivots, as for
+    /// [`slice::sort_unstable_by`].
+    ///
+    /// Partitions spanning several runs are split by quicksort, and each partition that ends up
+    /// within a single run is sorted there directly.
+    pub(crate) fn sort_by<F>(&mut self, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let limit = 2 * (usize::BITS - self.len.leading_zeros());
+        self.quicksort(0, self.len, limit, compare);
+    }
+
+    /// Reorder the elements so that the one at `index` is in its sorted position, with no greater
+    /// elements before it and no lesser elements after it.
+    pub(crate) fn select_nth_by<F>(&mut self, index: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (mut lo, mut hi) = (0, self.len);
+        // fall back to heapsort if the pivots keep being poor
+        let mut limit = 2 * (usize::BITS - self.len.leading_zeros());
+        while hi - lo > 1 {
+            let (first, last) = (self.locate(lo), self.locate(hi - 1));
+            if first.run == last.run {
+                self.runs[first.run][first.offset..=last.offset]
+                    .select_nth_unstable_by(index - lo, |a, b| compare(a, b));
+                return;
+            }
+            if limit == 0 {
+                self.heapsort(lo, hi, compare);
+                return;
+            }
+            limit -= 1;
+
+            let pivot = self.partition(lo, hi, compare);
+            match pivot.cmp(&index) {
+                Ordering::Equal => return,
+                Ordering::Less => lo = pivot + 1,
+                Ordering::Greater => hi = pivot,
+            }
+        }
+    }
+
+    /// Sort the elements in `lo..hi`, falling back to heapsort once `limit` partitions have been
+    /// made.
+    fn quicksort<F>(&mut self, mut lo: usize, mut hi: usize, mut limit: u32, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        while hi - lo > 1 {
+            let (first, last) = (self.locate(lo), self.locate(hi - 1));
+            if first.run == last.run {
+                self.runs[first.run][first.offset..=last.offset]
+                    .sort_unstable_by(|a, b| compare(a, b));
+                return;
+            }
+            if limit == 0 {
+                self.heapsort(lo, hi, compare);
+                return;
+            }
+            limit -= 1;
+
+            // recurse into the smaller side to keep the stack shallow
+            let pivot = self.partition(lo, hi, compare);
+            if pivot - lo < hi - pivot {
+                self.quicksort(lo, pivot, limit, compare);
+                lo = pivot + 1;
+            } else {
+                self.quicksort(pivot + 1, hi, limit, compare);
+                hi = pivot;
+            }
+        }
+    }
+
+    /// Partition the elements in `lo..hi` around the median of their first, middle and last
+    /// elements, returning the pivot's final index.
+    ///
+    /// Elements equal to the pivot stop the scans from both ends, so runs of equal elements are
+    /// split evenly.
+    fn partition<F>(&mut self, lo: usize, hi: usize, compare: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let pivot = self.median_of_three(lo, lo + (hi - lo) / 2, hi - 1, compare);
+        let pivot_position = self.locate(lo);
+        self.swap(pivot_position, self.locate(pivot));
+
+        // `left` is the position of index `l`, and `right` the position of index `r - 1`
+        let (mut l, mut r) = (lo + 1, hi);
+        let mut left = self.next(pivot_position);
+        let mut right = self.locate(hi - 1);
+        loop {
+            while l < r && compare(self.get(left), self.get(pivot_position)) == Ordering::Less {
+                l += 1;
+                left = self.next(left);
+            }
+            while l < r && compare(self.get(pivot_position), self.get(right)) == Ordering::Less {
+                r -= 1;
+                right = self.prev(right);
+            }
+            if l >= r {
+                break;
+            }
+            self.swap(left, right);
+            l += 1;
+            left = self.next(left);
+            r -= 1;
+            right = self.prev(right);
+        }
+        self.swap(pivot_position, right);
+        r - 1
+    }
+
+    /// Return whichever of the indices `a`, `b` and `c` holds the median element.
+    fn median_of_three<F>(&self, a: usize, b: usize, c: usize, compare: &mut F) -> usize
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let (a, b) = if self.less(b, a, compare) {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        if self.less(c, a, compare) {
+            a
+        } else if self.less(c, b, compare) {
+            c
+        } else {
+            b
+        }
+    }
+
+    /// Sort the elements in `lo..hi` with heapsort.
+    fn heapsort<F>(&mut self, lo: usize, hi: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let len = hi - lo;
+        for node in (0..len / 2).rev() {
+            self.sift_down(lo, node, len, compare);
+        }
+        for end in (1..len).rev() {
+            self.swap(self.locate(lo), self.locate(lo + end));
+            self.sift_down(lo, 0, end, compare);
+        }
+    }
+
+    /// Restore the max-heap property for the subtree at `node` of the heap starting at index
+    /// `lo`, considering only its first `end` elements.
+    fn sift_down<F>(&mut self, lo: usize, mut node: usize, end: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        loop {
+            let mut child = 2 * node + 1;
+            if child >= end {
+                return;
+            }
+            if child + 1 < end && self.less(lo + child, lo + child + 1, compare) {
+                child += 1;
+            }
+            if !self.less(lo + node, lo + child, compare) {
+                return;
+            }
+            self.swap(self.locate(lo + node), self.locate(lo + child));
+            node = child;
+        }
+    }
+
+    /// Whether the element at index `a` is less than the one at index `b`.
+    fn less<F>(&self, a: usize, b: usize, compare: &mut F) -> bool
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        compare(self.get(self.locate(a)), self.get(self.locate(b))) == Ordering::Less
+    }
+
+    /// The position of the element at `index`.
+    fn locate(&self, index: usize) -> Position {
+        let run = self.starts.partition_point(|&start| start <= index) - 1;
+        Position {
+            run,
+            offset: index - self.starts[run],
+        }
+    }
+
+    /// The position after `position`, or past the last run at the end.
+    fn next(&self, position: Position) -> Position {
+        if position.offset + 1 < self.runs[position.run].len() {
+            Position {
+                run: position.run,
+                offset: position.offset + 1,
+            }
+        } else {
+            Position {
+                run: position.run + 1,
+                offset: 0,
+            }
+        }
+    }
+
+    /// The position before `position`, which mustn't be the first.
+    fn prev(&self, position: Position) -> Position {
+        match position.offset.checked_sub(1) {
+            Some(offset) => Position {
+                run: position.run,
+                offset,
+            },
+            None => Position {
+                run: position.run - 1,
+                offset: self.runs[position.run - 1].len() - 1,
+            },
+        }
+    }
+
+    fn get(&self, position: Position) -> &T {
+        &self.runs[position.run][position.offset]
+    }
+
+    fn swap(&mut self, a: Position, b: Position) {
+        if a.run == b.run {
+            self.runs[a.run].swap(a.offset, b.offset);
+            return;
+        }
+        let (first, second) = if a.run < b.run { (a, b) } else { (b, a) };
+        let (before, after) = self.runs.split_at_mut(second.run);
+        mem::swap(
+            &mut before[first.run][first.offset],
+            &mut after[0][second.offset],
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use std::{fmt, iter};
+
+use crate::{BTreeList, Iter};
+
+/// A [`BTreeList`] whose elements are kept sorted and unique.
+///
+/// As the order is an invariant of the type rather than something to check, lookups go straight
+/// to a binary search and inserts only need to find their place, both in `O(log(n))` time.
+///
+/// ```
+/// # use btreelist::SortedList;
+/// let mut list = SortedList::<i32>::from_sorted_unique_iter(vec![1, 3, 5, 7]);
+/// assert_eq!(list.insert(4), Ok(2));
+/// assert_eq!(list.insert(5), Err(5));
+/// assert!(list.contains(&4));
+/// assert_eq!(list.iter().copied().collect::<Vec<_>>(), vec![1, 3, 4, 5, 7]);
+/// ```
+pub struct SortedList<T, const B: usize = 6> {
+    list: BTreeList<T, B>,
+}
+
+impl<T, const B: usize> SortedList<T, B> {
+    /// Construct a new, empty [`SortedList`].
+    ///
+    /// ```
+    /// # use btreelist::SortedList;
+    /// let list: SortedList<i32> = SortedList::new();
+    /// assert!(list.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            list: BTreeList::new(),
+        }
+    }
+
+    /// Get the number of elements in the list.
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    /// Check if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Create an iterator through the list's elements, in order.
+    pub fn iter(&self) -> Iter<'_, T, B> {
+        self.list.iter()
+    }
+
+    /// Get the element at `index`, if it is in the list.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.list.get(index)
+    }
+
+    /// Get the smallest element of the list, if it isn't empty.
+    pub fn first(&self) -> Option<&T> {
+        self.list.first()
+    }
+
+    /// Get the largest element of the list, if it isn't empty.
+    pub fn last(&self) -> Option<&T> {
+        self.list.last()
+    }
+
+    /// Remove and return the element at `index`, if it is in the list.
+    pub fn remove_index(&mut self, index: usize) -> Option<T> {
+        self.list.remove(index)
+    }
+
+    /// Borrow the underlying list, for the operations that don't change the order.
+    pub fn as_list(&self) -> &BTreeList<T, B> {
+        &self.list
+    }
+
+    /// Unwrap the underlying list.
+    pub fn into_list(self) -> BTreeList<T, B> {
+        self.list
+    }
+}
+
+impl<T, const B: usize> SortedList<T, B>
+where
+    T: Ord,
+{
+    /// Construct a list from an iterator of elements that are already sorted and unique, filling
+    /// the nodes directly in `O(n)` time.
+    ///
+    /// The order is trusted rather than checked, except in debug builds where out of order or
+    /// repeated elements panic as they are read.
+    ///
+    /// ```
+    /// # use btreelist::SortedList;
+    /// let list = SortedList::<i32>::from_sorted_unique_iter(vec![1, 3, 5, 7]);
+    /// assert_eq!(list.binary_search(&5), Ok(2));
+    /// ```
+    pub fn from_sorted_unique_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let iter = iter.into_iter();
+        let len = iter.len();
+        let mut iter = iter.peekable();
+        let checked = iter::from_fn(|| {
+            let element = iter.next()?;
+            debug_assert!(
+                !matches!(iter.peek(), Some(next) if *next <= element),
+                "elements should be sorted and unique"
+            );
+            Some(element)
+        });
+        Self {
+            list: BTreeList::from_exact_iter(checked, len),
+        }
+    }
+
+    /// Wrap a list, checking once in `O(n)` time that its elements are sorted and unique.
+    ///
+    /// Returns the list back if they aren't.
+    ///
+    /// ```
+    /// # use btreelist::{btreelist, SortedList};
+    /// assert!(SortedList::from_list(btreelist![1, 2, 3]).is_ok());
+    /// assert!(SortedList::from_list(btreelist![1, 1, 3]).is_err());
+    /// ```
+    pub fn from_list(list: BTreeList<T, B>) -> Result<Self, BTreeList<T, B>> {
+        let mut elements = list.elements();
+        let sorted = match elements.next() {
+            Some(mut previous) => elements.all(|element| {
+                let ordered = previous < element;
+                previous = element;
+                ordered
+            }),
+            None => true,
+        };
+        if sorted {
+            Ok(Self { list })
+        } else {
+            Err(list)
+        }
+    }
+
+    /// Search for `x`, returning `Ok` with its index if it is in the list, or `Err` with the index
+    /// it would be inserted at otherwise.
+    pub fn binary_search(&self, x: &T) -> Result<usize, usize> {
+        self.list.binary_search(x)
+    }
+
+    /// Check if the list contains `x`.
+    pub fn contains(&self, x: &T) -> bool {
+        self.binary_search(x).is_ok()
+    }
+
+    /// Insert `element` in its place, returning the index it was put at.
+    ///
+    /// Returns the element back if an equal one is already in the list.
+    pub fn insert(&mut self, element: T) -> Result<usize, T> {
+        match self.binary_search(&element) {
+            Ok(_) => Err(element),
+            Err(index) => {
+                self.list.insert(index, element)?;
+                Ok(index)
+            }
+        }
+    }
+
+    /// Remove and return the element equal to `x`, if there is one.
+    pub fn remove(&mut self, x: &T) -> Option<T> {
+        let index = self.binary_search(x).ok()?;
+        self.list.remove(index)
+    }
+}
+
+impl<T, const B: usize> Clone for SortedList<T, B>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            list: self.list.clone(),
+        }
+    }
+}
+
+impl<T, const B: usize> Default for SortedList<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const B: usize> fmt::Debug for SortedList<T, B>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, const B: usize> PartialEq for SortedList<T, B>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl<T, const B: usize> Eq for SortedList<T, B> where T: Eq {}
+
+impl<T, const B: usize> From<SortedList<T, B>> for BTreeList<T, B> {
+    fn from(list: SortedList<T, B>) -> Self {
+        list.into_list()
+    }
+}
+
+impl<'a, T, const B: usize> IntoIterator for &'a SortedList<T, B> {
+    type Item = &'a T;
+
+    type IntoIter = Iter<'a, T, B>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::TestRng;
+
+    #[test]
+    fn from_sorted_unique_iter() {
+        let t = SortedList::<usize, 3>::from_sorted_unique_iter((0..1000).map(|i| i * 3));
+        assert_eq!(t.len(), 1000);
+        assert!(t.as_list().is_sorted());
+        assert_eq!(t.binary_search(&300), Ok(100));
+        assert_eq!(t.binary_search(&301), Err(101));
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "sorted and unique")]
+    fn from_sorted_unique_iter_repeated() {
+        let _ = SortedList::<usize>::from_sorted_unique_iter(vec![1, 2, 2, 3]);
+    }
+
+    #[test]
+    fn from_list() {
+        let t = BTreeList::<usize, 2>::from_exact_iter(0..100, 100);
+        assert!(SortedList::from_list(t.clone()).is_ok());
+        assert!(SortedList::from_list(BTreeList::<usize, 2>::new()).is_ok());
+        let mut unsorted = t;
+        unsorted.swap(40, 41);
+        assert!(SortedList::from_list(unsorted).is_err());
+    }
+
+    #[test]
+    fn insert_remove() {
+        let mut t = SortedList::<usize, 2>::new();
+        let mut v = Vec::new();
+        let mut rng = TestRng::new(5);
+        for _ in 0..2000 {
+            let r = rng.next();
+            let x = r % 500;
+            if r & (1 << 20) == 0 {
+                assert_eq!(t.remove(&x), v.binary_search(&x).ok().map(|i| v.remove(i)));
+            } else {
+                match v.binary_search(&x) {
+                    Ok(_) => assert_eq!(t.insert(x), Err(x)),
+                    Err(i) => {
+                        v.insert(i, x);
+                        assert_eq!(t.insert(x), Ok(i));
+                    }
+                }
+            }
+            assert_eq!(t.len(), v.len());
+        }
+        assert!(t.iter().eq(v.iter()));
+    }
+}
@@ -0,0 +1,587 @@
+use std::{
+    fmt,
+    iter::FromIterator,
+    mem,
+    ops::{Bound, RangeBounds},
+    str,
+};
+
+use crate::{augmented::AugmentedIter, AugmentedList, Measure};
+
+/// The most bytes of text kept in a single chunk.
+const MAX_CHUNK: usize = 128;
+
+/// The lengths of a run of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TextSummary {
+    bytes: usize,
+    chars: usize,
+}
+
+/// The [`Measure`] of the chunks of a [`BTreeString`], summing their lengths in bytes and chars.
+struct TextMeasure;
+
+impl Measure<String> for TextMeasure {
+    type Summary = TextSummary;
+
+    fn empty() -> TextSummary {
+        TextSummary { bytes: 0, chars: 0 }
+    }
+
+    fn measure(chunk: &String) -> TextSummary {
+        TextSummary {
+            bytes: chunk.len(),
+            chars: chunk.chars().count(),
+        }
+    }
+
+    fn combine(left: &TextSummary, right: &TextSummary) -> TextSummary {
+        TextSummary {
+            bytes: left.bytes + right.bytes,
+            chars: left.chars + right.chars,
+        }
+    }
+}
+
+/// A string stored as a tree of chunks of UTF-8 text, for editing long text.
+///
+/// The text is split into chunks of up to 128 bytes held in an [`AugmentedList`], which caches
+/// the number of bytes and chars in each subtree. Inserting and removing text anywhere takes
+/// `O(log(n))` time plus the length of the edit, and byte and char positions are looked up and
+/// converted between in `O(log(n))` time.
+///
+/// Like [`String`], positions are byte indices which must lie on char boundaries. Edits at
+/// positions that don't, or that are past the end of the string, hand back their input instead.
+///
+/// ```
+/// # use btreelist::BTreeString;
+/// let mut text: BTreeString = BTreeString::from("hello world");
+/// text.insert_str(5, ",").unwrap();
+/// text.insert_str(text.len(), "!").unwrap();
+/// assert_eq!(text.to_string(), "hello, world!");
+///
+/// assert_eq!(text.remove_range(5..7), Some(", ".to_string()));
+/// assert_eq!(text, "helloworld!");
+/// assert_eq!(text.char_at(5), Some('w'));
+/// ```
+pub struct BTreeString<const B: usize = 6> {
+    chunks: AugmentedList<String, TextMeasure, B>,
+}
+
+impl<const B: usize> BTreeString<B> {
+    /// Construct a new, empty [`BTreeString`].
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let text: BTreeString = BTreeString::new();
+    /// assert!(text.is_empty());
+    /// ```
+    pub const fn new() -> Self {
+        Self {
+            chunks: AugmentedList::new(),
+        }
+    }
+
+    /// Get the length of the string in bytes.
+    pub fn len(&self) -> usize {
+        self.chunks.summary().bytes
+    }
+
+    /// Get the number of chars in the string.
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let text: BTreeString = BTreeString::from("héllo");
+    /// assert_eq!(text.len(), 6);
+    /// assert_eq!(text.char_count(), 5);
+    /// ```
+    pub fn char_count(&self) -> usize {
+        self.chunks.summary().chars
+    }
+
+    /// Check if the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Create an iterator through the chars of the string.
+    pub fn chars(&self) -> Chars<'_, B> {
+        Chars {
+            chunks: self.chunks.iter(),
+            chunk: "".chars(),
+        }
+    }
+
+    /// Create an iterator through the chunks of text making up the string.
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let text = "abc".repeat(100).parse::<BTreeString>().unwrap();
+    /// assert!(text.chunks().all(|chunk| chunk.len() <= 128));
+    /// assert_eq!(text.chunks().collect::<String>(), "abc".repeat(100));
+    /// ```
+    pub fn chunks(&self) -> StrChunks<'_, B> {
+        StrChunks {
+            chunks: self.chunks.iter(),
+        }
+    }
+
+    /// Check whether `index` is on a char boundary, counting the start and end of the string.
+    pub fn is_char_boundary(&self, index: usize) -> bool {
+        match self.locate(index) {
+            Some((chunk, offset)) => chunk.is_char_boundary(offset),
+            None => index == self.len(),
+        }
+    }
+
+    /// Get the byte at `index`, if it is in the string.
+    pub fn byte_at(&self, index: usize) -> Option<u8> {
+        let (chunk, offset) = self.locate(index)?;
+        chunk.as_bytes().get(offset).copied()
+    }
+
+    /// Get the char starting at byte `index`, if it is a char boundary in the string.
+    pub fn char_at_byte(&self, index: usize) -> Option<char> {
+        let (chunk, offset) = self.locate(index)?;
+        chunk.get(offset..)?.chars().next()
+    }
+
+    /// Get the char at char index `index`, if it is in the string.
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let text: BTreeString = BTreeString::from("héllo");
+    /// assert_eq!(text.char_at(1), Some('é'));
+    /// assert_eq!(text.char_at(2), Some('l'));
+    /// assert_eq!(text.char_at(5), None);
+    /// ```
+    pub fn char_at(&self, index: usize) -> Option<char> {
+        let chunk_index = self.chunks.find_index(|summary| summary.chars > index)?;
+        let before = self.chunks.prefix_summary(chunk_index)?;
+        self.chunks
+            .get(chunk_index)?
+            .chars()
+            .nth(index - before.chars)
+    }
+
+    /// Convert a char index into the byte index the char starts at, returning `None` if it is past
+    /// the end of the string. The char count converts to the length of the string.
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let text: BTreeString = BTreeString::from("héllo");
+    /// assert_eq!(text.char_to_byte(2), Some(3));
+    /// assert_eq!(text.char_to_byte(5), Some(6));
+    /// assert_eq!(text.char_to_byte(6), None);
+    /// ```
+    pub fn char_to_byte(&self, index: usize) -> Option<usize> {
+        if index == self.char_count() {
+            return Some(self.len());
+        }
+        let chunk_index = self.chunks.find_index(|summary| summary.chars > index)?;
+        let before = self.chunks.prefix_summary(chunk_index)?;
+        let (offset, _) = self
+            .chunks
+            .get(chunk_index)?
+            .char_indices()
+            .nth(index - before.chars)?;
+        Some(before.bytes + offset)
+    }
+
+    /// Convert a byte index into the index of the char starting there, returning `None` if it
+    /// isn't a char boundary in the string.
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let text: BTreeString = BTreeString::from("héllo");
+    /// assert_eq!(text.byte_to_char(3), Some(2));
+    /// assert_eq!(text.byte_to_char(2), None);
+    /// ```
+    pub fn byte_to_char(&self, index: usize) -> Option<usize> {
+        if index == self.len() {
+            return Some(self.char_count());
+        }
+        let chunk_index = self.chunks.find_index(|summary| summary.bytes > index)?;
+        let before = self.chunks.prefix_summary(chunk_index)?;
+        let chunk = self.chunks.get(chunk_index)?;
+        Some(before.chars + chunk.get(..index - before.bytes)?.chars().count())
+    }
+
+    /// Insert `string` at byte `index`, or return it back if the index isn't a char boundary in
+    /// the string.
+    pub fn insert_str<'s>(&mut self, index: usize, string: &'s str) -> Result<(), &'s str> {
+        if !self.is_char_boundary(index) {
+            return Err(string);
+        }
+        if string.is_empty() {
+            return Ok(());
+        }
+        let (chunk_index, offset) = if self.is_empty() {
+            self.chunks.push_back(String::new());
+            (0, 0)
+        } else if index == self.len() {
+            let last = self.chunks.len() - 1;
+            (last, self.chunks.get(last).map_or(0, String::len))
+        } else {
+            let chunk_index = self
+                .chunks
+                .find_index(|summary| summary.bytes > index)
+                .expect("index is in the string");
+            let before = self
+                .chunks
+                .prefix_summary(chunk_index)
+                .unwrap_or_else(TextMeasure::empty);
+            (chunk_index, index - before.bytes)
+        };
+        let mut text = self
+            .chunks
+            .update(chunk_index, mem::take)
+            .expect("chunk is in the list");
+        text.insert_str(offset, string);
+        let mut pieces = split_chunks(&text);
+        let _ = self
+            .chunks
+            .set(chunk_index, pieces.next().unwrap_or_default());
+        for (i, piece) in pieces.enumerate() {
+            let _ = self.chunks.insert(chunk_index + 1 + i, piece);
+        }
+        Ok(())
+    }
+
+    /// Insert the char `c` at byte `index`, or return it back if the index isn't a char boundary
+    /// in the string.
+    pub fn insert(&mut self, index: usize, c: char) -> Result<(), char> {
+        let mut buf = [0; 4];
+        self.insert_str(index, c.encode_utf8(&mut buf))
+            .map(|_| ())
+            .map_err(|_| c)
+    }
+
+    /// Append `string` to the end of the string.
+    pub fn push_str(&mut self, string: &str) {
+        let _ = self.insert_str(self.len(), string);
+    }
+
+    /// Append the char `c` to the end of the string.
+    pub fn push(&mut self, c: char) {
+        let _ = self.insert(self.len(), c);
+    }
+
+    /// Remove the text in the byte `range`, returning it, or `None` if the range isn't in the
+    /// string or doesn't start and end on char boundaries.
+    ///
+    /// ```
+    /// # use btreelist::BTreeString;
+    /// let mut text: BTreeString = BTreeString::from("héllo world");
+    /// assert_eq!(text.remove_range(..3), Some("hé".to_string()));
+    /// assert_eq!(text.remove_range(3..=20), None);
+    /// assert_eq!(text.remove_range(1..), Some("lo world".to_string()));
+    /// assert_eq!(text, "l");
+    /// ```
+    pub fn remove_range<R>(&mut self, range: R) -> Option<String>
+    where
+        R: RangeBounds<usize>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.len(),
+        };
+        if start > end || !self.is_char_boundary(start) || !self.is_char_boundary(end) {
+            return None;
+        }
+        let mut removed = String::new();
+        if start == end {
+            return Some(removed);
+        }
+        let first = self.chunks.find_index(|summary| summary.bytes > start)?;
+        let mut offset = start - self.chunks.prefix_summary(first)?.bytes;
+        let mut chunk_index = first;
+        let mut remaining = end - start;
+        while remaining > 0 {
+            let chunk_len = self.chunks.get(chunk_index)?.len();
+            let taken = remaining.min(chunk_len - offset);
+            if taken == chunk_len {
+                removed.push_str(&self.chunks.remove(chunk_index)?);
+            } else {
+                self.chunks.update(chunk_index, |chunk| {
+                    removed.extend(chunk.drain(offset..offset + taken))
+                });
+                chunk_index += 1;
+            }
+            offset = 0;
+            remaining -= taken;
+        }
+        // join up any chunks left small by the removal
+        self.merge_chunks(first);
+        if let Some(before) = first.checked_sub(1) {
+            self.merge_chunks(before);
+        }
+        Some(removed)
+    }
+
+    /// Remove all of the text from the string.
+    pub fn clear(&mut self) {
+        self.chunks = AugmentedList::new();
+    }
+
+    /// Find the chunk that byte `index` is in and the offset into it.
+    fn locate(&self, index: usize) -> Option<(&str, usize)> {
+        let chunk_index = self.chunks.find_index(|summary| summary.bytes > index)?;
+        let before = self.chunks.prefix_summary(chunk_index)?;
+        Some((self.chunks.get(chunk_index)?, index - before.bytes))
+    }
+
+    /// Join the chunk at `index` with the one after it if they fit in a single chunk.
+    fn merge_chunks(&mut self, index: usize) {
+        let fits = match (self.chunks.get(index), self.chunks.get(index + 1)) {
+            (Some(chunk), Some(next)) => chunk.len() + next.len() <= MAX_CHUNK,
+            _ => false,
+        };
+        if fits {
+            let next = self.chunks.remove(index + 1).expect("next chunk exists");
+            self.chunks.update(index, |chunk| chunk.push_str(&next));
+        }
+    }
+}
+
+/// Split `text` into chunks of at most [`MAX_CHUNK`] bytes, on char boundaries.
+fn split_chunks(mut text: &str) -> impl Iterator<Item = String> + '_ {
+    std::iter::from_fn(move || {
+        if text.is_empty() {
+            return None;
+        }
+        let mut len = text.len().min(MAX_CHUNK);
+        while !text.is_char_boundary(len) {
+            len -= 1;
+        }
+        let (chunk, rest) = text.split_at(len);
+        text = rest;
+        Some(chunk.to_owned())
+    })
+}
+
+impl<const B: usize> Clone for BTreeString<B> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+        }
+    }
+}
+
+impl<const B: usize> Default for BTreeString<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const B: usize> fmt::Display for BTreeString<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for chunk in self.chunks() {
+            f.write_str(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const B: usize> fmt::Debug for BTreeString<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+impl<const B: usize> PartialEq for BTreeString<B> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .chunks()
+                .flat_map(str::bytes)
+                .eq(other.chunks().flat_map(str::bytes))
+    }
+}
+
+impl<const B: usize> Eq for BTreeString<B> {}
+
+impl<const B: usize> PartialEq<str> for BTreeString<B> {
+    fn eq(&self, other: &str) -> bool {
+        self.len() == other.len() && self.chunks().flat_map(str::bytes).eq(other.bytes())
+    }
+}
+
+impl<'a, const B: usize> PartialEq<&'a str> for BTreeString<B> {
+    fn eq(&self, other: &&'a str) -> bool {
+        self == *other
+    }
+}
+
+impl<const B: usize> From<&str> for BTreeString<B> {
+    fn from(string: &str) -> Self {
+        let mut text = Self::new();
+        text.chunks.extend(split_chunks(string));
+        text
+    }
+}
+
+impl<const B: usize> From<String> for BTreeString<B> {
+    fn from(string: String) -> Self {
+        Self::from(string.as_str())
+    }
+}
+
+impl<const B: usize> str::FromStr for BTreeString<B> {
+    type Err = std::convert::Infallible;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(string))
+    }
+}
+
+impl<'a, const B: usize> Extend<&'a str> for BTreeString<B> {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        for string in iter {
+            self.push_str(string);
+        }
+    }
+}
+
+impl<const B: usize> Extend<char> for BTreeString<B> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        for c in iter {
+            self.push(c);
+        }
+    }
+}
+
+impl<const B: usize> FromIterator<char> for BTreeString<B> {
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut text = Self::new();
+        text.extend(iter);
+        text
+    }
+}
+
+impl<'a, const B: usize> FromIterator<&'a str> for BTreeString<B> {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut text = Self::new();
+        text.extend(iter);
+        text
+    }
+}
+
+/// An iterator over the chars of a [`BTreeString`].
+#[derive(Debug, Clone)]
+pub struct Chars<'a, const B: usize> {
+    chunks: AugmentedIter<'a, String, TextMeasure, B>,
+    chunk: str::Chars<'a>,
+}
+
+impl<'a, const B: usize> Iterator for Chars<'a, B> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(c) = self.chunk.next() {
+                return Some(c);
+            }
+            self.chunk = self.chunks.next()?.chars();
+        }
+    }
+}
+
+/// An iterator over the chunks of text in a [`BTreeString`].
+#[derive(Debug, Clone)]
+pub struct StrChunks<'a, const B: usize> {
+    chunks: AugmentedIter<'a, String, TextMeasure, B>,
+}
+
+impl<'a, const B: usize> Iterator for StrChunks<'a, B> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.chunks.next().map(String::as_str)
+    }
+}
+
+impl<'a, const B: usize> DoubleEndedIterator for StrChunks<'a, B> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.chunks.next_back().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::btreelist::tests::TestRng;
+
+    fn assert_chunks<const B: usize>(text: &BTreeString<B>) {
+        assert!(text.chunks().all(|c| !c.is_empty() && c.len() <= MAX_CHUNK));
+    }
+
+    #[test]
+    fn edits() {
+        let words = ["a", "héllo ", "wörld", "\u{1F600}", "", "line\n", "xyz"];
+        let mut t = BTreeString::<3>::new();
+        let mut s = String::new();
+        let mut rng = TestRng::new(13);
+        for _ in 0..3000 {
+            let r = rng.next();
+            let index = r % (s.len() + 1);
+            match r % 5 {
+                0..=2 => {
+                    let word = words[(r >> 8) % words.len()];
+                    let string = word.repeat((r >> 12) % 40);
+                    if s.is_char_boundary(index) {
+                        t.insert_str(index, &string).unwrap();
+                        s.insert_str(index, &string);
+                    } else {
+                        assert_eq!(t.insert_str(index, &string), Err(&string[..]));
+                    }
+                }
+                _ => {
+                    let end = index + (r >> 8) % (s.len() - index + 1).min(200);
+                    if s.is_char_boundary(index) && s.is_char_boundary(end) {
+                        let removed = s[index..end].to_string();
+                        s.replace_range(index..end, "");
+                        assert_eq!(t.remove_range(index..end), Some(removed));
+                    } else {
+                        assert_eq!(t.remove_range(index..end), None);
+                    }
+                }
+            }
+            assert_chunks(&t);
+            assert_eq!(t, *s);
+            assert_eq!(t.len(), s.len());
+            assert_eq!(t.char_count(), s.chars().count());
+            let index = r % (s.len() + 1);
+            assert_eq!(t.is_char_boundary(index), s.is_char_boundary(index));
+            assert_eq!(t.byte_at(index), s.as_bytes().get(index).copied());
+            let byte_to_char = s.get(..index).map(|prefix| prefix.chars().count());
+            assert_eq!(t.byte_to_char(index), byte_to_char);
+            if let Some(char_index) = byte_to_char {
+                assert_eq!(t.char_to_byte(char_index), Some(index));
+                assert_eq!(t.char_at(char_index), s[index..].chars().next());
+                assert_eq!(t.char_at_byte(index), s[index..].chars().next());
+            }
+        }
+        assert!(t.chars().eq(s.chars()));
+        assert_eq!(t.to_string(), s);
+        assert_eq!(format!("{:?}", t), format!("{:?}", s));
+    }
+
+    #[test]
+    fn merges_chunks() {
+        let mut t = BTreeString::<2>::from("x".repeat(10 * MAX_CHUNK).as_str());
+        assert_eq!(t.chunks().count(), 10);
+        for _ in 0..9 {
+            t.remove_range(MAX_CHUNK - 10..2 * MAX_CHUNK - 10).unwrap();
+        }
+        assert_chunks(&t);
+        assert_eq!(t, "x".repeat(MAX_CHUNK).as_str());
+        assert_eq!(t.chunks().count(), 1);
+    }
+}